@@ -1,9 +1,12 @@
 use std::env;
 use std::error::Error;
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 enum AppError {
@@ -11,20 +14,99 @@ enum AppError {
     Interrupted(String),
     Validation(String),
     Docker(String),
+    MissingBinary(String),
+    // Mantém a mensagem do erro interno e guarda a causa original para a cadeia de 'source'
+    Caused(Box<AppError>, Box<dyn Error + Send + Sync>),
 }
 
+impl AppError {
+    fn with_source(self, source: impl Into<Box<dyn Error + Send + Sync>>) -> AppError {
+        AppError::Caused(Box::new(self), source.into())
+    }
+
+    // Variante original, ignorando as camadas de causa
+    fn kind(&self) -> &AppError {
+        match self {
+            AppError::Caused(inner, _) => inner.kind(),
+            other => other,
+        }
+    }
+
+    fn map_docker_message(self, f: impl FnOnce(String) -> String) -> AppError {
+        match self {
+            AppError::Docker(message) => AppError::Docker(f(message)),
+            AppError::Caused(inner, source) => {
+                AppError::Caused(Box::new(inner.map_docker_message(f)), source)
+            }
+            other => other,
+        }
+    }
+}
+
+// '{:#}' acrescenta a cadeia de causas, uma por linha
 impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            AppError::Io(err) => write!(f, "Erro de I/O: {}", err),
-            AppError::Interrupted(msg) => write!(f, "Execução Interrompida: {}", msg),
-            AppError::Validation(msg) => write!(f, "Erro de validação: {}", msg),
-            AppError::Docker(msg) => write!(f, "Erro no Docker: {}", msg),
+            AppError::Io(err) => write!(f, "Erro de I/O: {}", redact(&err.to_string()))?,
+            AppError::Interrupted(msg) => write!(f, "Execução Interrompida: {}", redact(msg))?,
+            AppError::Validation(msg) => write!(f, "Erro de validação: {}", redact(msg))?,
+            AppError::Docker(msg) => write!(f, "Erro no Docker: {}", redact(msg))?,
+            AppError::MissingBinary(name) => {
+                write!(f, "Programa '{}' não encontrado no PATH", name)?
+            }
+            AppError::Caused(inner, _) => write!(f, "{}", inner)?,
+        }
+
+        if f.alternate() {
+            let mut source = self.source();
+            while let Some(cause) = source {
+                write!(f, "\n  causa: {}", redact(&cause.to_string()))?;
+                if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+                    write!(f, " [{:?}]", io_error.kind())?;
+                }
+                source = cause.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Io(err) => Some(err),
+            AppError::Caused(_, source) => Some(source.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl Error for AppError {}
+// Linha de comando que falhou ao iniciar, ligada ao erro do sistema operacional
+#[derive(Debug)]
+struct CommandContext {
+    command: String,
+    source: io::Error,
+}
+
+impl std::fmt::Display for CommandContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ao executar '{}'", self.command)
+    }
+}
+
+impl Error for CommandContext {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// traced() registra o último comando de cada thread; o spawn que falhou vem logo depois dele
+fn command_context(source: io::Error) -> CommandContext {
+    CommandContext {
+        command: LAST_COMMAND.with(|last| last.borrow().clone()),
+        source,
+    }
+}
 
 impl From<io::Error> for AppError {
     fn from(err: io::Error) -> AppError {
@@ -48,774 +130,12335 @@ const ENV_FILE: &str = ".env";
 const EXAMPLE_ENV_FILE: &str = "env.example";
 const DEFAULT_CONTAINER_NAME: &str = "dev_container";
 const DEFAULT_SERVER_PORT: u16 = 8000;
+const DEFAULT_SERVER_HTTPS_PORT: u16 = 8443;
 const DEFAULT_DB_PORT: u16 = 3306;
 const DEFAULT_DB_ROOT_PASSWORD: &str = "password";
 const VHOSTS_DIR: &str = "docker/apache/vhosts";
+const CONTAINER_VHOSTS_DIR: &str = "/usr/local/apache2/conf/vhosts";
+const VHOST_INCLUDE_FILE: &str = "projects.conf";
+const REGISTRY_FILE: &str = ".lara-registry";
+const BATCH_CHILD_ENV: &str = "LARA_BATCH_CHILD";
+const BATCH_LOG_DIR: &str = ".lara-batch";
+const SHARED_LOCK_FILE: &str = ".lara.lock";
+const APPLY_JOURNAL_DIR: &str = ".lara-applied";
+const SHARED_LOCK_TIMEOUT_SECS: u64 = 300;
+const USER_CONFIG_FILE: &str = "laravel-maker/config.toml";
+const LABEL_MARKER: &str = "# laravel-maker:";
+const LOOPBACK_ADDRESSES: [&str; 2] = ["127.0.0.1", "::1"];
+const COMPOSE_LOGS_HEADER: &str = "--- docker compose logs --tail 50";
+const COMPOSE_PULL_ARGS: [&str; 2] = ["pull", "--ignore-buildable"];
+const APACHE_RESTART_ARGS: [&str; 3] = ["compose", "restart", "apache"];
+const OPTIMIZE_CACHES: [&str; 3] = ["config", "route", "view"];
+const CONFIG_ENV_KEYS: [&str; 51] = [
+    "CONTAINER_NAME",
+    "SERVER_PORT",
+    "SERVER_HTTPS_PORT",
+    "DB_PORT",
+    "DB_ROOT_PASSWORD",
+    "DB_APP_USER",
+    "DB_APP_PASSWORD",
+    "ARTISAN_STEPS",
+    "BASE_PACKAGE",
+    "BASELINE_SEEDERS",
+    "COMPOSER_BIN",
+    "PHP_BIN",
+    "COMPOSE_PROFILES",
+    "DOCKER_EXEC_MODE",
+    "DOCKER_NETWORK",
+    "ENV_STRATEGY",
+    "EXEC_USER",
+    "INSTALLER",
+    "NAMING_STYLE",
+    "NPM_RETRIES",
+    "OCTANE",
+    "OCTANE_PORT",
+    "OCTANE_SERVER",
+    "PHP_FPM_HOST",
+    "PHP_FPM_PORT",
+    "PHP_FPM_SOCKET",
+    "PATCH_VITE",
+    "PROJECT_APP_ENV",
+    "PROJECT_APP_DEBUG",
+    "PROJECT_APP_SUBDIR",
+    "PROJECT_GROUP",
+    "RETRY_BACKOFF_FACTOR",
+    "SHARED_DB",
+    "SHARED_DB_NAME",
+    "SSL",
+    "TEMPLATE_DIR",
+    "THEME_COLORS",
+    "TRUSTED_PROXIES",
+    "WAIT_HEALTHY",
+    "WAIT_STRATEGY",
+    "WAIT_TIMEOUT",
+    "APACHE_SETTLE_SECS",
+    "DUMP_COMPOSE_LOGS",
+    "EDITOR_CMD",
+    "HOSTS_HELPER",
+    "VHOST_MODE",
+    "VHOST_ALLOW_OVERRIDE",
+    "VHOST_REQUIRE",
+    "VHOST_OPTIONS",
+    "VHOST_SERVER_ADMIN",
+    "VHOST_LOG_DIR",
+];
 const DEFAULT_LARAVEL_VERSION: u8 = 12;
 const MINIMAL_LARAVEL_VERSION: u8 = 10;
+const MAX_PROJECT_ROOT_DEPTH: usize = 5;
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 60;
+const MIN_FREE_DISK_MB: u64 = 1024;
+const DEFAULT_PHP_FPM_HOST: &str = "php";
+const DEFAULT_PHP_FPM_PORT: u16 = 9000;
+const DEFAULT_OCTANE_PORT: u16 = 8000;
+const DEFAULT_OCTANE_SERVER: &str = "frankenphp";
+const PACKAGIST_LARAVEL_URL: &str = "https://repo.packagist.org/p2/laravel/laravel.json";
+const VERSION_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/srRodolfo/dev-container/releases/latest";
+const RELEASES_PAGE_URL: &str = "https://github.com/srRodolfo/dev-container/releases";
+const CONTAINER_WEB_ROOT: &str = "/var/www/html";
+const DEFAULT_BASE_PACKAGE: &str = "laravel/laravel";
+const DEFAULT_NPM_RETRIES: u32 = 3;
+const DEFAULT_SHARED_DB_NAME: &str = "laravel";
+const MIN_COMPOSER_VERSION: (u32, u32) = (2, 2);
+const SPEC_SCHEMA: &str = "Campos do spec: name (obrigatório), laravel_version, package, label, php_container, php_service, node_container, template, out_dir, compose_profile, http_port, octane, ssl, seed, no_deps, skip_npm, no_migrate, no_vite, force, naming_style (kebab|snake|preserve). Formato plano: uma linha 'chave = valor' (.toml) ou 'chave: valor' (.yaml) por campo, sem tabelas, listas ou aninhamento; repita compose_profile para vários perfis.";
+const HELP_TEXT: &str = "Uso: lara [subcomando] [opções]
+
+Subcomandos:
+  init                      cria o esqueleto do dev-container no diretório atual
+  attach <nome>             configura um projeto Laravel já existente em src/
+  reconfigure <nome>        reaplica .env e config:clear em um projeto existente
+  status <nome>             confere vhost, hosts, contêineres e .env de um projeto
+  list / delete [nome]      lista ou remove projetos (filtro com --label)
+                            delete --with-data apaga também o banco; --with-volumes, os volumes Compose do projeto
+  validate-config <arquivo> valida um spec sem criar nada
+  batch <spec>...           cria vários projetos a partir de specs (--concurrency N em paralelo)
+  clean                     remove vhosts e registros de projetos que não existem mais
+
+Opções frequentes:
+  --from-spec <arquivo>     lê nome, versão e opções de um spec plano (.toml/.yaml, 'chave = valor' por linha)
+  --name-from-git           usa o branch atual (ou o repositório) como nome do projeto
+  --yes, -y                 responde sim às confirmações
+  --no-hosts                não altera o /etc/hosts
+  --ipv6                    grava também '::1 <host>' no /etc/hosts
+  --require-node            falha se não houver contêiner do Node (senão npm e vite são ignorados)
+  --php-ini chave=valor     ajusta o PHP do projeto via public/.user.ini (repetível)
+  --compose-build           reconstrói as imagens antes do 'up' (--build-no-cache ignora o cache)
+  --offline                 simula as etapas que dependem da rede
+  --verbose                 mostra os comandos docker e a cadeia de causas dos erros
+  --explain                 explica cada etapa antes de executá-la
+  --config-dump             mostra a configuração resolvida e a origem de cada chave
+  --list-services           lista os serviços do compose e o contêiner de cada um
+  --env-report [--json]     imprime um retrato do ambiente para anexar em issues
+  --check-update            verifica se há uma versão mais nova do lara publicada
+  --print-vhost             imprime o vhost que seria gerado e sai sem alterar nada
+  --dry-run                 lista as ações que seriam aplicadas, em ordem, sem executá-las
+  --plan-only               imprime o mesmo plano em JSON
+
+Estratégia de espera (--wait-strategy ou WAIT_STRATEGY, limite em WAIT_TIMEOUT):
+  ps      só confere se o contêiner aparece no 'docker ps'. Mais rápido, mas um
+          contêiner em execução ainda pode não aceitar conexões.
+  exec    executa um comando dentro do contêiner ('true' no PHP, 'mariadb-admin ping'
+          no banco). Confirma que o processo responde, sem depender de healthcheck.
+  health  espera o healthcheck do compose ficar 'healthy' (--wait-healthy). É o
+          sinal mais fiel, mas só existe se a imagem ou o compose definirem um.
+  auto    (padrão) usa health quando há healthcheck e exec nos demais casos.
+
+Use --config-dump para ver todas as variáveis do .env reconhecidas e seus valores.";
+const KNOWN_APP_ENVS: [&str; 3] = ["local", "staging", "production"];
+const NPM_RETRY_DELAY_SECS: u64 = 5;
+const RUN_RETRY_DELAY_SECS: u64 = 10;
+const HTTP_VERIFY_ATTEMPTS: u32 = 5;
+const HTTP_VERIFY_DELAY_SECS: u64 = 2;
+const CONTAINER_START_ATTEMPTS: u32 = 3;
+const CONTAINER_START_DELAY_SECS: u64 = 3;
+const APACHE_RESTART_ATTEMPTS: u32 = 5;
+const DEFAULT_APACHE_SETTLE_SECS: u64 = 3;
+const APACHE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_RETRY_BACKOFF_FACTOR: u32 = 2;
+const MAX_RETRY_DELAY_SECS: u64 = 60;
+const MIN_REDACTED_SECRET_LEN: usize = 4;
+const DEFAULT_VHOST_HTTP_PORT: u16 = 80;
+const DEFAULT_VHOST_HTTPS_PORT: u16 = 443;
+const DEFAULT_VHOST_SERVER_ADMIN: &str = "webmaster@localhost";
+const DEFAULT_VHOST_LOG_DIR: &str = "logs";
+const COMPOSE_FILE_NAMES: [&str; 4] = [
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
 
 #[derive(Debug)]
 struct AppConfig {
     php_container_name: String,
+    php_service: String,
     node_container_name: String,
+    db_container_name: String,
+    apache_container_name: String,
+    auto_container_name: Option<String>,
+    docker_network: Option<String>,
     db_root_password: String,
     server_port: u16,
     db_port: u16,
+    db_container_port: u16,
+    patch_vite: bool,
+    template_dir: Option<PathBuf>,
+    overwrite_template: bool,
+    wait_strategy: WaitStrategy,
+    wait_timeout_secs: u64,
+    apache_settle_secs: u64,
+    strict: bool,
+    php_fpm_handler: String,
+    octane: bool,
+    octane_target: String,
+    octane_server: String,
+    http_port: u16,
+    force: bool,
+    assume_yes: bool,
+    out_dir: Option<PathBuf>,
+    skip_deps: bool,
+    offline: bool,
+    name_from_git: bool,
+    skip_npm: bool,
+    require_node: bool,
+    skip_migrate: bool,
+    seed: bool,
+    allow_remote_db: bool,
+    no_app_seeders: bool,
+    baseline_seeders: Vec<String>,
+    artisan_steps: Option<Vec<String>>,
+    rollback: bool,
+    compose_profiles: Vec<String>,
+    dump_compose_logs: bool,
+    shared_db: Option<String>,
+    pull: bool,
+    pull_ignore_failure: bool,
+    compose_build: bool,
+    build_no_cache: bool,
+    with_data: bool,
+    with_volumes: bool,
+    optimize: bool,
+    env_strategy: EnvStrategy,
+    naming_style: NamingStyle,
+    app_env: Option<String>,
+    app_debug: Option<bool>,
+    ssl: bool,
+    behind_proxy: bool,
+    trusted_proxies: String,
+    https_port: u16,
+    server_https_port: u16,
+    label: Option<String>,
+    auto_update_composer: bool,
+    no_scripts: bool,
+    hosts_helper: Option<String>,
+    no_hosts: bool,
+    hosts_ipv6: bool,
+    vhost_allow_override: String,
+    vhost_require: String,
+    vhost_options: Option<String>,
+    vhost_server_admin: String,
+    vhost_log_dir: Option<String>,
+    vhost_mode: VhostMode,
+    installer: Installer,
+    app_subdir: Option<String>,
+    project_group: Option<String>,
+    create_db_user: bool,
+    exec_user: Option<String>,
+    fix_ownership: bool,
+    with_testing_env: bool,
+    php_ini: Vec<(String, String)>,
+    with_test_db: bool,
+    sync_env_example: bool,
+    defer_restart: bool,
+    resume: bool,
+    no_restart_on_resume: bool,
+    parallel: bool,
+    stop_after: bool,
+    stop_if_started: bool,
+    with_make: bool,
+    no_config_clear: bool,
+    db_app_user: Option<String>,
+    db_app_password: Option<String>,
+    base_package: String,
+    npm_retries: u32,
+    composer_bin: String,
+    php_bin: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Tone {
+    Step,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Debug)]
+struct Theme {
+    enabled: bool,
+    step: String,
+    success: String,
+    warning: String,
+    error: String,
+}
+
+// Regravado depois que o .env e a configuração do usuário são lidos (THEME_COLORS)
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
+static STEP_TIMINGS: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+static STEP_STARTS: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static PHP_SERVICE: OnceLock<String> = OnceLock::new();
+static OVERALL_BUDGET: OnceLock<(Instant, Duration)> = OnceLock::new();
+static DOCKER_EXEC_MODE: OnceLock<String> = OnceLock::new();
+static EXEC_USER: OnceLock<Vec<String>> = OnceLock::new();
+static RETRY_BACKOFF_FACTOR: OnceLock<u32> = OnceLock::new();
+static VERBOSE_DOCKER: OnceLock<bool> = OnceLock::new();
+thread_local! {
+    static LAST_COMMAND: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+}
+static EXPLAIN_STEPS: OnceLock<bool> = OnceLock::new();
+static OFFLINE: OnceLock<bool> = OnceLock::new();
+static SECRETS: OnceLock<Vec<String>> = OnceLock::new();
+static PROGRESS_EVENTS: OnceLock<Mutex<fs::File>> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct CliOptions {
+    open_editor: bool,
+    open_browser: bool,
+    browser: Option<String>,
+    no_vite: bool,
+    no_color: bool,
+    verbose_docker: bool,
+    explain: bool,
+    template_dir: Option<PathBuf>,
+    overwrite_template: bool,
+    wait_healthy: bool,
+    wait_strategy: Option<String>,
+    help: bool,
+    strict: bool,
+    json: bool,
+    octane: bool,
+    http_port: Option<u16>,
+    https_port: Option<u16>,
+    force: bool,
+    assume_yes: bool,
+    version_list: bool,
+    env_report: bool,
+    check_update: bool,
+    list_services: bool,
+    out_dir: Option<PathBuf>,
+    php_container: Option<String>,
+    php_service: Option<String>,
+    node_container: Option<String>,
+    verify: bool,
+    no_deps: bool,
+    skip_npm: bool,
+    require_node: bool,
+    no_migrate: bool,
+    minimal: bool,
+    offline: bool,
+    name_from_git: bool,
+    seed: bool,
+    allow_remote_db: bool,
+    no_app_seeders: bool,
+    no_rollback: bool,
+    compose_profiles: Vec<String>,
+    dump_compose_logs: bool,
+    shared_db: bool,
+    pull: bool,
+    pull_ignore_failure: bool,
+    compose_build: bool,
+    build_no_cache: bool,
+    with_data: bool,
+    with_volumes: bool,
+    timeout_overall: Option<u64>,
+    optimize: bool,
+    env_strategy: Option<String>,
+    naming_style: Option<String>,
+    attach: bool,
+    app_env: Option<String>,
+    app_debug: Option<bool>,
+    ssl: bool,
+    behind_proxy: bool,
+    trusted_proxies: Option<String>,
+    label: Option<String>,
+    env_override: bool,
+    config_dump: bool,
+    dump_env_diff: bool,
+    auto_update_composer: bool,
+    no_scripts: bool,
+    hosts_helper: Option<String>,
+    no_hosts: bool,
+    hosts_ipv6: bool,
+    from_spec: Option<PathBuf>,
+    export_spec: Option<PathBuf>,
+    retries: u32,
+    validate_only: bool,
+    print_vhost: bool,
+    dry_run: bool,
+    plan_only: bool,
+    vhost_allow_override: Option<String>,
+    vhost_require: Option<String>,
+    vhost_options: Option<String>,
+    vhost_server_admin: Option<String>,
+    vhost_log_dir: Option<String>,
+    vhost_mode: Option<String>,
+    installer: Option<String>,
+    auto_name_containers: bool,
+    network: Option<String>,
+    shared_vhost_logs: bool,
+    app_subdir: Option<String>,
+    project_group: Option<String>,
+    command: Option<Subcommand>,
+    create_db_user: bool,
+    exec_user: Option<String>,
+    match_host_user: bool,
+    fix_ownership: bool,
+    with_testing_env: bool,
+    php_ini: Vec<String>,
+    with_test_db: bool,
+    sync_env_example: bool,
+    defer_restart: bool,
+    resume: bool,
+    no_restart_on_resume: bool,
+    parallel: bool,
+    concurrency: Option<usize>,
+    stop_after: bool,
+    stop_if_started: bool,
+    with_make: bool,
+    no_config_clear: bool,
+    events_path: Option<PathBuf>,
+    package: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Subcommand {
+    Init,
+    Clean,
+    Attach(String),
+    Reconfigure(String),
+    Status(String),
+    List,
+    Delete(Option<String>),
+    ValidateConfig(PathBuf),
+    Batch(Vec<PathBuf>),
 }
 
 #[derive(Debug)]
 struct ProjectInput {
     project_name: String,
     project_host: String,
+    project_dir: String,
     project_path: String,
     laravel_version: String,
+    octane: bool,
+    label: Option<String>,
 }
 
-fn run() -> Result<(), AppError> {
-    println!("--- Dev Container Laravel Maker ---");
+// Só a criação de projeto anuncia o fim; relatórios e subcomandos deixam o stdout só com a saída deles
+enum RunOutcome {
+    Created { json: bool },
+    Done,
+}
 
-    let env_path_option = find_env_path(ENV_FILE);
-    let example_env_path_option = find_env_path(EXAMPLE_ENV_FILE);
+fn run() -> Result<RunOutcome, AppError> {
+    let run_started = Instant::now();
+    let mut prepared = match prepare_run(run_started)? {
+        Some(prepared) => prepared,
+        None => return Ok(RunOutcome::Done),
+    };
+    let attempts = prepared.options.retries + 1;
+    let base_delay = Duration::from_secs(RUN_RETRY_DELAY_SECS);
 
-    let env_path = ensure_env_file_exists(env_path_option, example_env_path_option)?;
+    retry_with_backoff(attempts, base_delay, |attempt| {
+        if attempt > 1
+            && let Ok(mut timings) = STEP_TIMINGS.lock()
+        {
+            timings.clear();
+        }
 
-    dotenv::from_path(&env_path).ok();
+        let result = create_and_finish(&mut prepared, attempt < attempts);
+        if let Err(error) = &result
+            && let AppError::Docker(message) = error.kind()
+            && attempt < attempts
+        {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Tentativa {} de {} falhou: {}. Repetindo em {}s...",
+                        attempt,
+                        attempts,
+                        message,
+                        backoff_delay(base_delay, attempt).as_secs()
+                    )
+                )
+            );
+        }
+        result
+    })?;
 
-    let config = get_app_config()?;
-    let input = get_user_input()?;
+    Ok(RunOutcome::Created {
+        json: prepared.options.json,
+    })
+}
 
-    execute_laravel_creation(&input, &config)?;
+fn retry_backoff_factor() -> u32 {
+    *RETRY_BACKOFF_FACTOR.get_or_init(|| match env::var("RETRY_BACKOFF_FACTOR") {
+        Ok(factor_str) => match factor_str.trim().parse::<u32>() {
+            Ok(factor) if factor > 0 => factor,
+            _ => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "RETRY_BACKOFF_FACTOR ('{}') inválido. Usando default: {}",
+                            factor_str.trim(),
+                            DEFAULT_RETRY_BACKOFF_FACTOR
+                        )
+                    )
+                );
+                DEFAULT_RETRY_BACKOFF_FACTOR
+            }
+        },
+        Err(_) => DEFAULT_RETRY_BACKOFF_FACTOR,
+    })
+}
 
-    configure_and_initialize_laravel(&input, &config)?;
+fn backoff_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let multiplier = retry_backoff_factor()
+        .checked_pow(attempt.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    base_delay
+        .saturating_mul(multiplier)
+        .min(Duration::from_secs(MAX_RETRY_DELAY_SECS))
+}
 
-    create_vhost_file(&input)?;
+// Só erros do Docker são tratados como transitórios; os demais encerram na hora.
+fn retry_with_backoff<T>(
+    attempts: u32,
+    base_delay: Duration,
+    mut op: impl FnMut(u32) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let attempts = attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op(attempt) {
+            Err(e) if matches!(e.kind(), AppError::Docker(_)) && attempt < attempts => {
+                std::thread::sleep(backoff_delay(base_delay, attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
 
-    update_etc_hosts(&input)?;
+// Tudo o que pergunta ou valida roda uma vez só; as novas tentativas repetem apenas a criação
+fn prepare_run(run_started: Instant) -> Result<Option<PreparedRun>, AppError> {
+    let mut options = parse_cli_args(env::args().skip(1))?;
 
-    restart_apache_container()?;
+    let spec = match options.from_spec.clone() {
+        Some(spec_path) => Some(apply_project_spec(&spec_path, &mut options)?),
+        None => None,
+    };
 
-    println!("\n---");
-    println!(
-        "Novo projeto Laravel '{}' criado com sucesso!",
-        input.project_name
-    );
-    println!(
-        "Domínio de acesso: http://{}:{}",
-        input.project_host, config.server_port
-    );
-    println!("---");
-    println!("O projeto está pronto. Você já pode acessá-lo pelo navegador.");
+    // As cores do shell valem desde já; o .env do projeto pode trocá-las logo abaixo
+    init_theme(options.no_color, false);
+    // Com --print-vhost, --plan-only ou --json na criação, o stdout fica reservado à saída para permitir '> arquivo'
+    let json_creation = options.json && options.command.is_none() && !options.env_report;
+    let diverted_stdout = if options.print_vhost || options.plan_only || json_creation {
+        divert_stdout_to_stderr()
+    } else {
+        None
+    };
+    let _ = VERBOSE_DOCKER.set(options.verbose_docker);
+    let _ = EXPLAIN_STEPS.set(options.explain);
+    let _ = OFFLINE.set(options.offline);
 
-    Ok(())
-}
+    if let Some(events_path) = &options.events_path
+        && PROGRESS_EVENTS.get().is_none()
+    {
+        let events = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(events_path)
+            .map_err(|e| {
+                AppError::Validation(format!(
+                    "Não foi possível abrir o destino de eventos '{}': {}",
+                    events_path.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+        let _ = PROGRESS_EVENTS.set(Mutex::new(events));
+    }
 
-fn find_env_path(filename: &str) -> Option<PathBuf> {
-    let path_dot = PathBuf::from(filename);
-    if path_dot.exists() {
-        return Some(path_dot);
+    if options.help {
+        println!("{}", HELP_TEXT);
+        return Ok(None);
     }
 
-    let path_dot_dot = PathBuf::from("..").join(filename);
-    if path_dot_dot.exists() {
-        return Some(path_dot_dot);
+    if options.version_list {
+        return print_laravel_version_list().map(|()| None);
     }
 
-    None
-}
+    if options.env_report {
+        return print_env_report(options.json).map(|()| None);
+    }
 
-fn ensure_env_file_exists(
-    env_path_option: Option<PathBuf>,
-    example_env_path_option: Option<PathBuf>,
-) -> Result<PathBuf, AppError> {
-    if let Some(env_path) = env_path_option {
-        println!("Arquivo .env encontrado.");
-        return Ok(env_path);
+    if options.check_update {
+        print_update_check();
+        return Ok(None);
     }
 
-    println!("Arquivo .env não encontrado. Tentando criar a partir do env.example... ");
+    if options.command == Some(Subcommand::Init) {
+        return init_project_layout().map(|()| None);
+    }
+    if let Some(Subcommand::ValidateConfig(spec_path)) = &options.command {
+        return validate_spec_file(spec_path).map(|()| None);
+    }
 
-    let example_env_path = match example_env_path_option {
-        Some(path) => path,
-        None => {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Nem o .env, nem o . env.example foram encontrados. Verifique a estrutura do projeto."),
-            ).into());
-        }
-    };
+    println!(
+        "{}",
+        paint(Tone::Step, "--- Dev Container Laravel Maker ---")
+    );
 
-    let env_path = example_env_path.with_file_name(".env");
+    let env_path_option = find_env_path(ENV_FILE);
+    let example_env_path_option = find_env_path(EXAMPLE_ENV_FILE);
 
-    match fs::copy(&example_env_path, &env_path) {
-        Ok(_) => {
-            println!(
-                "Copiado {} para {} ",
-                example_env_path.display(),
-                env_path.display()
-            );
+    ensure_expected_layout(env_path_option.is_some() || example_env_path_option.is_some())?;
 
-            loop {
-                println!("\n--- Configuração Inicial ---");
-                println!("O arquivo de configuração .env foi criado com as variáveis padrão.");
-                print!("Deseja prosseguir com a configuração padrão do .env ? (Y/n, ENTER=Y): ");
-                io::stdout().flush()?;
+    let env_path = ensure_env_file_exists(env_path_option, example_env_path_option)?;
 
-                let mut buffer = String::new();
-                io::stdin().read_line(&mut buffer)?;
-                let choice = buffer.trim().to_lowercase();
+    let shell_keys: Vec<String> = env::vars_os()
+        .filter_map(|(key, _)| key.into_string().ok())
+        .collect();
 
-                if choice.is_empty() || choice == "y" {
-                    println!("Continuando com as configurações padrão do .env.");
-                    return Ok(env_path);
-                } else if choice == "n" {
-                    println!(
-                        "\nProcesso interrompido. Edite o arquivo .env e execute o programa novamente."
-                    );
-                    println!("Pressione [Enter] para sair...");
-                    io::stdout().flush()?;
-                    let mut exit_buffer = String::new();
-                    io::stdin().read_line(&mut exit_buffer)?;
-
-                    return Err(AppError::Interrupted(
-                        "O usuário optou por configurar o .env manualmente.".to_string(),
-                    ));
-                } else {
-                    println!("Escolha inválida");
-                }
-            }
-        }
-        Err(e) => {
-            return Err(e.into());
-        }
-    }
-}
+    load_env_file(&env_path, options.strict, options.env_override)?;
+    let user_keys = load_user_config()?;
+    init_theme(options.no_color, true);
 
-fn get_app_config() -> Result<AppConfig, AppError> {
-    println!("Carregando configurações do .env...");
+    if options.config_dump {
+        print_config_dump(&env_path, &shell_keys, &user_keys, options.env_override);
+        return Ok(None);
+    }
 
-    let container_name = match env::var("CONTAINER_NAME") {
-        Ok(name) if !name.trim().is_empty() => name.trim().to_string(),
-        _ => {
-            println!(
-                "CONTAINER_NAME não encontrado ou vazio. Usando default: '{}'",
-                DEFAULT_CONTAINER_NAME
-            );
-            DEFAULT_CONTAINER_NAME.to_string()
-        }
-    };
+    let config = get_app_config(&options)?;
+    let _ = PHP_SERVICE.set(config.php_service.clone());
+    register_secrets(&config);
+    if let Some(user) = &config.exec_user {
+        let _ = EXEC_USER.set(vec!["-u".to_string(), user.clone()]);
+    }
 
-    let server_port = match env::var("SERVER_PORT") {
-        Ok(port_str) => match port_str.trim().parse::<u16>() {
-            Ok(port) => port,
-            Err(_) => {
-                println!(
-                    "SERVER_PORT ('{}') inválido. Usando default: {}",
-                    port_str.trim(),
-                    DEFAULT_SERVER_PORT
-                );
-                DEFAULT_SERVER_PORT
-            }
-        },
-        Err(_) => {
-            println!(
-                "SERVER_PORT não encontrado. Usando default: {}",
-                DEFAULT_SERVER_PORT
-            );
-            DEFAULT_SERVER_PORT
+    let subcommand_result = match &options.command {
+        Some(Subcommand::Clean) => clean_orphaned_projects(&config),
+        Some(Subcommand::Attach(name)) => attach_existing_project(name, &config),
+        Some(Subcommand::Reconfigure(name)) => reconfigure_existing_project(name, &config),
+        Some(Subcommand::Status(name)) => print_project_status(name, &config),
+        Some(Subcommand::List) => list_projects(&config),
+        Some(Subcommand::Delete(name)) => delete_projects(name.as_deref(), &config),
+        Some(Subcommand::Batch(specs)) => {
+            run_batch(specs, options.concurrency.unwrap_or(1), &config)
         }
+        Some(Subcommand::Init) | Some(Subcommand::ValidateConfig(_)) | None => Ok(()),
     };
+    if options.command.is_some() {
+        return subcommand_result.map(|()| None);
+    }
 
-    let db_port = match env::var("DB_PORT") {
-        Ok(port_str) => match port_str.trim().parse::<u16>() {
-            Ok(port) => port,
-            Err(_) => {
-                println!(
-                    "DB_PORT ('{}') inválido. Usando default: {}",
-                    port_str.trim(),
-                    DEFAULT_DB_PORT
-                );
-                DEFAULT_DB_PORT
-            }
-        },
-        Err(_) => {
-            println!(
-                "DB_PORT não encontrado. Usando default: {}",
-                DEFAULT_DB_PORT
-            );
-            DEFAULT_DB_PORT
-        }
-    };
+    if options.list_services {
+        return list_compose_services(&config).map(|()| None);
+    }
 
-    let php_container_name = format!("{}_php", container_name);
-    let node_container_name = format!("{}_node", container_name);
+    if let Some(secs) = options.timeout_overall {
+        let _ = OVERALL_BUDGET.set((run_started, Duration::from_secs(secs)));
+    }
 
-    let db_root_password = match env::var("DB_ROOT_PASSWORD") {
-        Ok(password) if !password.trim().is_empty() => password.trim().to_string(),
-        _ => {
-            println!(
-                "MYSQL_ROOT_PASSWORD não encontrada ou vazia. Usando default: '{}'",
-                DEFAULT_DB_ROOT_PASSWORD
-            );
-            DEFAULT_DB_ROOT_PASSWORD.to_string()
-        }
-    };
+    if options.validate_only && config.force {
+        return Err(AppError::Validation(
+            "--validate-only não pode ser combinado com --force.".to_string(),
+        ));
+    }
 
-    println!(
-        "Configurações base carregadas (Contêiner PHP: {}, Porta Apache: {})",
-        php_container_name, server_port
-    );
+    if options.print_vhost && config.force {
+        return Err(AppError::Validation(
+            "--print-vhost não pode ser combinado com --force.".to_string(),
+        ));
+    }
 
-    Ok(AppConfig {
-        php_container_name,
-        node_container_name,
-        db_root_password,
-        server_port,
-        db_port,
-    })
-}
+    let planning = options.dry_run || options.plan_only;
+    if planning && config.force {
+        return Err(AppError::Validation(
+            "--dry-run e --plan-only não podem ser combinados com --force.".to_string(),
+        ));
+    }
 
-fn get_user_input() -> Result<ProjectInput, AppError> {
-    let project_name = 'project_loop: loop {
-        print!("Digite o NOME do novo projeto (ex: example-app): ");
-        io::stdout().flush()?;
+    if !options.print_vhost && !planning {
+        check_compose_project_collision(&config);
+        check_disk_space(&config)?;
+    }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let raw_name = input.trim().to_lowercase();
+    let input = match spec {
+        Some(spec) => input_from_spec(spec, &config)?,
+        None => get_user_input(&config)?,
+    };
 
-        if raw_name.is_empty() {
-            eprintln!("O nome do projeto não pode ser vazio.");
-            continue;
-        }
-        let name = format_to_kebab_case(&raw_name);
+    if options.validate_only {
+        return validate_generated_config(&input, &config).map(|()| None);
+    }
 
-        if name.is_empty() {
-            eprintln!(
-                "A entrada original resultou em um nome vazio após a formatação. Tente novamente."
-            );
-            continue;
-        }
+    if options.print_vhost {
+        print_to_real_stdout(diverted_stdout, &vhost_file_content(&input, &config));
+        return Ok(None);
+    }
 
-        if name != raw_name {
-            println!(
-                "Formatado: '{}' alterado para '{}' (kebab-case).",
-                raw_name, name
-            );
+    if planning {
+        let plan = plan_project_actions(&input, &config);
+        if options.plan_only {
+            print_to_real_stdout(diverted_stdout, &render_plan_json(&input, &plan));
+        } else {
+            print_plan(&input, &plan);
         }
+        return Ok(None);
+    }
 
-        let project_path_check = PathBuf::from(format!("../src/{}", name));
-        if project_path_check.exists() {
-            eprintln!("ERRO DE VALIDAÇÃO: O diretório ../src/{} já existe.", name);
+    Ok(Some(PreparedRun {
+        options,
+        config,
+        input,
+        json_stdout: diverted_stdout,
+    }))
+}
 
-            loop {
-                print!("Deseja tentar outro nome de projeto? (Y/n, ENTER=Y): ");
-                io::stdout().flush()?;
+struct PreparedRun {
+    options: CliOptions,
+    config: AppConfig,
+    input: ProjectInput,
+    json_stdout: Option<i32>,
+}
 
-                let mut decision = String::new();
-                io::stdin().read_line(&mut decision)?;
-                let choice = decision.trim().to_lowercase();
+fn create_and_finish(run: &mut PreparedRun, retry_pending: bool) -> Result<(), AppError> {
+    let json_stdout = run.json_stdout;
+    let options = &run.options;
+    let config = &run.config;
+    let input = &mut run.input;
 
-                if choice.is_empty() || choice == "y" {
-                    continue 'project_loop;
-                } else if choice == "n" {
-                    return Err(AppError::Interrupted(
-                        "O usuário optou por encerrar a aplicação.".to_string(),
-                    ));
-                } else {
-                    eprintln!("Escolha inválida ('{}'). Digite 'Y' ou 'n'.", choice);
-                }
-                continue;
-            }
+    // O mesmo plano do --dry-run é o que a execução aplica
+    let mut plan = plan_project_actions(input, config);
+    let mut applied = PlanApplication::default();
+    if let Err(e) = apply_project_plan(&mut plan, &mut applied, input, config, options) {
+        let will_retry = retry_pending && matches!(e.kind(), AppError::Docker(_));
+        if (will_retry || matches!(e.kind(), AppError::Interrupted(_))) && config.rollback {
+            undo_applied_actions(&applied, input, config);
         }
-        break name;
-    };
+        return Err(e);
+    }
 
-    let laravel_version = loop {
-        println!("---");
-        println!(
-            "Versões de Laravel Comuns: {} (LTS), 11 (Mínimo aceito: {})",
-            DEFAULT_LARAVEL_VERSION, MINIMAL_LARAVEL_VERSION
-        );
-        print!(
-            "Digite a versão do Laravel (ex: {ver}, ENTER={ver}, Min={min}): ",
-            ver = DEFAULT_LARAVEL_VERSION,
-            min = MINIMAL_LARAVEL_VERSION
-        );
-        io::stdout().flush()?;
+    with_shared_lock(|| {
+        record_project_in_registry(input);
+        Ok(())
+    })?;
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let version_str = input.trim().to_string();
+    if let Some(spec_path) = &options.export_spec {
+        export_project_spec(spec_path, input, config)?;
+    }
 
-        if version_str.is_empty() {
-            let default_version = DEFAULT_LARAVEL_VERSION.to_string();
-            println!("Usando default: {}.", default_version);
-            break default_version;
-        }
+    write_project_info(input, config, options.json);
 
-        match version_str.parse::<u8>() {
-            Ok(version_num) => {
-                if version_num >= MINIMAL_LARAVEL_VERSION {
-                    break version_num.to_string();
-                } else {
-                    eprintln!(
-                        "ERRO: A versão informada ({}) é inválida. O campo é obrigatório e a versão mínima aceita é {}.",
-                        version_num, MINIMAL_LARAVEL_VERSION
-                    );
-                    continue;
-                }
-            }
-            Err(_) => {
-                eprintln!(
-                    "ERRO: O dado informado ('{}') é inválido. Por favor, digite apenas o número inteiro da versão (ex: {ver}, ENTER={ver}).",
-                    version_str,
-                    ver = DEFAULT_LARAVEL_VERSION
-                );
-                continue;
-            }
-        }
-    };
+    if config.with_make {
+        write_project_makefile(input, config);
+    }
 
-    let project_host = format!("{}.test", project_name);
-    let project_path = format!("../src/{}", project_name);
+    if applied.restart_pending {
+        timed_step("restart", || restart_apache_container(config))?;
+    }
 
-    println!("---");
+    timed_step("health", || verify_final_state(input, config))?;
+    clear_apply_journal(input);
+
+    println!("\n---");
     println!(
-        "Entradas válidas: Projeto='{}', Host='{}', Versão='{}'",
-        project_name, project_host, laravel_version
+        "{}",
+        paint(
+            Tone::Success,
+            &format!(
+                "Novo projeto Laravel '{}' criado com sucesso!",
+                input.project_name
+            )
+        )
     );
-    println!("---");
-
-    Ok(ProjectInput {
-        project_name,
-        project_host,
-        project_path,
-        laravel_version,
-    })
-}
-
-fn format_to_kebab_case(input: &str) -> String {
-    let lower = input.to_lowercase();
-    let mut result = lower
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() || c == '-' {
-                c
+    println!("Domínio de acesso: {}", project_url(input, config));
+    println!(
+        "vite.config.js ajustado: {}",
+        if config.patch_vite { "sim" } else { "não" }
+    );
+    println!(
+        "Dependências atualizadas (composer update): {}",
+        if config.offline {
+            "não (--offline)"
+        } else if config.skip_deps {
+            "não (--no-deps)"
+        } else {
+            "sim"
+        }
+    );
+    if let Some(steps) = &config.artisan_steps {
+        println!("Comandos Artisan (ARTISAN_STEPS): {}", steps.join(", "));
+    } else {
+        println!(
+            "Migrations: {}",
+            if config.skip_migrate {
+                "não executadas (--no-migrate)".to_string()
+            } else if config.no_app_seeders {
+                format!(
+                    "{} (seeders base: {})",
+                    migration_command(config),
+                    if config.baseline_seeders.is_empty() {
+                        "nenhum".to_string()
+                    } else {
+                        config.baseline_seeders.join(", ")
+                    }
+                )
             } else {
-                ' '
+                migration_command(config).to_string()
             }
-        })
-        .collect::<String>();
-
-    result = result.split_whitespace().collect::<Vec<&str>>().join("-");
+        );
+    }
+    println!(
+        "Bancos de dados: {}",
+        project_databases(input, config).join(", ")
+    );
+    if config.no_config_clear {
+        println!("config:clear: não executado (--no-config-clear)");
+    }
+    println!("---");
+    println!(
+        "{}",
+        paint(
+            Tone::Success,
+            "O projeto está pronto. Você já pode acessá-lo pelo navegador."
+        )
+    );
 
-    while result.contains("--") {
-        result = result.replace("--", "-");
+    if options.verify {
+        timed_step("verify", || verify_project_http(input, config))?;
     }
 
-    result.trim_matches('-').to_string()
-}
+    print_timing_summary();
 
-fn find_project_root() -> Option<PathBuf> {
-    let path_dot = PathBuf::from("./docker");
-    if path_dot.exists() && path_dot.is_dir() {
-        return Some(PathBuf::from("."));
+    if options.json {
+        print_to_real_stdout(
+            json_stdout,
+            &format!("{}\n", render_json_summary(input, config)),
+        );
     }
 
-    let path_dot_dot = PathBuf::from("../docker");
-    if path_dot_dot.exists() && path_dot_dot.is_dir() {
-        return Some(PathBuf::from(".."));
+    if options.open_editor {
+        open_project_in_editor(input)?;
     }
-    None
-}
 
-fn create_vhost_file(input: &ProjectInput) -> Result<(), AppError> {
-    println!("Criando arquivo de configuração Vhost...");
+    if options.open_browser || options.browser.is_some() {
+        open_project_in_browser(&project_url(input, config), options.browser.as_deref());
+    }
 
-    let project_root = find_project_root().ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::NotFound,
-            format!(
-                "Não foi possível determinar o diretório raiz do projeto {}.",
-                input.project_name
-            ),
-        )
-    })?;
+    stop_stack_after_setup(config, &applied.started_containers)?;
 
-    let vhosts_dir =project_root.join(VHOSTS_DIR);
-    let vhost_filename = format!("{}.conf", input.project_host);
-    let vhost_path = vhosts_dir.join(&vhost_filename);
+    if options.attach {
+        attach_to_logs(config);
+    }
 
-    let vhost_content = format!(
-        r#"<VirtualHost *:80>
-    # Nome do host que será usado (ex: minha-app.test)
-    ServerName {}
+    Ok(())
+}
 
-    # Diretório raiz do projeto Laravel (montado em /var/www/html/)
-    DocumentRoot /var/www/html/{}/public
+// Etapas do fluxo de criação, na ordem do plano; o nome vai para o diário do --resume e para o --explain
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    ComposeBuild,
+    ComposePull,
+    ComposeUp,
+    CreateProject,
+    Scripts,
+    DbUser,
+    TestDb,
+    EnvConfig,
+    EnvExample,
+    TestingEnv,
+    PhpIni,
+    Migrate,
+    Optimize,
+    Composer,
+    Npm,
+    Vite,
+    Octane,
+    Ownership,
+    OutDir,
+    Template,
+    Vhost,
+    Hosts,
+    Restart,
+}
 
-    <Directory /var/www/html/{}/public>
-        AllowOverride All
-         Require all granted
-        DirectoryIndex index.php index.html
-    </Directory>
+impl Step {
+    fn name(self) -> &'static str {
+        match self {
+            Step::ComposeBuild => "compose_build",
+            Step::ComposePull => "compose_pull",
+            Step::ComposeUp => "compose_up",
+            Step::CreateProject => "create_project",
+            Step::Scripts => "scripts",
+            Step::DbUser => "db_user",
+            Step::TestDb => "test_db",
+            Step::EnvConfig => "env_config",
+            Step::EnvExample => "env_example",
+            Step::TestingEnv => "testing_env",
+            Step::PhpIni => "php_ini",
+            Step::Migrate => "migrate",
+            Step::Optimize => "optimize",
+            Step::Composer => "composer",
+            Step::Npm => "npm",
+            Step::Vite => "vite",
+            Step::Octane => "octane",
+            Step::Ownership => "ownership",
+            Step::OutDir => "out_dir",
+            Step::Template => "template",
+            Step::Vhost => "vhost",
+            Step::Hosts => "hosts",
+            Step::Restart => "restart",
+        }
+    }
 
-    <FilesMatch \.php$>
-        SetHandler "proxy:fcgi://php:9000"
-    </FilesMatch>
-</VirtualHost>"#,
-        input.project_host, input.project_name, input.project_name
-    );
-    fs::write(&vhost_path, vhost_content)?;
+    fn phase(self) -> Phase {
+        match self {
+            Step::ComposeBuild | Step::ComposePull | Step::ComposeUp => Phase::Stack,
+            Step::CreateProject => Phase::Install,
+            Step::Scripts
+            | Step::DbUser
+            | Step::TestDb
+            | Step::EnvConfig
+            | Step::EnvExample
+            | Step::TestingEnv
+            | Step::PhpIni
+            | Step::Migrate
+            | Step::Optimize
+            | Step::Composer
+            | Step::Npm
+            | Step::Vite => Phase::Configure,
+            Step::Octane
+            | Step::Ownership
+            | Step::OutDir
+            | Step::Template
+            | Step::Vhost
+            | Step::Hosts
+            | Step::Restart => Phase::Publish,
+        }
+    }
+}
 
-    println!("Vhost criado com sucesso: {}", vhost_path.display());
+// Uma ação do plano: o que a etapa vai fazer e onde, calculado antes de qualquer alteração
+#[derive(Debug, Clone, PartialEq)]
+enum ActionKind {
+    Run {
+        target: String,
+        command: String,
+        note: Option<String>,
+    },
+    WriteFile {
+        path: String,
+        content: Option<String>,
+    },
+    CopyDir {
+        from: String,
+        to: String,
+    },
+    AppendHosts {
+        line: String,
+    },
+    Skip {
+        reason: String,
+    },
+}
 
-    Ok(())
+#[derive(Debug, Clone, PartialEq)]
+struct Action {
+    step: Step,
+    kind: ActionKind,
 }
 
-fn execute_laravel_creation(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
-    println!(">> Instalando Laravel ({})", input.laravel_version);
+impl Action {
+    fn run(step: Step, target: &str, command: String) -> Action {
+        Action {
+            step,
+            kind: ActionKind::Run {
+                target: target.to_string(),
+                command,
+                note: None,
+            },
+        }
+    }
 
-    let check_container_is_running = |name: &str| -> Result<bool, io::Error> {
-        let output = Command::new("docker")
-            .arg("ps")
-            .arg("-q")
-            .arg("-f")
-            .arg(format!("name={}", name))
-            .output()?;
+    fn write(step: Step, path: String, content: Option<String>) -> Action {
+        Action {
+            step,
+            kind: ActionKind::WriteFile { path, content },
+        }
+    }
 
-        let status = String::from_utf8_lossy(&output.stdout);
-        Ok(!status.trim().is_empty())
-    };
+    fn skip(step: Step, reason: String) -> Action {
+        Action {
+            step,
+            kind: ActionKind::Skip { reason },
+        }
+    }
 
-    match check_container_is_running(&config.php_container_name) {
-        Ok(true) => {
-            println!("Contêiner PHP ativo.");
+    // Condição ou alternativa que só se decide durante a execução (falta de memória, seeder ausente)
+    fn with_note(mut self, text: String) -> Action {
+        if let ActionKind::Run { note, .. } = &mut self.kind {
+            *note = Some(text);
         }
-        _ => {
-            println!(
-                "Contêiner PHP '{}' não está ativo. Iniciando o ambiente Docker Compose...",
-                config.php_container_name
-            );
-            let up_status = Command::new("docker")
-                .arg("compose")
-                .arg("up")
-                .arg("-d")
-                .status()
-                .map_err(|e| {
-                    AppError::Docker(format!("Falha ao executar 'docker compose up -d': {}", e))
-                })?;
+        self
+    }
 
-            if !up_status.success() {
-                return Err(AppError::Docker(
-                    "Falha ao iniciar o ambiente Docker Compose. Verifique as configurações."
-                        .to_string(),
-                ));
-            }
+    fn skip_reason(&self) -> Option<&str> {
+        match &self.kind {
+            ActionKind::Skip { reason } => Some(reason),
+            _ => None,
+        }
+    }
+}
 
-            let max_attempts = 3;
-            let wait_time = std::time::Duration::from_secs(3);
+// Única fonte das condições de cada etapa; só lê o estado (contêineres, /etc/hosts, vhost).
+// Os comandos saem dos mesmos helpers que as etapas executam
+fn plan_project_actions(input: &ProjectInput, config: &AppConfig) -> Vec<Action> {
+    let mut plan = Vec::new();
+    let app_dir = container_app_dir(input, config);
+    let php = config.php_container_name.as_str();
+    let artisan = |command: &str| artisan_command(input, config, command);
 
-            for attempt in 1..=max_attempts {
-                println!(
-                    "Aguardando inicialização do contêiner PHP (Tentativa {} de {})...",
-                    attempt, max_attempts
-                );
-                io::stdout().flush()?;
+    if config.compose_build {
+        plan.push(Action::run(
+            Step::ComposeBuild,
+            "host",
+            compose_display(config, &compose_build_args(config)),
+        ));
+    }
+    if config.pull {
+        plan.push(Action::run(
+            Step::ComposePull,
+            "host",
+            compose_display(config, &COMPOSE_PULL_ARGS),
+        ));
+    }
+    // Imagens recém-construídas só valem depois que o 'up' recria os contêineres
+    let stack_running = matches!(check_container_is_running(php), Ok(true));
+    let foreign_stack = if stack_running && !config.compose_build {
+        foreign_compose_project(config)
+    } else {
+        None
+    };
+    if config.compose_build || !stack_running || foreign_stack.is_some() {
+        if let Some(actual) = &foreign_stack {
+            plan.push(
+                Action::run(
+                    Step::ComposeUp,
+                    "host",
+                    format!("docker {}", stack_down_args(actual).join(" ")),
+                )
+                .with_note(format!(
+                    "o contêiner '{}' pertence à stack '{}'; pede confirmação antes de pará-la",
+                    php, actual
+                )),
+            );
+        }
+        plan.push(Action::run(
+            Step::ComposeUp,
+            "host",
+            compose_display(config, &compose_up_args(config)),
+        ));
+    } else if config.resume {
+        plan.push(Action::skip(
+            Step::ComposeUp,
+            resume_skip_reason("Contêineres ativos"),
+        ));
+    } else {
+        plan.push(Action::skip(
+            Step::ComposeUp,
+            "Contêiner PHP ativo; a stack não será reiniciada.".to_string(),
+        ));
+    }
 
-                std::thread::sleep(wait_time);
+    let project_exists =
+        config.resume && stack_running && project_has_artisan(input, config).unwrap_or(false);
+    let installer_ready = config.installer == Installer::Laravel
+        && matches!(
+            laravel_installer_unavailable(input, config, stack_running),
+            Ok(None)
+        );
+    if project_exists {
+        plan.push(Action::skip(
+            Step::CreateProject,
+            resume_skip_reason("Projeto Laravel"),
+        ));
+    } else if installer_ready {
+        let action = Action::run(Step::CreateProject, php, laravel_new_command(input, config));
+        plan.push(if stack_running {
+            action
+        } else {
+            action.with_note(
+                "sem o instalador 'laravel' no contêiner, usa composer create-project".to_string(),
+            )
+        });
+    } else {
+        let args = composer_create_project_args(input, config);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        plan.push(
+            Action::run(Step::CreateProject, php, args.join(" "))
+                .with_note(composer_memory_retry_note(&args)),
+        );
+    }
 
-                match check_container_is_running(&config.php_container_name) {
-                    Ok(true) => {
-                        println!("\rContêiner PHP ativo e pronto."); // Limpa a linha
-                        break;
-                    }
-                    Ok(false) if attempt == max_attempts => {
-                        return Err(AppError::Docker(format!(
-                            "O contêiner PHP '{}' falhou ao iniciar após {} tentativas.",
-                            config.php_container_name, max_attempts
-                        )));
-                    }
-                    Err(e) => {
-                        return Err(AppError::Docker(format!(
-                            "Falha ao verificar o status do contêiner: {}",
-                            e
-                        )));
+    if config.no_scripts {
+        plan.push(
+            Action::run(Step::Scripts, php, deferred_scripts_command(input, config)).with_note(
+                format!("se falhar: {}", env_from_example_command(input, config)),
+            ),
+        );
+    }
+    if config.create_db_user {
+        plan.push(Action::run(
+            Step::DbUser,
+            &config.db_container_name,
+            db_root_sql_args(config, &db_user_sql(input, config)).join(" "),
+        ));
+    } else if config.with_test_db {
+        plan.push(Action::run(
+            Step::TestDb,
+            &config.db_container_name,
+            db_root_sql_args(config, &test_database_sql(input, config)).join(" "),
+        ));
+    }
+    plan.push(Action::write(
+        Step::EnvConfig,
+        format!("{}/.env", app_dir),
+        None,
+    ));
+    if config.sync_env_example {
+        plan.push(Action::write(
+            Step::EnvExample,
+            format!("{}/.env.example", app_dir),
+            None,
+        ));
+    }
+    if config.with_testing_env {
+        plan.push(Action::write(
+            Step::TestingEnv,
+            format!("{}/.env.testing", app_dir),
+            None,
+        ));
+    }
+    if !config.php_ini.is_empty() {
+        plan.push(Action::write(
+            Step::PhpIni,
+            format!("{}/public/.user.ini", app_dir),
+            Some(php_ini_content(&config.php_ini)),
+        ));
+    }
+    match &config.artisan_steps {
+        Some(steps) => {
+            for step in steps {
+                plan.push(Action::run(Step::Migrate, php, artisan(step)));
+            }
+        }
+        None => {
+            if !config.no_config_clear {
+                plan.push(Action::run(Step::Migrate, php, artisan("config:clear")));
+            }
+            if !config.skip_migrate {
+                plan.push(Action::run(
+                    Step::Migrate,
+                    php,
+                    artisan(migration_command(config)),
+                ));
+                if config.no_app_seeders {
+                    for seeder in &config.baseline_seeders {
+                        plan.push(
+                            Action::run(Step::Migrate, php, seeder_command(input, config, seeder))
+                                .with_note(format!(
+                                    "só se database/seeders/{}.php existir no projeto",
+                                    seeder
+                                )),
+                        );
                     }
-                    _ => continue,
                 }
             }
+            if config.no_config_clear && config.skip_migrate {
+                plan.push(Action::skip(
+                    Step::Migrate,
+                    "config:clear e migrate desativados (--no-config-clear, --no-migrate). Etapa ignorada.".to_string(),
+                ));
+            }
         }
     }
+    if config.optimize {
+        for cache in OPTIMIZE_CACHES {
+            plan.push(Action::run(
+                Step::Optimize,
+                php,
+                artisan(&format!("{}:cache", cache)),
+            ));
+        }
+    }
+    if config.offline {
+        plan.push(Action::skip(
+            Step::Composer,
+            offline_stub("composer update"),
+        ));
+    } else if config.skip_deps {
+        plan.push(Action::skip(
+            Step::Composer,
+            "Atualização de dependências (composer update) desativada. Etapa ignorada.".to_string(),
+        ));
+    } else {
+        let command = composer_update_command(input, config);
+        plan.push(
+            Action::run(Step::Composer, php, command.clone())
+                .with_note(composer_memory_retry_note(&["sh", "-c", &command])),
+        );
+    }
+    // Sem o contêiner do Node o fluxo ignora npm e vite (ou falha com --require-node);
+    // com a stack parada vale o que o compose declara, já que o 'up' vai subi-la
+    let node_available = config.require_node
+        || if stack_running {
+            matches!(
+                check_container_is_running(&config.node_container_name),
+                Ok(true)
+            )
+        } else {
+            find_compose_file()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .is_some_and(|content| compose_services(&content).iter().any(|s| s == "node"))
+        };
+    let node_absent = format!(
+        "contêiner do Node ({}) não encontrado",
+        config.node_container_name
+    );
+    if config.offline {
+        plan.push(Action::skip(Step::Npm, offline_stub("npm install")));
+    } else if config.skip_npm {
+        plan.push(Action::skip(
+            Step::Npm,
+            "npm install desativado (--skip-npm). Etapa ignorada.".to_string(),
+        ));
+    } else if !node_available {
+        plan.push(Action::skip(
+            Step::Npm,
+            format!(
+                "npm install ignorado: {}. Use --require-node para tratar isso como erro.",
+                node_absent
+            ),
+        ));
+    } else {
+        let action = Action::run(
+            Step::Npm,
+            &config.node_container_name,
+            npm_install_command(input, config),
+        );
+        plan.push(if config.npm_retries > 1 {
+            action.with_note(format!("até {} tentativas", config.npm_retries))
+        } else {
+            action
+        });
+    }
+    if !config.skip_npm && !node_available {
+        plan.push(Action::skip(
+            Step::Vite,
+            format!("Ajuste do vite.config.js ignorado: {}.", node_absent),
+        ));
+    } else if config.patch_vite {
+        plan.push(Action::write(
+            Step::Vite,
+            format!("{}/vite.config.js", app_dir),
+            None,
+        ));
+    } else {
+        plan.push(Action::skip(
+            Step::Vite,
+            "Ajuste do vite.config.js desativado. Etapa ignorada.".to_string(),
+        ));
+    }
+    if config.octane {
+        plan.push(octane_action(input, config));
+    }
+    if config.fix_ownership {
+        let (owner, note) = match host_user_ids() {
+            Ok(owner) => (owner, "como root, via docker exec -u 0".to_string()),
+            Err(e) => ("?".to_string(), format!("a etapa vai falhar: {}", e)),
+        };
+        plan.push(
+            Action::run(
+                Step::Ownership,
+                php,
+                ownership_command(input, &owner).join(" "),
+            )
+            .with_note(note),
+        );
+    }
+    if let Some(out_dir) = &config.out_dir {
+        plan.push(Action {
+            step: Step::OutDir,
+            kind: ActionKind::CopyDir {
+                from: app_dir.clone(),
+                to: out_dir.display().to_string(),
+            },
+        });
+    }
+    if let Some(template_dir) = &config.template_dir {
+        plan.push(Action {
+            step: Step::Template,
+            kind: ActionKind::CopyDir {
+                from: template_dir.display().to_string(),
+                to: app_dir.clone(),
+            },
+        });
+    }
 
-    let status = Command::new("docker")
-        .arg("exec")
-        .arg("-it")
-        .arg(&config.php_container_name)
-        .arg("composer")
-        .arg("create-project")
-        .arg("laravel/laravel")
-        .arg(&input.project_name)
-        .arg(&input.laravel_version)
-        .status()
-        .map_err(|e| {
-            AppError::Docker(format!("Falha ao executar 'docker exec composer': {}", e))
-        })?;
+    let vhost_planned = !(config.resume && project_vhost_content(input, config).is_some());
+    if vhost_planned {
+        plan.push(Action::write(
+            Step::Vhost,
+            vhost_location(input, config),
+            Some(vhost_file_content(input, config)),
+        ));
+    } else {
+        plan.push(Action::skip(Step::Vhost, resume_skip_reason("Vhost")));
+    }
 
-    if !status.success() {
-        return Err(AppError::Docker(
-            "Composer falhou ao criar o projeto. Verifique logs do contêiner.".to_string(),
+    let host_lines: Vec<String> = hosts_addresses(config)
+        .iter()
+        .map(|address| format!("{} {}", address, input.project_host))
+        .collect();
+    let hosts = fs::read_to_string("/etc/hosts").unwrap_or_default();
+    let missing_lines: Vec<&String> = hosts_addresses(config)
+        .iter()
+        .zip(&host_lines)
+        .filter(|(address, _)| !hosts_content_has_address(&hosts, address, &input.project_host))
+        .map(|(_, line)| line)
+        .collect();
+    if config.no_hosts {
+        plan.push(Action::skip(
+            Step::Hosts,
+            format!(
+                "Atualização do /etc/hosts ignorada (--no-hosts). Adicione manualmente: {}",
+                host_lines.join(" e ")
+            ),
+        ));
+    } else if missing_lines.is_empty() && config.resume {
+        plan.push(Action::skip(
+            Step::Hosts,
+            resume_skip_reason("Entrada no /etc/hosts"),
+        ));
+    } else if missing_lines.is_empty() {
+        plan.push(Action::skip(
+            Step::Hosts,
+            format!(
+                "Entrada de host '{}' já existe em /etc/hosts.",
+                input.project_host
+            ),
         ));
+    } else {
+        for line in missing_lines {
+            plan.push(Action {
+                step: Step::Hosts,
+                kind: ActionKind::AppendHosts { line: line.clone() },
+            });
+        }
     }
 
-    println!(
-        "Projeto Laravel '{}' criado com sucesso em {}",
-        input.project_name, input.project_path
-    );
-    Ok(())
-}
+    if !vhost_planned {
+        plan.push(Action::skip(
+            Step::Restart,
+            "Vhost inalterado, reinício ignorado.".to_string(),
+        ));
+    } else if config.no_restart_on_resume {
+        plan.push(Action::skip(
+            Step::Restart,
+            "Reinício do Apache ignorado (--no-restart-on-resume). Reinicie-o manualmente para aplicar o Vhost.".to_string(),
+        ));
+    } else if is_batch_child() {
+        plan.push(Action::skip(
+            Step::Restart,
+            "Reinício do Apache adiado para o fim do batch.".to_string(),
+        ));
+    } else {
+        let action = Action::run(
+            Step::Restart,
+            "host",
+            format!("docker {}", APACHE_RESTART_ARGS.join(" ")),
+        );
+        plan.push(if config.defer_restart {
+            action.with_note("no fim da execução (--defer-restart)".to_string())
+        } else {
+            action
+        });
+    }
 
-fn restart_apache_container() -> Result<(), AppError> {
-    println!("---");
-    println!("Reiniciando o contêiner Apache para carregar o novo Vhost...");
+    plan
+}
 
-    let status = Command::new("docker")
-        .arg("compose")
-        .arg("restart")
-        .arg("apache")
-        .status()
-        .map_err(|e| {
-            AppError::Docker(format!("Falha ao executar 'docker compose restart': {}", e))
-        })?;
+fn octane_action(input: &ProjectInput, config: &AppConfig) -> Action {
+    Action::write(
+        Step::Octane,
+        format!("{}/.env", container_app_dir(input, config)),
+        None,
+    )
+}
 
-    if status.success() {
-        std::thread::sleep(std::time::Duration::from_secs(1));
+// As fases têm preparação própria (checagens, mensagens) que roda mesmo quando a fase não tem ações
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Phase {
+    Stack,
+    Install,
+    Configure,
+    Publish,
+}
 
-        println!("\rContêiner Apache reiniciado com sucesso.");
-        io::stdout().flush()?;
+// O que esta execução já aplicou: base do rollback, do diário do --resume e do resumo final
+#[derive(Default)]
+struct PlanApplication {
+    applied: Vec<Step>,
+    started_containers: Vec<String>,
+    env_before: Option<String>,
+    node_missing: Option<bool>,
+    vhost_changed: bool,
+    vhost_created: bool,
+    hosts_added: Vec<&'static str>,
+    restart_pending: bool,
+}
 
-        Ok(())
-    } else {
-        return Err(AppError::Docker(format!(
-            "Falha ao reiniciar o contêiner Apache. Verifique se o serviço 'apache' está correto no docker-compose.yml. Status: {:?}",
-            status
-        )));
+impl PlanApplication {
+    fn check_node(&mut self, config: &AppConfig) -> Result<bool, AppError> {
+        if self.node_missing.is_none() {
+            self.node_missing = Some(node_container_missing(config)?);
+        }
+        Ok(self.node_missing == Some(true))
     }
 }
 
-fn update_etc_hosts(input: &ProjectInput) -> Result<(), AppError> {
-    use std::process::Command;
+// Aplica o plano em ordem, uma etapa por vez; cada etapa concluída vai para o diário do --resume
+fn apply_project_plan(
+    plan: &mut Vec<Action>,
+    state: &mut PlanApplication,
+    input: &mut ProjectInput,
+    config: &AppConfig,
+    options: &CliOptions,
+) -> Result<(), AppError> {
+    let journal = if config.resume {
+        read_apply_journal(input)
+    } else {
+        clear_apply_journal(input);
+        Vec::new()
+    };
 
-    println!("---");
-    println!(
-        "O próximo passo exige permissão de administrador (sudo) para atualizar o /etc/hosts."
-    );
+    let mut index = 0;
+    for phase in [
+        Phase::Stack,
+        Phase::Install,
+        Phase::Configure,
+        Phase::Publish,
+    ] {
+        enter_phase(phase, plan, index, state, input, config, options)?;
 
-    let host_entry = format!("127.0.0.1 {}", input.project_host);
-    let hosts_file_path = "/etc/hosts";
+        while index < plan.len() && plan[index].step.phase() <= phase {
+            let step = plan[index].step;
+            let group_len = plan[index..]
+                .iter()
+                .take_while(|action| action.step == step)
+                .count();
+            let group = &plan[index..index + group_len];
+            index += group_len;
 
-    match fs::read_to_string(hosts_file_path) {
-        Ok(content) => {
-            if content.contains(&input.project_host) {
+            // Ex.: o npm já rodou junto com o composer (--parallel)
+            if state.applied.contains(&step) {
+                continue;
+            }
+            let skipped: Vec<&str> = group.iter().filter_map(Action::skip_reason).collect();
+            if !skipped.is_empty() {
+                for reason in skipped {
+                    println!("{}", paint(Tone::Step, &format!(">> {}", reason)));
+                }
+                continue;
+            }
+            // A stack não entra no diário: contêineres parados precisam subir de novo
+            let journaled = step.phase() != Phase::Stack;
+            if journaled && journal.iter().any(|done| done == step.name()) {
                 println!(
-                    "✅ Entrada de host '{}' já existe em /etc/hosts.",
-                    input.project_host
+                    "{}",
+                    paint(
+                        Tone::Step,
+                        &format!(
+                            ">> Etapa '{}' já aplicada, pulando (--resume).",
+                            step.name()
+                        )
+                    )
                 );
-                return Ok(());
+                continue;
+            }
+
+            apply_step(step, plan, state, input, config)?;
+            state.applied.push(step);
+            if journaled {
+                record_applied_step(input, step.name());
             }
         }
-        Err(e) => {
+    }
+
+    Ok(())
+}
+
+fn enter_phase(
+    phase: Phase,
+    plan: &mut Vec<Action>,
+    index: usize,
+    state: &mut PlanApplication,
+    input: &mut ProjectInput,
+    config: &AppConfig,
+    options: &CliOptions,
+) -> Result<(), AppError> {
+    match phase {
+        Phase::Stack => {
             println!(
-                "Não foi possível ler /etc/hosts para verificação: {}. Tentando escrever com sudo.",
-                e
+                "{}",
+                paint(
+                    Tone::Step,
+                    &format!(">> Instalando Laravel ({})", input.laravel_version)
+                )
             );
+            validate_compose_file(config)
         }
-    }
+        Phase::Install => {
+            wait_for_stack_ready(config)?;
+            ensure_container_binaries(config)?;
+            check_composer_version(config)
+        }
+        Phase::Configure => {
+            println!("---");
+            println!("Iniciando configurações e inicialização do projeto Laravel...");
+            state.env_before = options
+                .dump_env_diff
+                .then(|| read_project_env(input, config));
+            Ok(())
+        }
+        Phase::Publish => {
+            println!(
+                "Projeto '{}' completamente inicializado.",
+                input.project_name
+            );
+            if let Some(before) = &state.env_before {
+                print_env_diff(before, &read_project_env(input, config));
+            }
 
-    let command_string = format!("echo '{}' >> {}", host_entry, hosts_file_path);
+            // O composer.json só existe depois do create-project: o Octane detectado entra no plano aqui
+            input.octane = config.octane || detect_octane(input, config);
+            if input.octane && !plan.iter().any(|action| action.step == Step::Octane) {
+                plan.insert(index, octane_action(input, config));
+            }
 
-    let status = Command::new("sudo")
-        .arg("sh")
-        .arg("-c")
-        .arg(command_string)
-        .status()
-        .map_err(|e| AppError::Io(e.into()))?; // Trata erros de IO ao executar sudo
+            report_project_location(input, config);
+            Ok(())
+        }
+    }
+}
 
-    if status.success() {
-        println!("Host '{}' adicionado a /etc/hosts.", input.project_host);
-    } else {
-        return Err(AppError::Validation(format!(
-            "Falha ao executar 'sudo'. Verifique se você digitou a senha corretamente. Status: {:?}",
-            status
-        )));
+// Executa uma etapa do plano com a função da etapa; as condições já foram decididas no plano
+fn apply_step(
+    step: Step,
+    plan: &[Action],
+    state: &mut PlanApplication,
+    input: &mut ProjectInput,
+    config: &AppConfig,
+) -> Result<(), AppError> {
+    let name = step.name();
+    match step {
+        Step::ComposeBuild => timed_step(name, || build_compose_images(config)),
+        Step::ComposePull => timed_step(name, || pull_compose_images(config)),
+        Step::ComposeUp => {
+            state.started_containers = timed_step(name, || start_compose_stack(config))?;
+            Ok(())
+        }
+        Step::CreateProject => install_laravel_project(input, config),
+        Step::Scripts => timed_step(name, || run_deferred_composer_scripts(input, config)),
+        Step::DbUser => timed_step(name, || provision_db_user(input, config)),
+        Step::TestDb => timed_step(name, || create_test_database(input, config)),
+        Step::EnvConfig => timed_step(name, || configure_project_env(input, config)),
+        Step::EnvExample => timed_step(name, || sync_env_example(input, config)),
+        Step::TestingEnv => timed_step(name, || write_testing_env(input, config)),
+        Step::PhpIni => timed_step(name, || write_php_ini(input, config)),
+        Step::Migrate => timed_step(name, || run_artisan_setup(input, config)),
+        Step::Optimize => timed_step(name, || run_artisan_optimize(input, config)),
+        Step::Composer => {
+            let npm_planned = plan
+                .iter()
+                .any(|action| action.step == Step::Npm && action.skip_reason().is_none());
+            // O Node é conferido antes do composer para o --require-node falhar cedo
+            let node_missing = npm_planned && state.check_node(config)?;
+            if config.parallel && npm_planned && !node_missing {
+                run_dependencies_in_parallel(input, config)?;
+                state.applied.push(Step::Npm);
+                record_applied_step(input, Step::Npm.name());
+                return Ok(());
+            }
+            timed_step(name, || run_composer_update(input, config))
+        }
+        Step::Npm => {
+            if state.check_node(config)? {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Step,
+                        ">> Sem contêiner do Node: npm install ignorado."
+                    )
+                );
+                return Ok(());
+            }
+            timed_step(name, || run_npm_install(input, config))
+        }
+        Step::Vite => {
+            if !config.skip_npm && state.check_node(config)? {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Step,
+                        ">> Sem contêiner do Node: ajuste do vite.config.js ignorado."
+                    )
+                );
+                return Ok(());
+            }
+            timed_step(name, || patch_vite_config(input, config))
+        }
+        Step::Octane => configure_octane_env(input, config),
+        Step::Ownership => timed_step(name, || fix_project_ownership(input, config)),
+        Step::OutDir => match &config.out_dir {
+            Some(out_dir) => copy_project_to_out_dir(input, config, out_dir),
+            None => Ok(()),
+        },
+        Step::Template => match &config.template_dir {
+            Some(template_dir) => {
+                apply_project_template(input, template_dir, config.overwrite_template)
+            }
+            None => Ok(()),
+        },
+        Step::Vhost => {
+            state.vhost_created = project_vhost_content(input, config).is_none();
+            state.vhost_changed = timed_step(name, || {
+                with_shared_lock(|| create_vhost_file(input, config))
+            })?;
+            Ok(())
+        }
+        Step::Hosts => {
+            state.hosts_added = timed_step(name, || {
+                with_shared_lock(|| update_etc_hosts(input, config))
+            })?;
+            Ok(())
+        }
+        Step::Restart => {
+            if !state.vhost_changed {
+                println!("Vhost inalterado, reinício ignorado.");
+            } else if config.defer_restart {
+                println!("Reinício do Apache adiado para o fim da execução (--defer-restart).");
+                state.restart_pending = true;
+            } else {
+                timed_step(name, || restart_apache_container(config))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Desfaz em ordem inversa o que esta execução criou: hosts e vhost novos e o diretório do projeto
+fn undo_applied_actions(state: &PlanApplication, input: &ProjectInput, config: &AppConfig) {
+    for step in state.applied.iter().rev() {
+        let result = match step {
+            Step::Hosts if !state.hosts_added.is_empty() => with_shared_lock(|| {
+                remove_hosts_entries(
+                    std::slice::from_ref(&input.project_host),
+                    &state.hosts_added,
+                    config,
+                )
+            }),
+            Step::Vhost if state.vhost_created => {
+                with_shared_lock(|| remove_project_vhost(input, config))
+            }
+            Step::CreateProject => {
+                rollback_partial_project(input, config);
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Não foi possível desfazer a etapa '{}': {}. Remova manualmente.",
+                        step.name(),
+                        e
+                    )
+                )
+            );
+        }
     }
+    clear_apply_journal(input);
+}
+
+fn remove_project_vhost(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let vhosts_dir = match find_project_root() {
+        Some(project_root) => project_root.join(VHOSTS_DIR),
+        None => return Ok(()),
+    };
 
+    match config.vhost_mode {
+        VhostMode::PerProject => {
+            let vhost_path = vhosts_dir.join(format!("{}.conf", input.project_host));
+            if vhost_path.exists() {
+                fs::remove_file(&vhost_path)?;
+                println!("Vhost removido: {}", vhost_path.display());
+            }
+        }
+        VhostMode::SingleInclude => {
+            remove_vhost_include_block(&vhosts_dir, &input.project_name)?;
+        }
+    }
     Ok(())
 }
 
-fn execute_command_in_container(container_name: &str, args: &[&str]) -> Result<(), AppError> {
-    let status = Command::new("docker")
-        .arg("exec")
-        .arg("-it")
-        .arg(container_name)
-        .args(args)
-        .status()
-        .map_err(|e| {
-            AppError::Docker(format!(
-                "Falha ao executar comando no contênier '{}':{}",
-                container_name, e
-            ))
-        })?;
-    if status.success() {
-        Ok(())
-    } else {
-        Err(AppError::Docker(format!(
-            "Comando falho dentro do contêiner '{}'. Status: {:?}",
-            container_name, status,
-        )))
+fn apply_journal_path(input: &ProjectInput) -> Option<PathBuf> {
+    find_project_root().map(|project_root| {
+        project_root
+            .join(APPLY_JOURNAL_DIR)
+            .join(&input.project_host)
+    })
+}
+
+fn read_apply_journal(input: &ProjectInput) -> Vec<String> {
+    apply_journal_path(input)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| content.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+// Sem o diário o --resume só refaz a etapa, então uma falha aqui não derruba a execução
+fn record_applied_step(input: &ProjectInput, step: &str) {
+    let journal_path = match apply_journal_path(input) {
+        Some(journal_path) => journal_path,
+        None => return,
+    };
+
+    let result = journal_path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&journal_path)
+        })
+        .and_then(|mut file| writeln!(file, "{}", step));
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Não foi possível registrar a etapa '{}' em {}: {}",
+                    step,
+                    journal_path.display(),
+                    e
+                )
+            )
+        );
     }
 }
 
-fn configure_and_initialize_laravel(
-    input: &ProjectInput,
-    config: &AppConfig,
-) -> Result<(), AppError> {
-    println!("---");
-    println!("Iniciando configurações e inicialização do projeto Laravel...");
+fn clear_apply_journal(input: &ProjectInput) {
+    if let Some(journal_path) = apply_journal_path(input) {
+        let _ = fs::remove_file(journal_path);
+    }
+}
 
-    println!(">> Configurando arquivo .env...");
-    let env_updates = vec![
-        format!(
-            "s/APP_URL=http:\\/\\/localhost/APP_URL=http:\\/\\/{}/",
-            input.project_host
-        ),
-        "s/DB_CONNECTION=sqlite/DB_CONNECTION=mariadb/".to_string(),
-        format!("s/# DB_PORT=3306/DB_PORT={}/", config.db_port),
-        format!(
-            "s/# DB_DATABASE=laravel/DB_DATABASE={}/",
-            input.project_name
-        ),
-        "s/# DB_HOST=127.0.0.1/DB_HOST=mariadb/".to_string(),
-        "s/# DB_USERNAME=root/DB_USERNAME=root/".to_string(),
-        format!("s/# DB_PASSWORD=/DB_PASSWORD={}/", config.db_root_password),
-    ];
+fn print_plan(input: &ProjectInput, plan: &[Action]) {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Plano para '{}' ({} ações, nada foi alterado):",
+                input.project_name,
+                plan.iter()
+                    .filter(|action| action.skip_reason().is_none())
+                    .count()
+            )
+        )
+    );
+    for (index, action) in plan.iter().enumerate() {
+        let description = match &action.kind {
+            ActionKind::Run {
+                target,
+                command,
+                note: None,
+            } => format!("{}: {}", target, redact(command)),
+            ActionKind::Run {
+                target,
+                command,
+                note: Some(note),
+            } => format!("{}: {} ({})", target, redact(command), note),
+            ActionKind::WriteFile {
+                path,
+                content: Some(content),
+            } => format!("grava {} ({} linhas)", path, content.lines().count()),
+            ActionKind::WriteFile {
+                path,
+                content: None,
+            } => format!("edita {}", path),
+            ActionKind::CopyDir { from, to } => format!("copia {} para {}", from, to),
+            ActionKind::AppendHosts { line } => format!("adiciona ao /etc/hosts: {}", line),
+            ActionKind::Skip { reason } => format!("ignorada: {}", reason),
+        };
+        println!(
+            "  {:>2}. [{}] {}",
+            index + 1,
+            action.step.name(),
+            description
+        );
+    }
+}
 
-    for update in env_updates {
-        let command_str = format!(
-            "cd /var/www/html/{} && sed -i '{}' .env",
-            input.project_name, update
+fn render_plan_json(input: &ProjectInput, plan: &[Action]) -> String {
+    let actions: Vec<String> = plan
+        .iter()
+        .map(|action| {
+            let fields = match &action.kind {
+                ActionKind::Run {
+                    target,
+                    command,
+                    note,
+                } => format!(
+                    "\"type\":\"run\",\"target\":\"{}\",\"command\":\"{}\",\"note\":{}",
+                    json_escape(target),
+                    json_escape(&redact(command)),
+                    note.as_ref().map_or("null".to_string(), |note| format!(
+                        "\"{}\"",
+                        json_escape(note)
+                    ))
+                ),
+                ActionKind::WriteFile { path, content } => format!(
+                    "\"type\":\"write_file\",\"path\":\"{}\",\"content\":{}",
+                    json_escape(path),
+                    content
+                        .as_ref()
+                        .map_or("null".to_string(), |content| format!(
+                            "\"{}\"",
+                            json_escape(content)
+                        ))
+                ),
+                ActionKind::CopyDir { from, to } => format!(
+                    "\"type\":\"copy_dir\",\"from\":\"{}\",\"to\":\"{}\"",
+                    json_escape(from),
+                    json_escape(to)
+                ),
+                ActionKind::AppendHosts { line } => format!(
+                    "\"type\":\"append_hosts\",\"line\":\"{}\"",
+                    json_escape(line)
+                ),
+                ActionKind::Skip { reason } => {
+                    format!("\"type\":\"skip\",\"reason\":\"{}\"", json_escape(reason))
+                }
+            };
+            format!("{{\"step\":\"{}\",{}}}", action.step.name(), fields)
+        })
+        .collect();
+
+    format!(
+        "{{\"project\":\"{}\",\"actions\":[{}]}}\n",
+        json_escape(&input.project_name),
+        actions.join(",")
+    )
+}
+
+fn attach_to_logs(config: &AppConfig) {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Acompanhando logs de {} e apache (Ctrl-C para sair)...",
+                config.php_service
+            )
+        )
+    );
+
+    let mut command = compose_command(config);
+    if let Some(project_root) = find_project_root() {
+        command.current_dir(project_root);
+    }
+
+    if let Err(e) = command
+        .args(["logs", "-f", &config.php_service, "apache"])
+        .traced()
+        .status()
+    {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!("AVISO: Falha ao executar 'docker compose logs': {}", e)
+            )
         );
+    }
+}
 
-        let args: Vec<&str> = vec!["sh", "-c", command_str.as_str()];
+fn init_theme(no_color: bool, report_invalid: bool) {
+    let enabled = !no_color && env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal();
 
-        let status = Command::new("docker")
-            .arg("exec")
-            .arg("-it")
-            .arg(&config.php_container_name)
-            .args(&args)
-            .status()
-            .map_err(|e| AppError::Docker(format!("Falha ao executar sed para .env: {}", e)))?;
+    let mut theme = Theme {
+        enabled,
+        step: "1;36".to_string(),
+        success: "32".to_string(),
+        warning: "33".to_string(),
+        error: "31".to_string(),
+    };
 
-        if !status.success() {
-            return Err(AppError::Docker(format!(
-                "Falha ao atualizar o .env com: '{}'. Status: {:?}",
-                update, status
-            )));
+    if let Ok(spec) = env::var("THEME_COLORS") {
+        for entry in spec.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(key, code)| {
+                let code = code.trim();
+                let valid_code =
+                    !code.is_empty() && code.chars().all(|c| c.is_ascii_digit() || c == ';');
+                if valid_code {
+                    Some((key.trim().to_lowercase(), code.to_string()))
+                } else {
+                    None
+                }
+            });
+
+            match parsed {
+                Some((key, code)) if key == "step" => theme.step = code,
+                Some((key, code)) if key == "success" => theme.success = code,
+                Some((key, code)) if key == "warning" => theme.warning = code,
+                Some((key, code)) if key == "error" => theme.error = code,
+                _ if !report_invalid => {}
+                _ => println!(
+                    "THEME_COLORS: entrada inválida ignorada ('{}').",
+                    entry.trim()
+                ),
+            }
         }
     }
 
-    println!("Arquivo .env configurado.");
-    println!(">> Executando comandos Artisan (config:clear, migrate)...");
-
-    execute_command_in_container(
-        &config.php_container_name,
-        &[
-            "sh",
-            "-c",
-            &format!(
-                "cd /var/www/html/{} && php artisan config:clear",
-                input.project_name
-            ),
-        ],
-    )?;
-    execute_command_in_container(
-        &config.php_container_name,
-        &[
-            "sh",
-            "-c",
-            &format!(
-                "cd /var/www/html/{} && php artisan migrate --force",
-                input.project_name
-            ),
-        ],
-    )?;
+    if let Ok(mut current) = THEME.write() {
+        *current = Some(theme);
+    }
+}
 
-    println!(">> Executando composer update...");
-    execute_command_in_container(
-        &config.php_container_name,
-        &[
-            "sh",
-            "-c",
-            &format!("cd /var/www/html/{} && composer update", input.project_name),
-        ],
-    )?;
+fn paint(tone: Tone, text: &str) -> String {
+    match THEME.read().ok().as_deref().and_then(Option::as_ref) {
+        Some(theme) if theme.enabled => {
+            let code = match tone {
+                Tone::Step => &theme.step,
+                Tone::Success => &theme.success,
+                Tone::Warning => &theme.warning,
+                Tone::Error => &theme.error,
+            };
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        }
+        _ => text.to_string(),
+    }
+}
 
-    println!(">> Executando npm install...");
-    execute_command_in_container(
-        &config.node_container_name,
-        &[
-            "sh",
-            "-c",
-            &format!("cd /var/www/html/{} && npm install", input.project_name),
-        ],
-    )?;
+fn app_url(input: &ProjectInput, config: &AppConfig) -> String {
+    let scheme = if config.behind_proxy { "https" } else { "http" };
+    format!("{}://{}", scheme, input.project_host)
+}
 
-    println!(">> Configurando vite.config.js...");
+fn project_url(input: &ProjectInput, config: &AppConfig) -> String {
+    if config.behind_proxy {
+        return app_url(input, config);
+    }
 
-    let vite_update = "s|});$|\\tserver: {\\n\\t\\thost: '0.0.0.0'\\n\\t}\\n});|";
+    // Porta padrão do esquema fica de fora para a URL ser a mesma que o navegador mostra
+    let scheme = if config.ssl { "https" } else { "http" };
+    match (scheme, project_port(config)) {
+        ("http", 80) | ("https", 443) => format!("{}://{}", scheme, input.project_host),
+        (scheme, port) => format!("{}://{}:{}", scheme, input.project_host, port),
+    }
+}
 
-    let command_str = format!(
-        "cd /var/www/html/{} && sed -i \"{}\" vite.config.js",
-        input.project_name, vite_update
-    );
+fn project_port(config: &AppConfig) -> u16 {
+    if config.ssl {
+        config.server_https_port
+    } else {
+        config.server_port
+    }
+}
 
-    let args: Vec<&str> = vec!["sh", "-c", command_str.as_str()];
+fn timed_step<T>(
+    name: &'static str,
+    step: impl FnOnce() -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    check_overall_budget(name)?;
 
-    let status = Command::new("docker")
-        .arg("exec")
-        .arg("-it")
-        .arg(&config.php_container_name)
-        .args(&args)
-        .status()
-        .map_err(|e| {
-            AppError::Docker(format!("Falha ao executar sed para vite.config.js: {}", e))
-        })?;
+    if EXPLAIN_STEPS.get().copied().unwrap_or(false)
+        && let Some(explanation) = step_explanation(name)
+    {
+        println!("💡 {}", explanation);
+    }
 
-    if !status.success() {
-        return Err(AppError::Docker(format!(
-            "Falha ao atualizar o vite.config.js com: '{}'. Status: {:?}",
-            vite_update, status,
-        )));
+    emit_progress_event(name, "start", None);
+    if let Ok(mut starts) = STEP_STARTS.lock() {
+        starts.push((name, unix_now_secs()));
     }
+    let started = Instant::now();
+    let result = match (step(), step_service(name)) {
+        // Falhas de contêiner levam junto os logs do serviço desde o início da etapa
+        (Err(error), Some(service)) if matches!(error.kind(), AppError::Docker(message) if !message.contains(COMPOSE_LOGS_HEADER)) => {
+            Err(append_service_logs(error, &service, Some(name)))
+        }
+        (result, _) => result,
+    };
 
-    println!("vite.config.js configurado com sucesso.");
+    match &result {
+        Ok(_) => emit_progress_event(name, "done", None),
+        Err(e) => emit_progress_event(name, "error", Some(&e.to_string())),
+    }
 
-    println!(
-        "Projeto '{}' completamente inicializado.",
-        input.project_name
-    );
+    if let Ok(mut timings) = STEP_TIMINGS.lock() {
+        timings.push((name, started.elapsed()));
+    }
 
-    Ok(())
+    result
 }
 
-fn main() {
-    match run() {
-        Ok(_) => {
-            println!("\n Rotina concluída com sucesso.");
+// Texto didático do --explain: o que cada etapa faz e por que ela é necessária
+fn step_explanation(step: &str) -> Option<&'static str> {
+    let explanation = match step {
+        "compose_up" => {
+            "Subindo os contêineres com 'docker compose up -d': PHP, Apache, MariaDB e Node rodam isolados, sem instalar nada na sua máquina."
         }
-        Err(e) => {
-            eprintln!("\n Falha na execução: {}", e);
-            std::process::exit(1);
+        "compose_pull" => {
+            "Baixando as versões mais recentes das imagens com 'docker compose pull' antes de subir a stack."
+        }
+        "compose_build" => {
+            "Reconstruindo as imagens com 'docker compose build' para que mudanças nos Dockerfiles locais entrem na stack."
+        }
+        "create_project" => {
+            "Rodando 'composer create-project' dentro do contêiner PHP para baixar o esqueleto do Laravel e suas dependências em src/."
+        }
+        "scripts" => {
+            "Executando os scripts do Composer adiados (package:discover etc.), que o Laravel usa para registrar os pacotes instalados."
+        }
+        "db_user" => {
+            "Criando um usuário próprio no MariaDB para o projeto, para que a aplicação não precise da senha de root."
+        }
+        "env_config" => {
+            "Editando o .env do projeto: APP_URL aponta para o domínio local e DB_* aponta para o contêiner do MariaDB, não para 127.0.0.1."
+        }
+        "test_db" => {
+            "Criando o banco <nome>_testing no MariaDB, assim os testes rodam no mesmo motor da aplicação sem tocar nos seus dados."
+        }
+        "env_example" => {
+            "Levando para o .env.example os valores do ambiente Docker (host e porta do banco, APP_URL), sem a senha, para quem clonar o projeto."
+        }
+        "testing_env" => {
+            "Gerando o .env.testing para que os testes rodem contra um banco separado e não apaguem os seus dados."
+        }
+        "php_ini" => {
+            "Gravando o public/.user.ini com os ajustes de --php-ini; o PHP-FPM lê esse arquivo a cada diretório servido, sem editar o contêiner."
+        }
+        "migrate" => {
+            "Rodando 'php artisan migrate' para criar as tabelas no banco; é aqui que se descobre se a conexão com o MariaDB está correta."
+        }
+        "config_clear" => {
+            "Limpando o cache de configuração do Laravel para que as novas variáveis do .env passem a valer."
+        }
+        "optimize" => {
+            "Rodando 'php artisan config:cache', 'route:cache' e 'view:cache' para cachear configuração, rotas e views, deixando as requisições mais rápidas."
+        }
+        "composer" => {
+            "Atualizando as dependências PHP com o Composer para que o vendor/ fique consistente com o composer.json."
+        }
+        "npm" => {
+            "Instalando as dependências de frontend com npm no contêiner Node; o Vite precisa delas para compilar CSS e JS."
+        }
+        "vite" => {
+            "Ajustando o vite.config.js para que o servidor do Vite escute dentro do contêiner e o navegador consiga alcançá-lo."
+        }
+        "ownership" => {
+            "Corrigindo o dono dos arquivos criados pelo contêiner, para que você consiga editá-los sem sudo."
+        }
+        "vhost" => {
+            "Criando o VirtualHost do Apache: é ele que liga o domínio .test à pasta public/ do projeto e repassa o PHP ao php-fpm."
+        }
+        "hosts" => {
+            "Editando /etc/hosts para que o domínio .test do projeto resolva para 127.0.0.1 no seu navegador."
+        }
+        "restart" => {
+            "Reiniciando o Apache para que ele carregue o novo VirtualHost; sem isso o domínio não responde."
+        }
+        "health" => {
+            "Conferindo o resultado: contêineres ativos, .env, APP_KEY e vhost, para que você saiba se algo ficou pela metade."
         }
+        "verify" => {
+            "Fazendo uma requisição HTTP ao domínio do projeto para confirmar que Apache, PHP e Laravel respondem juntos."
+        }
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+// Início da etapa informada ou, sem nome, da etapa iniciada por último
+fn step_started_at(step: Option<&str>) -> Option<u64> {
+    let starts = STEP_STARTS.lock().ok()?;
+    starts
+        .iter()
+        .rev()
+        .find(|(name, _)| step.is_none_or(|step| *name == step))
+        .map(|(_, started)| *started)
+}
+
+fn step_service(step: &str) -> Option<String> {
+    let service = match step {
+        "npm" => "node",
+        "restart" | "verify" => "apache",
+        "db_user" | "test_db" => "mariadb",
+        "compose_up" | "create_project" | "scripts" | "env_config" | "env_example"
+        | "testing_env" | "php_ini" | "migrate" | "config_clear" | "optimize" | "composer"
+        | "vite" | "ownership" | "health" => PHP_SERVICE.get().map_or("php", String::as_str),
+        _ => return None,
+    };
+    Some(service.to_string())
+}
+
+fn emit_progress_event(step: &str, status: &str, message: Option<&str>) {
+    let events = match PROGRESS_EVENTS.get() {
+        Some(events) => events,
+        None => return,
+    };
+
+    let mut event = format!(
+        "{{\"step\":\"{}\",\"status\":\"{}\"",
+        json_escape(step),
+        status
+    );
+    if let Some(message) = message {
+        event.push_str(&format!(",\"message\":\"{}\"", json_escape(message)));
+    }
+    event.push_str("}\n");
+
+    if let Ok(mut events) = events.lock() {
+        let _ = events.write_all(event.as_bytes());
+        let _ = events.flush();
+    }
+}
+
+fn check_overall_budget(next_step: &str) -> Result<(), AppError> {
+    let (started, budget) = match OVERALL_BUDGET.get() {
+        Some(budget) => budget,
+        None => return Ok(()),
+    };
+
+    if started.elapsed() <= *budget {
+        return Ok(());
+    }
+
+    let completed = recorded_timings()
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<&str>>()
+        .join(", ");
+
+    Err(AppError::Interrupted(format!(
+        "Tempo total de {}s excedido antes da etapa '{}'. Etapas concluídas: {}.",
+        budget.as_secs(),
+        next_step,
+        if completed.is_empty() {
+            "nenhuma"
+        } else {
+            &completed
+        }
+    )))
+}
+
+fn recorded_timings() -> Vec<(&'static str, Duration)> {
+    STEP_TIMINGS
+        .lock()
+        .map(|timings| timings.clone())
+        .unwrap_or_default()
+}
+
+fn print_timing_summary() {
+    let timings = recorded_timings();
+    if timings.is_empty() {
+        return;
+    }
+
+    println!("Tempo por etapa:");
+    for (name, duration) in &timings {
+        println!("  {:<16} {:>8.1}s", name, duration.as_secs_f64());
+    }
+
+    let total: Duration = timings.iter().map(|(_, duration)| *duration).sum();
+    println!("  {:<16} {:>8.1}s", "total", total.as_secs_f64());
+}
+
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_json_summary(input: &ProjectInput, config: &AppConfig) -> String {
+    let timings = recorded_timings()
+        .iter()
+        .map(|(name, duration)| format!("\"{}\":{:.3}", name, duration.as_secs_f64()))
+        .collect::<Vec<String>>()
+        .join(",");
+
+    format!(
+        "{{\"project\":\"{}\",\"host\":\"{}\",\"url\":\"{}\",\"vite_patched\":{},\"timings\":{{{}}}}}",
+        json_escape(&input.project_name),
+        json_escape(&input.project_host),
+        json_escape(&project_url(input, config)),
+        config.patch_vite,
+        timings
+    )
+}
+
+#[derive(Debug, Clone)]
+struct RegistryEntry {
+    name: String,
+    host: String,
+    path: PathBuf,
+    label: String,
+    created_at: String,
+}
+
+fn registry_path() -> Option<PathBuf> {
+    find_project_root().map(|project_root| project_root.join(REGISTRY_FILE))
+}
+
+fn read_registry() -> Vec<RegistryEntry> {
+    let content = registry_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some(RegistryEntry {
+                name: fields.next()?.to_string(),
+                host: fields.next()?.to_string(),
+                path: PathBuf::from(fields.next()?),
+                label: fields.next().unwrap_or("").to_string(),
+                created_at: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .filter(|entry| !entry.name.is_empty())
+        .collect()
+}
+
+fn write_registry(entries: &[RegistryEntry]) -> Result<(), AppError> {
+    let path = match registry_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let content: String = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                entry.name,
+                entry.host,
+                entry.path.display(),
+                entry.label,
+                entry.created_at
+            )
+        })
+        .collect();
+    fs::write(path, content)?;
+    Ok(())
+}
+
+// Caminho da aplicação relativo ao web root (ex: grupo/projeto ou projeto/app)
+fn vhost_app_dir(vhost_content: &str) -> Option<String> {
+    vhost_content.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix("DocumentRoot")
+            .map(|root| root.trim().trim_matches('"'))
+            .and_then(|root| root.strip_prefix(&format!("{}/", CONTAINER_WEB_ROOT)))
+            .map(|rest| rest.trim_end_matches('/').trim_end_matches("/public"))
+            .filter(|dir| !dir.is_empty())
+            .map(str::to_string)
+    })
+}
+
+fn vhost_label(vhost_content: &str) -> Option<String> {
+    vhost_content
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(LABEL_MARKER))
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+}
+
+// Projetos conhecidos: registro do lara mais os vhosts gerados (que podem não estar no registro)
+fn known_projects() -> Vec<RegistryEntry> {
+    let mut projects: Vec<RegistryEntry> = Vec::new();
+    for entry in read_registry() {
+        if let Some(known) = projects.iter_mut().find(|known| known.name == entry.name) {
+            *known = entry;
+        } else {
+            projects.push(entry);
+        }
+    }
+
+    let vhosts_dir = match find_project_root() {
+        Some(project_root) => project_root.join(VHOSTS_DIR),
+        None => return projects,
+    };
+    for path in fs::read_dir(&vhosts_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+    {
+        let host = match path.file_name().and_then(|name| name.to_str()) {
+            Some(VHOST_INCLUDE_FILE) => continue,
+            Some(file_name) if file_name.ends_with(".conf") => {
+                file_name.trim_end_matches(".conf").to_string()
+            }
+            _ => continue,
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let app_dir = match vhost_app_dir(&content) {
+            Some(app_dir) => app_dir,
+            None => continue,
+        };
+
+        let label = vhost_label(&content).unwrap_or_default();
+        match projects.iter_mut().find(|known| known.host == host) {
+            Some(known) => {
+                if known.label.is_empty() {
+                    known.label = label;
+                }
+            }
+            None => projects.push(RegistryEntry {
+                name: host.trim_end_matches(".test").to_string(),
+                path: host_src_dir().join(&app_dir),
+                host,
+                label,
+                created_at: String::new(),
+            }),
+        }
+    }
+
+    let include_content =
+        fs::read_to_string(vhosts_dir.join(VHOST_INCLUDE_FILE)).unwrap_or_default();
+    for name in include_content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("# BEGIN "))
+    {
+        let block = extract_vhost_block(&include_content, name).unwrap_or_default();
+        let host = match block
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("ServerName"))
+        {
+            Some(host) => host.trim().to_string(),
+            None => continue,
+        };
+        let label = vhost_label(&block).unwrap_or_default();
+        match projects.iter_mut().find(|known| known.host == host) {
+            Some(known) => {
+                if known.label.is_empty() {
+                    known.label = label;
+                }
+            }
+            None => projects.push(RegistryEntry {
+                name: name.to_string(),
+                path: host_src_dir()
+                    .join(vhost_app_dir(&block).unwrap_or_else(|| name.to_string())),
+                host,
+                label,
+                created_at: String::new(),
+            }),
+        }
+    }
+
+    projects
+}
+
+// Projeto já conhecido (registro ou vhost) com o mesmo nome ou host, mas apontando para outro diretório
+fn registry_conflict(name: &str, host: &str, project_dir: &str) -> Option<RegistryEntry> {
+    let project_path = host_src_dir().join(project_dir);
+    known_projects().into_iter().find(|entry| {
+        (entry.name == name || entry.host == host) && !entry.path.starts_with(&project_path)
+    })
+}
+
+fn registry_conflict_message(entry: &RegistryEntry, name: &str) -> String {
+    if entry.name == name {
+        format!(
+            "O projeto '{}' já está registrado com o host '{}' em {}.",
+            entry.name,
+            entry.host,
+            entry.path.display()
+        )
+    } else {
+        format!(
+            "O host '{}' já está registrado para o projeto '{}' em {}.",
+            entry.host,
+            entry.name,
+            entry.path.display()
+        )
+    }
+}
+
+fn is_batch_child() -> bool {
+    env::var_os(BATCH_CHILD_ENV).is_some()
+}
+
+// Nos processos filhos do batch, /etc/hosts, o registro e o projects.conf são editados por
+// vários lara ao mesmo tempo; um arquivo de lock na raiz serializa essas escritas
+fn with_shared_lock<T>(f: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    let lock_path = match find_project_root() {
+        Some(project_root) if is_batch_child() => project_root.join(SHARED_LOCK_FILE),
+        _ => return f(),
+    };
+
+    let lock = acquire_file_lock(&lock_path, Duration::from_secs(SHARED_LOCK_TIMEOUT_SECS))?;
+    let result = f();
+    drop(lock);
+    result
+}
+
+// flock no arquivo, que nunca é apagado: o kernel solta o lock quando o processo termina,
+// então não existe lock órfão para alguém remover por baixo de quem acabou de pegá-lo
+fn acquire_file_lock(lock_path: &Path, timeout: Duration) -> Result<fs::File, AppError> {
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+
+    let started = Instant::now();
+    loop {
+        match lock.try_lock() {
+            Ok(()) => return Ok(lock),
+            Err(fs::TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                thread::sleep(Duration::from_millis(100));
+            }
+            Err(fs::TryLockError::WouldBlock) => {
+                return Err(AppError::Validation(format!(
+                    "Tempo esgotado aguardando o lock {}. Outro lara ainda está gravando o estado compartilhado.",
+                    lock_path.display()
+                )));
+            }
+            Err(fs::TryLockError::Error(e)) => return Err(AppError::Io(e)),
+        }
+    }
+}
+
+struct BatchJob {
+    name: String,
+    spec: PathBuf,
+    log: PathBuf,
+    started: Instant,
+    child: std::process::Child,
+}
+
+struct BatchResult {
+    name: String,
+    ok: bool,
+    elapsed: Duration,
+    log: PathBuf,
+}
+
+// Argumentos da linha de comando repassados a cada filho, sem o subcomando e seus specs
+fn batch_forwarded_args(specs: &[PathBuf]) -> Vec<String> {
+    let mut forwarded = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--concurrency" {
+            args.next();
+        } else if arg != "batch"
+            && !arg.starts_with("--concurrency=")
+            && !specs.iter().any(|spec| spec.as_os_str() == arg.as_str())
+        {
+            forwarded.push(arg);
+        }
+    }
+    forwarded
+}
+
+// Cada projeto roda em um processo lara próprio (--from-spec), com até 'concurrency' ao mesmo tempo.
+// A stack sobe uma vez antes dos filhos e o Apache reinicia uma vez no fim.
+fn run_batch(specs: &[PathBuf], concurrency: usize, config: &AppConfig) -> Result<(), AppError> {
+    if specs.is_empty() {
+        return Err(AppError::Validation(
+            "Informe ao menos um spec: lara batch <spec>... [--concurrency N]".to_string(),
+        ));
+    }
+
+    let mut queue = Vec::new();
+    for spec_path in specs {
+        let name = apply_project_spec(spec_path, &mut CliOptions::default())?.name;
+        if queue
+            .iter()
+            .any(|(queued, _): &(String, PathBuf)| *queued == name)
+        {
+            return Err(AppError::Validation(format!(
+                "O projeto '{}' aparece em mais de um spec do batch.",
+                name
+            )));
+        }
+        queue.push((name, spec_path.clone()));
+    }
+    queue.reverse();
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Batch: {} projeto(s), até {} em paralelo",
+                queue.len(),
+                concurrency
+            )
+        )
+    );
+
+    validate_compose_file(config)?;
+    timed_step("compose_up", || ensure_stack_running(config))?;
+
+    let log_dir = find_project_root()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(BATCH_LOG_DIR);
+    fs::create_dir_all(&log_dir)?;
+    let executable = env::current_exe()?;
+    let forwarded = batch_forwarded_args(specs);
+    let total = queue.len();
+
+    let mut running: Vec<BatchJob> = Vec::new();
+    let mut results: Vec<BatchResult> = Vec::new();
+    while !queue.is_empty() || !running.is_empty() {
+        while running.len() < concurrency
+            && let Some((name, spec)) = queue.pop()
+        {
+            let log = log_dir.join(format!("{}.log", name));
+            let log_file = fs::File::create(&log)?;
+            let child = Command::new(&executable)
+                .args(&forwarded)
+                .arg("--from-spec")
+                .arg(&spec)
+                .arg("--yes")
+                .env(BATCH_CHILD_ENV, "1")
+                .stdin(Stdio::null())
+                .stdout(log_file.try_clone()?)
+                .stderr(log_file)
+                .traced()
+                .spawn()
+                .map_err(|e| {
+                    AppError::Validation(format!("Falha ao iniciar o lara para '{}': {}", name, e))
+                        .with_source(command_context(e))
+                })?;
+            println!(
+                "[{}/{}] Iniciado: {} ({})",
+                results.len() + running.len() + 1,
+                total,
+                name,
+                spec.display()
+            );
+            running.push(BatchJob {
+                name,
+                spec,
+                log,
+                started: Instant::now(),
+                child,
+            });
+        }
+
+        let mut index = 0;
+        while index < running.len() {
+            match running[index].child.try_wait()? {
+                Some(status) => {
+                    let job = running.remove(index);
+                    let elapsed = job.started.elapsed();
+                    println!(
+                        "{} {} ({:.1}s)",
+                        if status.success() { "✅" } else { "❌" },
+                        job.name,
+                        elapsed.as_secs_f64()
+                    );
+                    if !status.success() {
+                        eprintln!(
+                            "{}",
+                            paint(
+                                Tone::Error,
+                                &format!(
+                                    "'{}' ({}) falhou. Últimas linhas de {}:\n{}",
+                                    job.name,
+                                    job.spec.display(),
+                                    job.log.display(),
+                                    output_tail(
+                                        &fs::read_to_string(&job.log).unwrap_or_default(),
+                                        10
+                                    )
+                                )
+                            )
+                        );
+                    }
+                    results.push(BatchResult {
+                        name: job.name,
+                        ok: status.success(),
+                        elapsed,
+                        log: job.log,
+                    });
+                }
+                None => index += 1,
+            }
+        }
+        if !running.is_empty() {
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    if results.iter().any(|result| result.ok) {
+        timed_step("restart", || restart_apache_container(config))?;
+    }
+
+    println!("\n{:<28} {:<8} {:>8}  LOG", "PROJETO", "STATUS", "TEMPO");
+    for result in &results {
+        println!(
+            "{:<28} {:<8} {:>7.1}s  {}",
+            result.name,
+            if result.ok { "ok" } else { "falhou" },
+            result.elapsed.as_secs_f64(),
+            result.log.display()
+        );
+    }
+
+    let failed = results.iter().filter(|result| !result.ok).count();
+    if failed > 0 {
+        return Err(AppError::Validation(format!(
+            "{} de {} projeto(s) do batch falharam. Veja os logs em {}.",
+            failed,
+            results.len(),
+            log_dir.display()
+        )));
+    }
+    Ok(())
+}
+
+fn list_projects(config: &AppConfig) -> Result<(), AppError> {
+    let projects: Vec<RegistryEntry> = known_projects()
+        .into_iter()
+        .filter(|project| {
+            config
+                .label
+                .as_ref()
+                .is_none_or(|label| &project.label == label)
+        })
+        .collect();
+
+    if projects.is_empty() {
+        match &config.label {
+            Some(label) => println!("Nenhum projeto com o label '{}'.", label),
+            None => println!("Nenhum projeto encontrado."),
+        }
+        return Ok(());
+    }
+
+    for project in &projects {
+        println!(
+            "{} {}\t{}\t{}\t{}",
+            if project.path.exists() { "✅" } else { "❌" },
+            project.name,
+            project.host,
+            if project.label.is_empty() {
+                "-"
+            } else {
+                &project.label
+            },
+            project.path.display()
+        );
+    }
+    Ok(())
+}
+
+fn delete_projects(name: Option<&str>, config: &AppConfig) -> Result<(), AppError> {
+    let targets: Vec<RegistryEntry> = match (name, &config.label) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::Validation(
+                "Use 'delete <nome>' ou 'delete --label <label>', não os dois.".to_string(),
+            ));
+        }
+        (None, None) => {
+            return Err(AppError::Validation(
+                "Informe o projeto ('delete <nome>') ou um label ('delete --label <label>')."
+                    .to_string(),
+            ));
+        }
+        (Some(name), None) => known_projects()
+            .into_iter()
+            .filter(|project| project.name == name)
+            .collect(),
+        (None, Some(label)) => known_projects()
+            .into_iter()
+            .filter(|project| &project.label == label)
+            .collect(),
+    };
+
+    if targets.is_empty() {
+        return Err(AppError::Validation(match (name, &config.label) {
+            (Some(name), _) => format!(
+                "Projeto '{}' não encontrado no registro nem nos vhosts.",
+                name
+            ),
+            (_, Some(label)) => format!("Nenhum projeto com o label '{}'.", label),
+            _ => "Nenhum projeto encontrado.".to_string(),
+        }));
+    }
+
+    println!(
+        "{}",
+        paint(Tone::Warning, "Os seguintes projetos serão removidos:")
+    );
+    for project in &targets {
+        println!(
+            "- {} ({}) em {}",
+            project.name,
+            project.host,
+            project.path.display()
+        );
+    }
+
+    let data_plans: Vec<ProjectData> = if config.with_data {
+        targets
+            .iter()
+            .map(|project| project_data_plan(project, config))
+            .collect::<Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+    print_project_data_plans(&data_plans);
+
+    if !config.assume_yes
+        && !confirm(
+            "Remover os projetos listados, seus vhosts e entradas de hosts?",
+            false,
+        )?
+    {
+        println!("Remoção cancelada.");
+        return Ok(());
+    }
+
+    let has_data = data_plans.iter().any(|plan| !plan.is_empty());
+    if has_data
+        && !config.assume_yes
+        && !confirm(
+            "Apagar DEFINITIVAMENTE os bancos, usuários e volumes listados acima?",
+            false,
+        )?
+    {
+        println!("Remoção cancelada. Nenhum dado foi apagado.");
+        return Ok(());
+    }
+
+    // Os dados saem antes dos arquivos: se o banco falhar, o projeto continua lá para uma nova tentativa
+    let mut removed_data = Vec::new();
+    for plan in &data_plans {
+        removed_data.extend(remove_project_data(plan, config)?);
+    }
+
+    let src_dir = resolved_src_dir();
+    let vhosts_dir = find_project_root().map(|project_root| project_root.join(VHOSTS_DIR));
+    let mut vhost_removed = false;
+
+    for project in &targets {
+        if fs::symlink_metadata(&project.path).is_ok() {
+            remove_project_dir(&project.path, &src_dir, true)?;
+        }
+
+        if let Some(vhost_path) = vhosts_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.conf", project.host)))
+            .filter(|path| path.exists())
+        {
+            fs::remove_file(&vhost_path)?;
+            println!("Vhost removido: {}", vhost_path.display());
+            vhost_removed = true;
+        }
+        if let Some(vhosts_dir) = &vhosts_dir
+            && remove_vhost_include_block(vhosts_dir, &project.name)?
+        {
+            vhost_removed = true;
+        }
+    }
+
+    let removed_hosts: Vec<String> = targets.iter().map(|project| project.host.clone()).collect();
+    remove_hosts_entries(&removed_hosts, &LOOPBACK_ADDRESSES, config)?;
+
+    let mut registry = read_registry();
+    registry.retain(|entry| !removed_hosts.contains(&entry.host));
+    write_registry(&registry)?;
+
+    if vhost_removed {
+        restart_apache_container(config)?;
+    }
+
+    if config.with_data {
+        if removed_data.is_empty() {
+            println!("Nenhum dado de banco ou volume foi removido.");
+        } else {
+            println!("Dados removidos:");
+            for item in &removed_data {
+                println!("- {}", item);
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Success,
+            &format!("{} projeto(s) removido(s).", targets.len())
+        )
+    );
+    Ok(())
+}
+
+// O que 'delete --with-data' apaga de um projeto, levantado antes da confirmação
+struct ProjectData {
+    project: String,
+    databases: Vec<String>,
+    user: Option<String>,
+    volumes: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl ProjectData {
+    fn is_empty(&self) -> bool {
+        self.databases.is_empty() && self.user.is_none() && self.volumes.is_empty()
+    }
+}
+
+fn is_safe_sql_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn query_db_root(config: &AppConfig, sql: &str) -> Result<String, AppError> {
+    capture_command_in_container(
+        &config.db_container_name,
+        &[
+            "mariadb",
+            "-uroot",
+            &format!("-p{}", config.db_root_password),
+            "-N",
+            "-e",
+            sql,
+        ],
+    )
+}
+
+// Lê o .env do projeto para achar banco e usuário reais; bancos remotos, sqlite e bancos
+// compartilhados (DB_TABLE_PREFIX) nunca entram na lista
+fn project_data_plan(project: &RegistryEntry, config: &AppConfig) -> Result<ProjectData, AppError> {
+    let mut plan = ProjectData {
+        project: project.name.clone(),
+        databases: Vec::new(),
+        user: None,
+        volumes: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    let env_content = std::iter::once(project.path.clone())
+        .chain(
+            config
+                .app_subdir
+                .iter()
+                .map(|subdir| project.path.join(subdir)),
+        )
+        .find_map(|dir| fs::read_to_string(dir.join(".env")).ok())
+        .unwrap_or_default();
+
+    // Só volumes que o Compose rotulou com o projeto; um prefixo no nome pegaria volumes
+    // criados à mão ou de outro projeto com nome parecido (demo-app x demo-app_admin)
+    if config.with_volumes {
+        let compose_project = env_value(&env_content, "COMPOSE_PROJECT_NAME")
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| project.name.clone());
+        let filter = format!("label=com.docker.compose.project={}", compose_project);
+        plan.volumes = command_stdout("docker", &["volume", "ls", "-q", "--filter", &filter])
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|volume| !volume.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+
+    if env_value(&env_content, "DB_CONNECTION").is_some_and(|connection| connection == "sqlite") {
+        plan.skipped
+            .push("usa sqlite; o arquivo do banco sai junto com o diretório".to_string());
+        return Ok(plan);
+    }
+    if let Some(host) = env_value(&env_content, "DB_HOST")
+        && !is_local_db_host(&host, config)
+    {
+        plan.skipped.push(format!(
+            "DB_HOST '{}' não é o MariaDB local; o banco não será tocado",
+            host
+        ));
+        return Ok(plan);
+    }
+
+    let database = env_value(&env_content, "DB_DATABASE").unwrap_or_else(|| project.name.clone());
+    if env_value(&env_content, "DB_TABLE_PREFIX").is_some()
+        || config.shared_db.as_deref() == Some(database.as_str())
+    {
+        plan.skipped.push(format!(
+            "o banco '{}' é compartilhado com outros projetos e não será apagado",
+            database
+        ));
+        return Ok(plan);
+    }
+    if !is_safe_sql_identifier(&database) {
+        plan.skipped.push(format!(
+            "nome de banco inesperado '{}'; remova manualmente",
+            database
+        ));
+        return Ok(plan);
+    }
+
+    if !matches!(
+        check_container_is_running(&config.db_container_name),
+        Ok(true)
+    ) {
+        plan.skipped.push(format!(
+            "contêiner '{}' parado; inicie a stack para apagar o banco '{}'",
+            config.db_container_name, database
+        ));
+        return Ok(plan);
+    }
+
+    let testing = format!("{}_testing", database);
+    plan.databases = query_db_root(config, "SHOW DATABASES")?
+        .lines()
+        .map(str::trim)
+        .filter(|name| *name == database || *name == testing)
+        .map(str::to_string)
+        .collect();
+
+    if let Some(user) = env_value(&env_content, "DB_USERNAME")
+        && user != "root"
+        && is_safe_sql_identifier(&user)
+        && query_db_root(
+            config,
+            &format!("SELECT COUNT(*) FROM mysql.user WHERE User = '{}'", user),
+        )?
+        .trim()
+            != "0"
+    {
+        plan.user = Some(user);
+    }
+
+    Ok(plan)
+}
+
+fn print_project_data_plans(plans: &[ProjectData]) {
+    if plans.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        paint(
+            Tone::Warning,
+            "--with-data: os dados abaixo serão apagados sem possibilidade de recuperação:"
+        )
+    );
+    for plan in plans {
+        for database in &plan.databases {
+            println!("- {}: banco '{}'", plan.project, database);
+        }
+        if let Some(user) = &plan.user {
+            println!("- {}: usuário '{}'", plan.project, user);
+        }
+        for volume in &plan.volumes {
+            println!("- {}: volume '{}'", plan.project, volume);
+        }
+        for reason in &plan.skipped {
+            println!("- {}: ignorado ({})", plan.project, reason);
+        }
+        if plan.is_empty() && plan.skipped.is_empty() {
+            println!("- {}: nenhum banco ou volume encontrado", plan.project);
+        }
+    }
+}
+
+fn remove_project_data(plan: &ProjectData, config: &AppConfig) -> Result<Vec<String>, AppError> {
+    let mut removed = Vec::new();
+    if !plan.databases.is_empty() || plan.user.is_some() {
+        let mut sql: String = plan
+            .databases
+            .iter()
+            .map(|database| format!("DROP DATABASE IF EXISTS `{}`; ", database))
+            .collect();
+        if let Some(user) = &plan.user {
+            sql.push_str(&format!("DROP USER IF EXISTS '{}'@'%'; ", user));
+        }
+        run_db_root_sql(config, sql.trim_end())?;
+        removed.extend(
+            plan.databases
+                .iter()
+                .map(|database| format!("banco '{}' ({})", database, plan.project)),
+        );
+        removed.extend(
+            plan.user
+                .iter()
+                .map(|user| format!("usuário '{}' ({})", user, plan.project)),
+        );
+    }
+
+    for volume in &plan.volumes {
+        let status = Command::new("docker")
+            .args(["volume", "rm", volume])
+            .stdout(Stdio::null())
+            .traced()
+            .status();
+        if status.is_ok_and(|status| status.success()) {
+            removed.push(format!("volume '{}' ({})", volume, plan.project));
+        } else {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Não foi possível remover o volume '{}' (ainda em uso?).",
+                        volume
+                    )
+                )
+            );
+        }
+    }
+    Ok(removed)
+}
+
+// Remove só os nomes dos projetos; a linha some apenas quando não sobra nenhum outro nome
+fn strip_hosts_entries(content: &str, hosts: &[String], addresses: &[&str]) -> Option<String> {
+    let mut changed = false;
+    let mut kept: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        let (data, comment) = match line.find('#') {
+            Some(index) => (&line[..index], Some(line[index..].trim_end())),
+            None => (line, None),
+        };
+        let mut fields = data.split_whitespace();
+        let address = fields.next();
+        let names: Vec<&str> = fields.collect();
+        if !address.is_some_and(|address| addresses.contains(&address))
+            || !names
+                .iter()
+                .any(|name| hosts.iter().any(|host| host == name))
+        {
+            kept.push(line.to_string());
+            continue;
+        }
+
+        changed = true;
+        let remaining: Vec<&str> = names
+            .into_iter()
+            .filter(|name| !hosts.iter().any(|host| host == name))
+            .collect();
+        if remaining.is_empty() {
+            continue;
+        }
+        let mut rebuilt = format!("{} {}", address.unwrap_or_default(), remaining.join(" "));
+        // O marcador de label é do lara; comentários do usuário ficam
+        if let Some(comment) = comment.filter(|comment| !comment.starts_with(LABEL_MARKER)) {
+            rebuilt.push(' ');
+            rebuilt.push_str(comment);
+        }
+        kept.push(rebuilt);
+    }
+
+    if !changed {
+        return None;
+    }
+    let mut new_content = kept.join("\n");
+    new_content.push('\n');
+    Some(new_content)
+}
+
+fn remove_hosts_entries(
+    hosts: &[String],
+    addresses: &[&str],
+    config: &AppConfig,
+) -> Result<(), AppError> {
+    let hosts_file_path = "/etc/hosts";
+    let content = fs::read_to_string(hosts_file_path)?;
+
+    let new_content = match strip_hosts_entries(&content, hosts, addresses) {
+        Some(new_content) => new_content,
+        None => return Ok(()),
+    };
+
+    if writes_hosts_directly(config) {
+        fs::write(hosts_file_path, new_content)?;
+    } else {
+        let mut child = privileged_command(config)
+            .args(["sh", "-c", &format!("cat > {}", hosts_file_path)])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| privileged_spawn_error(e, config))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(new_content.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(AppError::Validation(format!(
+                "Falha ao atualizar {}. Status: {:?}",
+                hosts_file_path, status
+            )));
+        }
+    }
+
+    println!(
+        "Entradas removidas de {}: {}",
+        hosts_file_path,
+        hosts.join(", ")
+    );
+    Ok(())
+}
+
+fn existing_project_input(
+    name: &str,
+    command: &str,
+    config: &AppConfig,
+) -> Result<ProjectInput, AppError> {
+    let project_name = format_project_name(name.trim(), config.naming_style);
+    if project_name != name.trim() || project_name.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Nome de projeto inválido para {}: '{}'. Use o nome exato do diretório ({}).",
+            command,
+            name,
+            config.naming_style.describe()
+        )));
+    }
+
+    let project_host = project_host_for(&project_name);
+    validate_dns_host(&project_host).map_err(|reason| {
+        AppError::Validation(format!(
+            "'{}' não é um hostname válido: {}",
+            project_host, reason
+        ))
+    })?;
+
+    let project_dir = project_dir_name(&project_name, config);
+    Ok(ProjectInput {
+        project_path: format!("../src/{}", project_dir),
+        project_name,
+        project_host,
+        project_dir,
+        laravel_version: "existente".to_string(),
+        octane: false,
+        label: config.label.clone(),
+    })
+}
+
+fn resolve_existing_project(
+    name: &str,
+    command: &str,
+    config: &AppConfig,
+) -> Result<ProjectInput, AppError> {
+    let input = existing_project_input(name, command, config)?;
+
+    ensure_stack_running(config)?;
+
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, input.project_dir);
+    let layout = capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "if [ ! -d '{dir}' ]; then echo missing; elif [ ! -d '{dir}/public' ]; then echo no-public; else echo ok; fi",
+                dir = project_dir
+            ),
+        ],
+    )?;
+
+    match layout.as_str() {
+        "ok" => {}
+        "no-public" => {
+            return Err(AppError::Validation(format!(
+                "O projeto {} não possui o diretório public/. Verifique se é um projeto Laravel.",
+                project_dir
+            )));
+        }
+        _ => {
+            return Err(AppError::Validation(format!(
+                "O diretório {} não existe no contêiner '{}'.",
+                project_dir, config.php_container_name
+            )));
+        }
+    }
+
+    Ok(input)
+}
+
+fn print_project_status(name: &str, config: &AppConfig) -> Result<(), AppError> {
+    let input = existing_project_input(name, "status", config)?;
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Estado do projeto '{}'", input.project_name)
+        )
+    );
+
+    let mut checks: Vec<(String, bool)> = Vec::new();
+
+    let php_running = matches!(
+        check_container_is_running(&config.php_container_name),
+        Ok(true)
+    );
+    checks.push((
+        format!("Contêiner '{}' em execução", config.php_container_name),
+        php_running,
+    ));
+    checks.push((
+        format!("Contêiner '{}' em execução", config.db_container_name),
+        matches!(
+            check_container_is_running(&config.db_container_name),
+            Ok(true)
+        ),
+    ));
+
+    checks.push((
+        format!("Vhost {}", vhost_location(&input, config)),
+        project_vhost_content(&input, config).is_some(),
+    ));
+
+    checks.push((
+        format!("Entrada '{}' em /etc/hosts", input.project_host),
+        fs::read_to_string("/etc/hosts")
+            .is_ok_and(|content| hosts_content_has_entry(&content, &input.project_host)),
+    ));
+
+    let url = project_url(&input, config);
+    let http_status = fetch_http_status(&url);
+    checks.push((
+        format!(
+            "{} responde ({})",
+            url,
+            http_status
+                .map(|code| format!("HTTP {}", code))
+                .unwrap_or_else(|| "sem resposta".to_string())
+        ),
+        http_status == Some(200),
+    ));
+
+    let env_app_url = if php_running {
+        capture_command_in_container(
+            &config.php_container_name,
+            &[
+                "sh",
+                "-c",
+                &format!(
+                    "grep '^APP_URL=' '{}/.env' 2>/dev/null || true",
+                    container_app_dir(&input, config)
+                ),
+            ],
+        )
+        .ok()
+        .and_then(|line| line.strip_prefix("APP_URL=").map(str::to_string))
+    } else {
+        None
+    };
+
+    for (description, ok) in &checks {
+        println!("{} {}", if *ok { "✅" } else { "❌" }, description);
+    }
+    println!(
+        "APP_URL no .env: {}",
+        env_app_url.as_deref().unwrap_or("indisponível")
+    );
+
+    let failed = checks.iter().filter(|(_, ok)| !ok).count();
+    if failed > 0 {
+        return Err(AppError::Validation(format!(
+            "Projeto '{}' com {} de {} verificações falhando.",
+            input.project_name,
+            failed,
+            checks.len()
+        )));
+    }
+    Ok(())
+}
+
+fn attach_existing_project(name: &str, config: &AppConfig) -> Result<(), AppError> {
+    let input = resolve_existing_project(name, "attach", config)?;
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, input.project_dir);
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Anexando o projeto existente '{}'", input.project_name)
+        )
+    );
+
+    execute_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd '{}' && [ -f .env ] || cp .env.example .env",
+                project_dir
+            ),
+        ],
+    )?;
+
+    timed_step("env_config", || configure_project_env(&input, config))?;
+    if !config.php_ini.is_empty() {
+        timed_step("php_ini", || write_php_ini(&input, config))?;
+    }
+    timed_step("migrate", || run_artisan_setup(&input, config))?;
+    let vhost_changed = timed_step("vhost", || create_vhost_file(&input, config))?;
+    timed_step("hosts", || update_etc_hosts(&input, config))?;
+    if vhost_changed {
+        timed_step("restart", || restart_apache_container(config))?;
+    } else {
+        println!("Vhost inalterado, reinício ignorado.");
+    }
+
+    record_project_in_registry(&input);
+
+    println!("\n---");
+    println!(
+        "{}",
+        paint(
+            Tone::Success,
+            &format!("Projeto '{}' anexado com sucesso!", input.project_name)
+        )
+    );
+    println!("Domínio de acesso: {}", project_url(&input, config));
+    println!("---");
+
+    print_timing_summary();
+    Ok(())
+}
+
+fn reconfigure_existing_project(name: &str, config: &AppConfig) -> Result<(), AppError> {
+    let input = resolve_existing_project(name, "reconfigure", config)?;
+
+    let env_present = capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "[ -f '{}/.env' ] && echo ok || true",
+                container_app_dir(&input, config)
+            ),
+        ],
+    )? == "ok";
+    if !env_present {
+        return Err(AppError::Validation(format!(
+            "O projeto '{}' não possui .env. Use 'lara attach {}' para configurá-lo do zero.",
+            input.project_name, input.project_name
+        )));
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Reaplicando a configuração de banco e APP_URL em '{}'",
+                input.project_name
+            )
+        )
+    );
+
+    timed_step("env_config", || apply_env_replacements(&input, config))?;
+    if !config.php_ini.is_empty() {
+        timed_step("php_ini", || write_php_ini(&input, config))?;
+    }
+    timed_step("config_clear", || run_config_clear(&input, config))?;
+
+    println!(
+        "{}",
+        paint(
+            Tone::Success,
+            &format!("Projeto '{}' reconfigurado.", input.project_name)
+        )
+    );
+
+    print_timing_summary();
+    Ok(())
+}
+
+fn clean_orphaned_projects(config: &AppConfig) -> Result<(), AppError> {
+    let project_root = find_project_root().ok_or_else(|| {
+        AppError::Validation(
+            "Não foi possível determinar o diretório raiz do dev-container.".to_string(),
+        )
+    })?;
+
+    let src_dir = host_src_dir();
+    if !src_dir.is_dir() {
+        return Err(AppError::Validation(format!(
+            "O diretório de projetos {} não existe. Limpeza cancelada para evitar remoções indevidas.",
+            src_dir.display()
+        )));
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Procurando vhosts e entradas de projetos removidos..."
+        )
+    );
+
+    let mut orphans: Vec<(String, Option<PathBuf>)> = Vec::new();
+    let vhosts_dir = project_root.join(VHOSTS_DIR);
+    for entry in fs::read_dir(&vhosts_dir)?.filter_map(Result::ok) {
+        let path = entry.path();
+        let host = match path.file_name().and_then(|name| name.to_str()) {
+            Some(VHOST_INCLUDE_FILE) => continue,
+            Some(file_name) if file_name.ends_with(".conf") => {
+                file_name.trim_end_matches(".conf").to_string()
+            }
+            _ => continue,
+        };
+
+        let app_dir = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| vhost_app_dir(&content));
+        if let Some(app_dir) = app_dir
+            && !src_dir.join(&app_dir).exists()
+        {
+            orphans.push((host, Some(path)));
+        }
+    }
+
+    let mut registry = read_registry();
+    for entry in &registry {
+        if !entry.path.exists() && !orphans.iter().any(|(host, _)| host == &entry.host) {
+            orphans.push((entry.host.clone(), None));
+        }
+    }
+
+    if orphans.is_empty() {
+        println!("Nenhum recurso órfão encontrado.");
+        return Ok(());
+    }
+
+    let mut removed_hosts = Vec::new();
+    let mut vhost_removed = false;
+
+    for (host, vhost_path) in orphans {
+        if !config.assume_yes
+            && !confirm(
+                &format!("Remover o vhost e a entrada de hosts órfãos de '{}'?", host),
+                false,
+            )?
+        {
+            continue;
+        }
+
+        if let Some(vhost_path) = vhost_path {
+            fs::remove_file(&vhost_path)?;
+            println!("Vhost removido: {}", vhost_path.display());
+            vhost_removed = true;
+        }
+        removed_hosts.push(host);
+    }
+
+    if removed_hosts.is_empty() {
+        println!("Nenhum recurso removido.");
+        return Ok(());
+    }
+
+    remove_hosts_entries(&removed_hosts, &LOOPBACK_ADDRESSES, config)?;
+
+    registry.retain(|entry| !removed_hosts.contains(&entry.host));
+    write_registry(&registry)?;
+
+    if vhost_removed {
+        restart_apache_container(config)?;
+    }
+
+    Ok(())
+}
+
+fn record_project_in_registry(input: &ProjectInput) {
+    let registry_path = match registry_path() {
+        Some(registry_path) => registry_path,
+        None => return,
+    };
+
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    // Entradas antigas com o mesmo nome ou host são substituídas para o registro não ter duplicatas
+    let mut registry = read_registry();
+    registry.retain(|entry| entry.name != input.project_name && entry.host != input.project_host);
+    registry.push(RegistryEntry {
+        name: input.project_name.clone(),
+        host: input.project_host.clone(),
+        path: host_project_dir(input),
+        label: input.label.clone().unwrap_or_default(),
+        created_at: created_at.to_string(),
+    });
+
+    if let Err(e) = write_registry(&registry) {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Não foi possível registrar o projeto em {}: {}",
+                    registry_path.display(),
+                    e
+                )
+            )
+        );
+    }
+}
+
+fn write_project_info(input: &ProjectInput, config: &AppConfig, json: bool) {
+    let project_dir = host_project_dir(input);
+    let url = project_url(input, config);
+
+    let (file_name, content) = if json {
+        (
+            ".project-info.json",
+            format!(
+                "{{\"project\":\"{}\",\"url\":\"{}\",\"host\":\"{}\",\"port\":{},\"db\":{{\"host\":\"{}\",\"port\":{},\"host_port\":{},\"database\":\"{}\",\"username\":\"{}\",\"password\":\"{}\"}},\"start\":\"docker compose up -d\",\"stop\":\"docker compose down\",\"hosts_entry\":\"127.0.0.1 {}\"}}\n",
+                json_escape(&input.project_name),
+                json_escape(&url),
+                json_escape(&input.project_host),
+                project_port(config),
+                json_escape(db_host(config)),
+                config.db_container_port,
+                config.db_port,
+                json_escape(&database_name(input, config)),
+                json_escape(&db_username(input, config)),
+                json_escape(db_password(config)),
+                json_escape(&input.project_host)
+            ),
+        )
+    } else {
+        (
+            "PROJECT-INFO.md",
+            format!(
+                "# {name}\n\n\
+                 ## Acesso\n\n\
+                 - URL: {url}\n\
+                 - Host: {host}\n\
+                 - Porta: {port}\n\n\
+                 ## Banco de dados\n\n\
+                 - Host: {db_host} (porta {db_container_port})\n\
+                 - Acesso pelo host: 127.0.0.1:{db_port}\n\
+                 - Banco: {database}\n\
+                 - Usuário: {username}\n\
+                 - Senha: {password}\n\n\
+                 ## Stack\n\n\
+                 - Iniciar: `docker compose up -d`\n\
+                 - Parar: `docker compose down`\n\n\
+                 O domínio depende da entrada `127.0.0.1 {host}` no /etc/hosts.\n",
+                name = input.project_name,
+                url = url,
+                host = input.project_host,
+                port = project_port(config),
+                db_host = db_host(config),
+                db_container_port = config.db_container_port,
+                db_port = config.db_port,
+                database = database_name(input, config),
+                username = db_username(input, config),
+                password = db_password(config)
+            ),
+        )
+    };
+
+    let info_path = project_dir.join(file_name);
+    match fs::write(&info_path, content) {
+        Ok(()) => println!("Informações de acesso salvas em {}", info_path.display()),
+        Err(e) => eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Não foi possível gravar {}: {}",
+                    info_path.display(),
+                    e
+                )
+            )
+        ),
+    }
+}
+
+fn render_project_makefile(input: &ProjectInput, config: &AppConfig, compose_dir: &Path) -> String {
+    let compose = std::iter::once("docker compose".to_string())
+        .chain(
+            config
+                .compose_profiles
+                .iter()
+                .map(|profile| format!("--profile {}", profile)),
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+    let exec_user = config
+        .exec_user
+        .as_ref()
+        .map(|user| format!(" -u {}", user))
+        .unwrap_or_default();
+
+    format!(
+        "# Gerado pelo laravel-maker. Uso: make artisan ARGS=\"migrate:status\"\n\
+         COMPOSE_DIR := {compose_dir}\n\
+         PHP_CONTAINER := {php_container}\n\
+         NODE_CONTAINER := {node_container}\n\
+         APP_DIR := {app_dir}\n\
+         EXEC := docker exec -it{exec_user} -w $(APP_DIR)\n\
+         ARGS ?=\n\n\
+         .PHONY: up down artisan composer npm test\n\n\
+         up:\n\tcd \"$(COMPOSE_DIR)\" && {compose} up -d\n\n\
+         down:\n\tcd \"$(COMPOSE_DIR)\" && {compose} down\n\n\
+         artisan:\n\t$(EXEC) $(PHP_CONTAINER) {php} artisan $(ARGS)\n\n\
+         composer:\n\t$(EXEC) $(PHP_CONTAINER) {composer} $(ARGS)\n\n\
+         npm:\n\t$(EXEC) $(NODE_CONTAINER) npm $(ARGS)\n\n\
+         test:\n\t$(EXEC) $(PHP_CONTAINER) {php} artisan test $(ARGS)\n",
+        compose_dir = compose_dir.display(),
+        php_container = config.php_container_name,
+        node_container = config.node_container_name,
+        app_dir = container_app_dir(input, config),
+        exec_user = exec_user,
+        compose = compose,
+        php = config.php_bin,
+        composer = config.composer_bin
+    )
+}
+
+fn write_project_makefile(input: &ProjectInput, config: &AppConfig) {
+    let makefile_path = host_project_dir(input).join("Makefile");
+    if makefile_path.exists() {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: {} já existe e não foi sobrescrito.",
+                    makefile_path.display()
+                )
+            )
+        );
+        return;
+    }
+
+    let compose_dir = find_project_root()
+        .and_then(|root| fs::canonicalize(root).ok())
+        .unwrap_or_else(|| PathBuf::from(".."));
+
+    match fs::write(
+        &makefile_path,
+        render_project_makefile(input, config, &compose_dir),
+    ) {
+        Ok(()) => println!(
+            "Makefile com comandos do projeto salvo em {}",
+            makefile_path.display()
+        ),
+        Err(e) => eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Não foi possível gravar {}: {}",
+                    makefile_path.display(),
+                    e
+                )
+            )
+        ),
+    }
+}
+
+fn parse_cli_args<I: Iterator<Item = String>>(mut args: I) -> Result<CliOptions, AppError> {
+    let mut options = CliOptions::default();
+
+    while let Some(raw_arg) = args.next() {
+        let (arg, inline_value) = match raw_arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (raw_arg, None),
+        };
+
+        let mut inline_used = false;
+        let mut value = |flag: &str| -> Result<String, AppError> {
+            inline_used = true;
+            inline_value.clone().or_else(|| args.next()).ok_or_else(|| {
+                AppError::Validation(format!("O argumento '{}' exige um valor.", flag))
+            })
+        };
+
+        match arg.as_str() {
+            "--open-editor" => options.open_editor = true,
+            "--open" => options.open_browser = true,
+            "--browser" => options.browser = Some(value("--browser")?),
+            "--no-vite" => options.no_vite = true,
+            "--verbose-docker" | "--verbose" => options.verbose_docker = true,
+            "--explain" => options.explain = true,
+            "--no-color" => options.no_color = true,
+            "--template" => options.template_dir = Some(PathBuf::from(value("--template")?)),
+            "--overwrite-template" => options.overwrite_template = true,
+            "--wait-healthy" => options.wait_healthy = true,
+            "--wait-strategy" => options.wait_strategy = Some(value("--wait-strategy")?),
+            "--help" | "-h" => options.help = true,
+            "--strict" => options.strict = true,
+            "--json" => options.json = true,
+            "--octane" => options.octane = true,
+            "--force" => options.force = true,
+            "--yes" | "-y" => options.assume_yes = true,
+            "--version-list" => options.version_list = true,
+            "--env-report" => options.env_report = true,
+            "--check-update" => options.check_update = true,
+            "--list-services" => options.list_services = true,
+            "--out-dir" => options.out_dir = Some(PathBuf::from(value("--out-dir")?)),
+            "--php-container" => options.php_container = Some(value("--php-container")?),
+            "--php-service" => options.php_service = Some(value("--php-service")?),
+            "--node-container" => options.node_container = Some(value("--node-container")?),
+            "--verify" => options.verify = true,
+            "--no-deps" => options.no_deps = true,
+            "--skip-npm" => options.skip_npm = true,
+            "--require-node" => options.require_node = true,
+            "--no-migrate" => options.no_migrate = true,
+            "--minimal" => options.minimal = true,
+            "--offline" => options.offline = true,
+            "--name-from-git" => options.name_from_git = true,
+            "--seed" => options.seed = true,
+            "--allow-remote-db" => options.allow_remote_db = true,
+            "--no-app-seeders" => options.no_app_seeders = true,
+            "--no-rollback" => options.no_rollback = true,
+            "--dump-compose-logs" => options.dump_compose_logs = true,
+            "--shared-db" => options.shared_db = true,
+            "--pull" => options.pull = true,
+            "--pull-ignore-failure" => options.pull_ignore_failure = true,
+            "--compose-build" => options.compose_build = true,
+            "--build-no-cache" => options.build_no_cache = true,
+            "--with-data" => options.with_data = true,
+            "--with-volumes" => options.with_volumes = true,
+            "--optimize" => options.optimize = true,
+            "--attach" => options.attach = true,
+            "--ssl" => options.ssl = true,
+            "--behind-proxy" => options.behind_proxy = true,
+            "--trusted-proxies" => options.trusted_proxies = Some(value("--trusted-proxies")?),
+            "--label" => options.label = Some(value("--label")?),
+            "--env-override" => options.env_override = true,
+            "--config-dump" => options.config_dump = true,
+            "--dump-env-diff" => options.dump_env_diff = true,
+            "--auto-update-composer" => options.auto_update_composer = true,
+            "--no-scripts" => options.no_scripts = true,
+            "--hosts-helper" => options.hosts_helper = Some(value("--hosts-helper")?),
+            "--no-hosts" => options.no_hosts = true,
+            "--ipv6" => options.hosts_ipv6 = true,
+            "--from-spec" => options.from_spec = Some(PathBuf::from(value("--from-spec")?)),
+            "--export-spec" => options.export_spec = Some(PathBuf::from(value("--export-spec")?)),
+            "--retries" => {
+                let raw = value("--retries")?;
+                options.retries = raw.trim().parse::<u32>().map_err(|_| {
+                    AppError::Validation(format!(
+                        "Valor inválido para --retries: '{}'. Informe um número inteiro.",
+                        raw
+                    ))
+                })?;
+            }
+            "--validate-only" => options.validate_only = true,
+            "--print-vhost" => options.print_vhost = true,
+            "--dry-run" => options.dry_run = true,
+            "--plan-only" => options.plan_only = true,
+            "--create-db-user" => options.create_db_user = true,
+            "--exec-user" => options.exec_user = Some(value("--exec-user")?),
+            "--match-host-user" => options.match_host_user = true,
+            "--fix-ownership" => options.fix_ownership = true,
+            "--with-testing-env" => options.with_testing_env = true,
+            "--php-ini" => options.php_ini.push(value("--php-ini")?),
+            "--with-test-db" => options.with_test_db = true,
+            "--sync-env-example" => options.sync_env_example = true,
+            "--defer-restart" => options.defer_restart = true,
+            "--resume" => options.resume = true,
+            "--no-restart-on-resume" => options.no_restart_on_resume = true,
+            "--parallel" => options.parallel = true,
+            "--concurrency" => {
+                let raw = value("--concurrency")?;
+                options.concurrency = match raw.trim().parse::<usize>() {
+                    Ok(limit) if limit > 0 => Some(limit),
+                    _ => {
+                        return Err(AppError::Validation(format!(
+                            "Valor inválido para --concurrency: '{}'. Informe um número maior que zero.",
+                            raw
+                        )));
+                    }
+                };
+            }
+            "--stop-after" => options.stop_after = true,
+            "--stop-if-started" => options.stop_if_started = true,
+            "--with-make" => options.with_make = true,
+            "--no-config-clear" => options.no_config_clear = true,
+            "--events" => options.events_path = Some(PathBuf::from(value("--events")?)),
+            "--progress-fd" => {
+                let raw = value("--progress-fd")?;
+                let fd = raw.trim().parse::<u32>().map_err(|_| {
+                    AppError::Validation(format!(
+                        "Descritor inválido para --progress-fd: '{}'",
+                        raw
+                    ))
+                })?;
+                options.events_path = Some(PathBuf::from(format!("/dev/fd/{}", fd)));
+            }
+            "--vhost-allow-override" => {
+                options.vhost_allow_override = Some(value("--vhost-allow-override")?)
+            }
+            "--vhost-require" => options.vhost_require = Some(value("--vhost-require")?),
+            "--vhost-options" => options.vhost_options = Some(value("--vhost-options")?),
+            "--server-admin" => options.vhost_server_admin = Some(value("--server-admin")?),
+            "--vhost-log-dir" => options.vhost_log_dir = Some(value("--vhost-log-dir")?),
+            "--vhost-mode" => options.vhost_mode = Some(value("--vhost-mode")?),
+            "--installer" => options.installer = Some(value("--installer")?),
+            "--auto-name-containers" => options.auto_name_containers = true,
+            "--network" => options.network = Some(value("--network")?),
+            "--shared-vhost-logs" => options.shared_vhost_logs = true,
+            "--project-group" => options.project_group = Some(value("--project-group")?),
+            "--app-subdir" => options.app_subdir = Some(value("--app-subdir")?),
+            "--app-env" => options.app_env = Some(value("--app-env")?),
+            "--app-debug" => {
+                let raw = value("--app-debug")?;
+                options.app_debug = Some(parse_bool_value(&raw).ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Valor inválido para --app-debug: '{}'. Use true ou false.",
+                        raw
+                    ))
+                })?);
+            }
+            "--env-strategy" => options.env_strategy = Some(value("--env-strategy")?),
+            "--naming-style" => options.naming_style = Some(value("--naming-style")?),
+            "--compose-profile" => options.compose_profiles.push(value("--compose-profile")?),
+            "--package" => options.package = Some(value("--package")?),
+            "--http-port" => {
+                options.http_port = Some(parse_port_arg("--http-port", &value("--http-port")?)?)
+            }
+            "--https-port" => {
+                options.https_port = Some(parse_port_arg("--https-port", &value("--https-port")?)?)
+            }
+            "--timeout-overall" => {
+                let raw = value("--timeout-overall")?;
+                options.timeout_overall = match raw.trim().parse::<u64>() {
+                    Ok(secs) if secs > 0 => Some(secs),
+                    _ => {
+                        return Err(AppError::Validation(format!(
+                            "Valor inválido para --timeout-overall: '{}'. Informe os segundos.",
+                            raw
+                        )));
+                    }
+                };
+            }
+            "init" if options.command.is_none() => options.command = Some(Subcommand::Init),
+            "clean" if options.command.is_none() => options.command = Some(Subcommand::Clean),
+            "attach" if options.command.is_none() => {
+                options.command = Some(Subcommand::Attach(value("attach")?))
+            }
+            "reconfigure" if options.command.is_none() => {
+                options.command = Some(Subcommand::Reconfigure(value("reconfigure")?))
+            }
+            "status" if options.command.is_none() => {
+                options.command = Some(Subcommand::Status(value("status")?))
+            }
+            "validate-config" if options.command.is_none() => {
+                options.command = Some(Subcommand::ValidateConfig(PathBuf::from(value(
+                    "validate-config",
+                )?)))
+            }
+            "list" if options.command.is_none() => options.command = Some(Subcommand::List),
+            "delete" if options.command.is_none() => {
+                options.command = Some(Subcommand::Delete(None))
+            }
+            name if options.command == Some(Subcommand::Delete(None)) && !name.starts_with('-') => {
+                options.command = Some(Subcommand::Delete(Some(name.to_string())))
+            }
+            "batch" if options.command.is_none() => {
+                options.command = Some(Subcommand::Batch(Vec::new()))
+            }
+            spec if matches!(options.command, Some(Subcommand::Batch(_)))
+                && !spec.starts_with('-') =>
+            {
+                if let Some(Subcommand::Batch(specs)) = &mut options.command {
+                    specs.push(PathBuf::from(spec));
+                }
+            }
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Argumento desconhecido: '{}'",
+                    other
+                )));
+            }
+        }
+
+        // Flags booleanas não leem valor: '--ssl=no' ligaria a opção em silêncio
+        if let Some(inline) = &inline_value
+            && !inline_used
+        {
+            return Err(AppError::Validation(format!(
+                "O argumento '{}' não aceita valor (recebido '{}={}'). Informe só '{}' para ativá-lo.",
+                arg, arg, inline, arg
+            )));
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_port_arg(flag: &str, value: &str) -> Result<u16, AppError> {
+    match value.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(AppError::Validation(format!(
+            "Porta inválida para {}: '{}'",
+            flag, value
+        ))),
+    }
+}
+
+// Com a raiz do dev-container encontrada, o .env é sempre o dela: em src/<projeto>
+// o '.' apontaria para o .env do próprio Laravel
+fn find_env_path(filename: &str) -> Option<PathBuf> {
+    if let Some(project_root) = find_project_root() {
+        let path = project_root.join(filename);
+        return path.exists().then_some(path);
+    }
+
+    let path_dot = PathBuf::from(filename);
+    if path_dot.exists() {
+        return Some(path_dot);
+    }
+
+    let path_dot_dot = PathBuf::from("..").join(filename);
+    if path_dot_dot.exists() {
+        return Some(path_dot_dot);
+    }
+
+    None
+}
+
+const COMPOSE_SKELETON: &str = r#"# Esqueleto gerado por 'lara init'. Ajuste as imagens conforme a sua stack:
+# o serviço php precisa de php-fpm e composer, e o apache precisa incluir
+# /usr/local/apache2/conf/vhosts/*.conf e carregar mod_proxy_fcgi.
+services:
+  php:
+    image: ${PHP_IMAGE:-php:8.4-fpm}
+    container_name: ${CONTAINER_NAME:-dev_container}_php
+    volumes:
+      - ./src:/var/www/html:Z
+    networks:
+      - net
+
+  node:
+    image: node:${NODE_VERSION:-22}
+    container_name: ${CONTAINER_NAME:-dev_container}_node
+    command: sleep infinity
+    volumes:
+      - ./src:/var/www/html:Z
+    ports:
+      - ${VITE_PORT:-5173}:5173
+    networks:
+      - net
+
+  apache:
+    image: httpd:2.4
+    container_name: ${CONTAINER_NAME:-dev_container}_apache
+    ports:
+      - ${SERVER_PORT:-8000}:80
+      - ${SERVER_HTTPS_PORT:-8443}:443
+    volumes:
+      - ./src:/var/www/html:Z
+      - ./docker/apache/vhosts:/usr/local/apache2/conf/vhosts:ro
+    depends_on:
+      - php
+    networks:
+      - net
+
+  mariadb:
+    image: mariadb:11
+    container_name: ${CONTAINER_NAME:-dev_container}_mariadb
+    environment:
+      MYSQL_ROOT_PASSWORD: ${DB_ROOT_PASSWORD:-password}
+    ports:
+      - ${DB_PORT:-3306}:3306
+    volumes:
+      - mysql_data:/var/lib/mysql:Z
+    networks:
+      - net
+
+networks:
+  net:
+    name: ${CONTAINER_NAME:-dev_container}_net
+    driver: bridge
+
+volumes:
+  mysql_data:
+"#;
+
+fn init_project_layout() -> Result<(), AppError> {
+    let root = env::current_dir()?;
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Preparando a estrutura do dev-container em {}...",
+                root.display()
+            )
+        )
+    );
+
+    for dir in [VHOSTS_DIR, "src"] {
+        let path = root.join(dir);
+        if path.is_dir() {
+            println!("Já existe: {}/", dir);
+        } else {
+            fs::create_dir_all(&path)?;
+            println!("Criado: {}/", dir);
+        }
+    }
+
+    let files = [
+        (EXAMPLE_ENV_FILE, include_str!("../../env.example")),
+        (COMPOSE_FILE_NAMES[0], COMPOSE_SKELETON),
+    ];
+    for (name, content) in files {
+        let path = root.join(name);
+        if path.exists() {
+            println!("Já existe, mantido: {}", name);
+            continue;
+        }
+        fs::write(&path, content)?;
+        println!("Criado: {}", name);
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Success,
+            "Estrutura pronta. Revise o docker-compose.yml e execute o lara novamente para criar um projeto."
+        )
+    );
+    Ok(())
+}
+
+fn ensure_expected_layout(env_source_found: bool) -> Result<(), AppError> {
+    let project_root_found = find_project_root().is_some();
+    if project_root_found && env_source_found {
+        return Ok(());
+    }
+
+    let current_dir = env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|_| ".".to_string());
+
+    let mut missing = Vec::new();
+    if !project_root_found {
+        missing.push(format!(
+            "diretório docker/ (procurado em até {} níveis acima)",
+            MAX_PROJECT_ROOT_DEPTH
+        ));
+    }
+    if !env_source_found {
+        missing.push(format!(
+            "{} ou {} (na raiz do dev-container)",
+            ENV_FILE, EXAMPLE_ENV_FILE
+        ));
+    }
+
+    Err(AppError::Validation(format!(
+        "Diretório incorreto ({}). Não encontrados: {}. Execute o lara a partir da raiz do dev-container (onde ficam docker/, docker-compose.yml e env.example) ou de laravel-maker/.",
+        current_dir,
+        missing.join("; ")
+    )))
+}
+
+fn ensure_env_file_exists(
+    env_path_option: Option<PathBuf>,
+    example_env_path_option: Option<PathBuf>,
+) -> Result<PathBuf, AppError> {
+    if let Some(env_path) = env_path_option {
+        println!("Arquivo .env encontrado.");
+        return Ok(env_path);
+    }
+
+    println!("Arquivo .env não encontrado. Tentando criar a partir do env.example... ");
+
+    let example_env_path = match example_env_path_option {
+        Some(path) => path,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Nem o .env, nem o . env.example foram encontrados. Verifique a estrutura do projeto.",
+            ).into());
+        }
+    };
+
+    let env_path = example_env_path.with_file_name(".env");
+
+    match fs::copy(&example_env_path, &env_path) {
+        Ok(_) => {
+            println!(
+                "Copiado {} para {} ",
+                example_env_path.display(),
+                env_path.display()
+            );
+
+            println!("\n--- Configuração Inicial ---");
+            println!("O arquivo de configuração .env foi criado com as variáveis padrão.");
+            if confirm(
+                "Deseja prosseguir com a configuração padrão do .env ?",
+                true,
+            )? {
+                println!("Continuando com as configurações padrão do .env.");
+                return Ok(env_path);
+            }
+
+            println!(
+                "\nProcesso interrompido. Edite o arquivo .env e execute o programa novamente."
+            );
+            println!("Pressione [Enter] para sair...");
+            io::stdout().flush()?;
+            let mut exit_buffer = String::new();
+            io::stdin().read_line(&mut exit_buffer)?;
+
+            Err(AppError::Interrupted(
+                "O usuário optou por configurar o .env manualmente.".to_string(),
+            ))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn override_env_from_path(env_path: &Path) -> dotenv::Result<()> {
+    for key in env_file_keys(env_path) {
+        // A leitura do .env acontece antes de qualquer thread ser criada.
+        unsafe { env::remove_var(key) };
+    }
+    dotenv::from_path(env_path)
+}
+
+fn env_file_keys(env_path: &Path) -> Vec<String> {
+    fs::read_to_string(env_path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            line.split_once('=').map(|(key, _)| key.trim().to_string())
+        })
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+fn print_config_dump(
+    env_path: &Path,
+    shell_keys: &[String],
+    user_keys: &[String],
+    override_existing: bool,
+) {
+    let file_keys = env_file_keys(env_path);
+
+    println!("Configuração resolvida ({}):", env_path.display());
+    if let Some(user_path) = user_config_path().filter(|path| path.is_file()) {
+        println!("Defaults do usuário: {}", user_path.display());
+    }
+    for key in CONFIG_ENV_KEYS {
+        let in_shell = shell_keys.iter().any(|shell_key| shell_key == key);
+        let in_file = file_keys.iter().any(|file_key| file_key == key);
+        let in_user = user_keys.iter().any(|user_key| user_key == key);
+
+        let source = match (in_shell, in_file) {
+            (true, true) if override_existing => ".env (--env-override)",
+            (true, true) => "shell (sobrepõe o .env)",
+            (true, false) => "shell",
+            (false, true) => ".env",
+            (false, false) if in_user => "config do usuário",
+            (false, false) => "default",
+        };
+
+        let value = match env::var(key) {
+            Ok(_) if key.contains("PASSWORD") => "********".to_string(),
+            Ok(value) => value,
+            Err(_) => "-".to_string(),
+        };
+
+        println!("  {:<20} {:<28} {}", key, value, source);
+    }
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join(USER_CONFIG_FILE))
+}
+
+// Subconjunto de TOML: 'chave = valor' no nível raiz, com as mesmas chaves do .env em minúsculas
+fn parse_user_config(path: &Path, content: &str) -> Result<Vec<(String, String)>, AppError> {
+    let mut entries = Vec::new();
+    for (index, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = || {
+            AppError::Validation(format!(
+                "Linha {} de {} inválida: '{}'. Use 'chave = \"valor\"' no nível raiz (sem [seções]).",
+                index + 1,
+                path.display(),
+                line
+            ))
+        };
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let key = key.trim();
+        let value = value.trim();
+        if key.is_empty() || key.starts_with('[') {
+            return Err(invalid());
+        }
+
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted.strip_suffix('"').ok_or_else(invalid)?.to_string(),
+            None => value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim()
+                .to_string(),
+        };
+        entries.push((key.to_string(), value));
+    }
+    Ok(entries)
+}
+
+// Precedência: flags > shell/.env > config do usuário > defaults; só preenche o que ainda não foi definido
+fn load_user_config() -> Result<Vec<String>, AppError> {
+    let Some(path) = user_config_path().filter(|path| path.is_file()) else {
+        return Ok(Vec::new());
+    };
+
+    let content = fs::read_to_string(&path)?;
+    let mut applied = Vec::new();
+    for (key, value) in parse_user_config(&path, &content)? {
+        let env_key = key.to_uppercase().replace('-', "_");
+        if !CONFIG_ENV_KEYS.contains(&env_key.as_str()) {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Chave '{}' desconhecida em {}. Ignorada.",
+                        key,
+                        path.display()
+                    )
+                )
+            );
+            continue;
+        }
+        if env::var_os(&env_key).is_some() {
+            continue;
+        }
+
+        // A leitura da configuração acontece antes de qualquer thread ser criada.
+        unsafe { env::set_var(&env_key, &value) };
+        applied.push(env_key);
+    }
+    Ok(applied)
+}
+
+fn load_env_file(env_path: &Path, strict: bool, override_existing: bool) -> Result<(), AppError> {
+    let result = if override_existing {
+        override_env_from_path(env_path)
+    } else {
+        dotenv::from_path(env_path)
+    };
+
+    let error = match result {
+        Ok(()) => return Ok(()),
+        Err(dotenv::Error::LineParse(line, index)) => format!(
+            "Falha ao interpretar {} na posição {}: '{}'. Verifique se a linha segue o formato CHAVE=valor.",
+            env_path.display(),
+            index,
+            line
+        ),
+        Err(e) => format!("Falha ao carregar {}: {}", env_path.display(), e),
+    };
+
+    if strict {
+        return Err(AppError::Validation(error));
+    }
+
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            &format!(
+                "AVISO: {} As variáveis seguintes a esse ponto foram ignoradas e os valores padrão serão usados.",
+                error
+            )
+        )
+    );
+    Ok(())
+}
+
+fn get_app_config(options: &CliOptions) -> Result<AppConfig, AppError> {
+    println!("Carregando configurações do .env...");
+
+    let mut auto_container_name = None;
+    let container_name = match env::var("CONTAINER_NAME") {
+        Ok(name) if !name.trim().is_empty() => {
+            let raw_name = name.trim();
+            let normalized = normalize_container_name(raw_name);
+            if normalized.is_empty() {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "CONTAINER_NAME ('{}') não contém caracteres válidos. Usando default: '{}'",
+                            raw_name, DEFAULT_CONTAINER_NAME
+                        )
+                    )
+                );
+                DEFAULT_CONTAINER_NAME.to_string()
+            } else {
+                if normalized != raw_name {
+                    println!(
+                        "{}",
+                        paint(
+                            Tone::Warning,
+                            &format!(
+                                "AVISO: CONTAINER_NAME ('{}') contém caracteres inválidos para o Docker. Usando '{}'. Ajuste o .env para manter o docker-compose.yml consistente.",
+                                raw_name, normalized
+                            )
+                        )
+                    );
+                }
+                normalized
+            }
+        }
+        _ if options.auto_name_containers => {
+            let checkout = find_project_root()
+                .or_else(|| env::current_dir().ok())
+                .and_then(|dir| {
+                    dir.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .unwrap_or_default();
+            let name =
+                normalize_container_name(&format!("{}_{}", DEFAULT_CONTAINER_NAME, checkout))
+                    .trim_end_matches('_')
+                    .to_string();
+            println!(
+                "CONTAINER_NAME não definido. Usando '{}' derivado do diretório (--auto-name-containers).",
+                name
+            );
+            auto_container_name = Some(name.clone());
+            name
+        }
+        _ => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: CONTAINER_NAME não encontrado ou vazio. Usando default: '{}'. Outros checkouts com o mesmo default usarão os mesmos contêineres; defina CONTAINER_NAME ou use --auto-name-containers.",
+                        DEFAULT_CONTAINER_NAME
+                    )
+                )
+            );
+            DEFAULT_CONTAINER_NAME.to_string()
+        }
+    };
+
+    let server_port = match env::var("SERVER_PORT") {
+        Ok(port_str) => match port_str.trim().parse::<u16>() {
+            Ok(port) => port,
+            Err(_) => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "SERVER_PORT ('{}') inválido. Usando default: {}",
+                            port_str.trim(),
+                            DEFAULT_SERVER_PORT
+                        )
+                    )
+                );
+                DEFAULT_SERVER_PORT
+            }
+        },
+        Err(_) => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "SERVER_PORT não encontrado. Usando default: {}",
+                        DEFAULT_SERVER_PORT
+                    )
+                )
+            );
+            DEFAULT_SERVER_PORT
+        }
+    };
+
+    let db_port = match env::var("DB_PORT") {
+        Ok(port_str) => match port_str.trim().parse::<u16>() {
+            Ok(port) => port,
+            Err(_) => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "DB_PORT ('{}') inválido. Usando default: {}",
+                            port_str.trim(),
+                            DEFAULT_DB_PORT
+                        )
+                    )
+                );
+                DEFAULT_DB_PORT
+            }
+        },
+        Err(_) => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "DB_PORT não encontrado. Usando default: {}",
+                        DEFAULT_DB_PORT
+                    )
+                )
+            );
+            DEFAULT_DB_PORT
+        }
+    };
+
+    let php_service = match &options.php_service {
+        Some(service) => {
+            if options.php_container.is_some() {
+                return Err(AppError::Validation(
+                    "--php-service não pode ser combinado com --php-container.".to_string(),
+                ));
+            }
+            validate_php_service(service)?
+        }
+        None => DEFAULT_PHP_FPM_HOST.to_string(),
+    };
+    let php_container_name = match &options.php_container {
+        Some(name) => validate_container_override("--php-container", name)?,
+        None => format!("{}_{}", container_name, php_service),
+    };
+    let node_container_name = match &options.node_container {
+        Some(name) => validate_container_override("--node-container", name)?,
+        None => format!("{}_node", container_name),
+    };
+    let db_container_name = format!("{}_mariadb", container_name);
+    let apache_container_name = format!("{}_apache", container_name);
+
+    let db_root_password = match env::var("DB_ROOT_PASSWORD") {
+        Ok(password) if !password.trim().is_empty() => password.trim().to_string(),
+        _ => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "MYSQL_ROOT_PASSWORD não encontrada ou vazia. Usando default: '{}'",
+                        DEFAULT_DB_ROOT_PASSWORD
+                    )
+                )
+            );
+            DEFAULT_DB_ROOT_PASSWORD.to_string()
+        }
+    };
+
+    let patch_vite = !options.no_vite && !options.minimal && env_flag("PATCH_VITE").unwrap_or(true);
+    let skip_deps = options.no_deps || options.minimal || options.offline;
+    let skip_npm = options.skip_npm || options.minimal || options.offline;
+    let skip_migrate = options.no_migrate || options.minimal;
+
+    if options.seed && skip_migrate {
+        return Err(AppError::Validation(
+            "--seed não pode ser combinado com --no-migrate ou --minimal.".to_string(),
+        ));
+    }
+
+    if options.stop_after && options.stop_if_started {
+        return Err(AppError::Validation(
+            "Use --stop-after (para toda a stack) ou --stop-if-started (só o que o lara iniciou), não os dois.".to_string(),
+        ));
+    }
+    if (options.stop_after || options.stop_if_started) && options.attach {
+        return Err(AppError::Validation(
+            "--stop-after/--stop-if-started não podem ser combinados com --attach.".to_string(),
+        ));
+    }
+
+    if (options.with_data || options.with_volumes)
+        && !matches!(options.command, Some(Subcommand::Delete(_)))
+    {
+        return Err(AppError::Validation(
+            "--with-data e --with-volumes só valem para o subcomando 'delete'.".to_string(),
+        ));
+    }
+
+    if options.concurrency.is_some() && !matches!(options.command, Some(Subcommand::Batch(_))) {
+        return Err(AppError::Validation(
+            "--concurrency só vale para o subcomando 'batch'.".to_string(),
+        ));
+    }
+
+    if options.name_from_git && options.from_spec.is_some() {
+        return Err(AppError::Validation(
+            "--name-from-git não pode ser combinado com --from-spec.".to_string(),
+        ));
+    }
+
+    if options.offline && (options.pull || options.pull_ignore_failure) {
+        return Err(AppError::Validation(
+            "--offline não pode ser combinado com --pull.".to_string(),
+        ));
+    }
+
+    if options.resume && options.force {
+        return Err(AppError::Validation(
+            "--resume retoma um projeto existente e não pode ser combinado com --force."
+                .to_string(),
+        ));
+    }
+    if options.no_restart_on_resume && !options.resume {
+        return Err(AppError::Validation(
+            "--no-restart-on-resume só pode ser usado junto com --resume.".to_string(),
+        ));
+    }
+
+    if options.no_app_seeders && (options.seed || skip_migrate) {
+        return Err(AppError::Validation(
+            "--no-app-seeders substitui o --seed (que executa o DatabaseSeeder completo) e exige as migrations: não combine com --seed, --no-migrate ou --minimal.".to_string(),
+        ));
+    }
+
+    let baseline_seeders: Vec<String> = env::var("BASELINE_SEEDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|seeder| seeder.trim().to_string())
+        .filter(|seeder| !seeder.is_empty())
+        .collect();
+    if let Some(invalid) = baseline_seeders.iter().find(|seeder| {
+        !seeder
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }) {
+        return Err(AppError::Validation(format!(
+            "BASELINE_SEEDERS contém um nome de classe inválido: '{}'.",
+            invalid
+        )));
+    }
+
+    let artisan_steps = match env::var("ARTISAN_STEPS") {
+        Ok(raw) => {
+            let steps: Vec<String> = raw
+                .split(',')
+                .map(|step| step.split_whitespace().collect::<Vec<&str>>().join(" "))
+                .filter(|step| !step.is_empty())
+                .collect();
+            if steps.is_empty() {
+                return Err(AppError::Validation(
+                    "ARTISAN_STEPS está definido mas vazio. Informe os comandos separados por vírgula (ex: config:clear,migrate --force) ou remova a variável.".to_string(),
+                ));
+            }
+            if let Some(invalid) = steps.iter().find(|step| !is_plausible_artisan_step(step)) {
+                return Err(AppError::Validation(format!(
+                    "ARTISAN_STEPS contém um comando inválido: '{}'. Use o nome do comando Artisan (ex: storage:link) seguido de opções simples.",
+                    invalid
+                )));
+            }
+            if options.seed || options.no_migrate || options.no_app_seeders {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        "AVISO: ARTISAN_STEPS substitui a sequência padrão; --seed, --no-migrate e --no-app-seeders são ignorados."
+                    )
+                );
+            }
+            Some(steps)
+        }
+        Err(_) => None,
+    };
+
+    if options.minimal {
+        println!(
+            "Perfil --minimal: etapas ignoradas: composer update, npm install, vite.config.js, migrate."
+        );
+    }
+    if options.offline {
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                "Modo --offline: etapas simuladas: consulta ao Packagist, pull de imagens, composer update, npm install. O composer create-project usa apenas o cache local (COMPOSER_DISABLE_NETWORK=1)."
+            )
+        );
+    }
+
+    let template_dir = options.template_dir.clone().or_else(|| {
+        env::var("TEMPLATE_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(|dir| PathBuf::from(dir.trim()))
+    });
+
+    // --wait-healthy/WAIT_HEALTHY continuam valendo como atalho para 'health'
+    let wait_strategy = match options
+        .wait_strategy
+        .clone()
+        .or_else(|| env::var("WAIT_STRATEGY").ok())
+        .map(|strategy| strategy.trim().to_lowercase())
+        .as_deref()
+    {
+        Some("ps") => WaitStrategy::Ps,
+        Some("exec") => WaitStrategy::Exec,
+        Some("health") => WaitStrategy::Health,
+        Some("auto") => WaitStrategy::Auto,
+        None | Some("") if options.wait_healthy || env_flag("WAIT_HEALTHY").unwrap_or(false) => {
+            WaitStrategy::Health
+        }
+        None | Some("") => WaitStrategy::Auto,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Estratégia de espera inválida: '{}'. Use 'ps', 'exec', 'health' ou 'auto'.",
+                other
+            )));
+        }
+    };
+
+    let wait_timeout_secs = match env::var("WAIT_TIMEOUT") {
+        Ok(timeout_str) => match timeout_str.trim().parse::<u64>() {
+            Ok(timeout) if timeout > 0 => timeout,
+            _ => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "WAIT_TIMEOUT ('{}') inválido. Usando default: {}",
+                            timeout_str.trim(),
+                            DEFAULT_WAIT_TIMEOUT_SECS
+                        )
+                    )
+                );
+                DEFAULT_WAIT_TIMEOUT_SECS
+            }
+        },
+        Err(_) => DEFAULT_WAIT_TIMEOUT_SECS,
+    };
+
+    let apache_settle_secs = match env::var("APACHE_SETTLE_SECS") {
+        Ok(settle_str) => match settle_str.trim().parse::<u64>() {
+            Ok(settle) => settle,
+            _ => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "APACHE_SETTLE_SECS ('{}') inválido. Usando default: {}",
+                            settle_str.trim(),
+                            DEFAULT_APACHE_SETTLE_SECS
+                        )
+                    )
+                );
+                DEFAULT_APACHE_SETTLE_SECS
+            }
+        },
+        Err(_) => DEFAULT_APACHE_SETTLE_SECS,
+    };
+
+    let php_fpm_handler = resolve_php_fpm_handler(options.php_service.as_deref())?;
+
+    let compose_profiles = resolve_compose_profiles(options)?;
+
+    let app_env = match options.app_env.clone().or_else(|| {
+        env::var("PROJECT_APP_ENV")
+            .ok()
+            .filter(|app_env| !app_env.trim().is_empty())
+    }) {
+        Some(app_env) => {
+            let app_env = app_env.trim().to_string();
+            if !app_env
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(AppError::Validation(format!(
+                    "APP_ENV inválido: '{}'. Use apenas letras, números, '-' e '_'.",
+                    app_env
+                )));
+            }
+            if !KNOWN_APP_ENVS.contains(&app_env.as_str()) {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "AVISO: APP_ENV '{}' não é um valor conhecido ({}).",
+                            app_env,
+                            KNOWN_APP_ENVS.join(", ")
+                        )
+                    )
+                );
+            }
+            Some(app_env)
+        }
+        None => None,
+    };
+
+    let env_strategy = match options
+        .env_strategy
+        .clone()
+        .or_else(|| env::var("ENV_STRATEGY").ok())
+        .map(|strategy| strategy.trim().to_lowercase())
+        .as_deref()
+    {
+        None | Some("") | Some("sed") => EnvStrategy::Sed,
+        Some("rewrite") => EnvStrategy::Rewrite,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Estratégia de .env inválida: '{}'. Use 'sed' ou 'rewrite'.",
+                other
+            )));
+        }
+    };
+
+    let naming_style = match options
+        .naming_style
+        .clone()
+        .or_else(|| env::var("NAMING_STYLE").ok())
+        .map(|style| style.trim().to_lowercase())
+        .as_deref()
+    {
+        None | Some("") | Some("kebab") => NamingStyle::Kebab,
+        Some("snake") => NamingStyle::Snake,
+        Some("preserve") => NamingStyle::Preserve,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Estilo de nome inválido: '{}'. Use 'kebab', 'snake' ou 'preserve'.",
+                other
+            )));
+        }
+    };
+
+    let vhost_mode = match options
+        .vhost_mode
+        .clone()
+        .or_else(|| env::var("VHOST_MODE").ok())
+        .map(|mode| mode.trim().to_lowercase())
+        .as_deref()
+    {
+        None | Some("") | Some("per-project") => VhostMode::PerProject,
+        Some("single-include") => VhostMode::SingleInclude,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Modo de vhost inválido: '{}'. Use 'per-project' ou 'single-include'.",
+                other
+            )));
+        }
+    };
+
+    let installer = match options
+        .installer
+        .clone()
+        .or_else(|| env::var("INSTALLER").ok())
+        .map(|installer| installer.trim().to_lowercase())
+        .as_deref()
+    {
+        None | Some("") | Some("composer") => Installer::Composer,
+        Some("laravel") => Installer::Laravel,
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "Instalador inválido: '{}'. Use 'composer' ou 'laravel'.",
+                other
+            )));
+        }
+    };
+
+    let docker_network = options
+        .network
+        .clone()
+        .or_else(|| env::var("DOCKER_NETWORK").ok())
+        .map(|network| network.trim().to_string())
+        .filter(|network| !network.is_empty());
+    if let Some(network) = &docker_network
+        && !is_valid_network_name(network)
+    {
+        return Err(AppError::Validation(format!(
+            "Nome de rede inválido: '{}'. Use letras, números, '_', '.' ou '-', começando por letra ou número.",
+            network
+        )));
+    }
+
+    let shared_db = if options.shared_db || env_flag("SHARED_DB").unwrap_or(false) {
+        let name = env::var("SHARED_DB_NAME")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| DEFAULT_SHARED_DB_NAME.to_string());
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AppError::Validation(format!(
+                "SHARED_DB_NAME ('{}') inválido. Use apenas letras, números e '_'.",
+                name
+            )));
+        }
+        Some(name)
+    } else {
+        None
+    };
+
+    let npm_retries = match env::var("NPM_RETRIES") {
+        Ok(retries_str) => match retries_str.trim().parse::<u32>() {
+            Ok(retries) if retries > 0 => retries,
+            _ => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "NPM_RETRIES ('{}') inválido. Usando default: {}",
+                            retries_str.trim(),
+                            DEFAULT_NPM_RETRIES
+                        )
+                    )
+                );
+                DEFAULT_NPM_RETRIES
+            }
+        },
+        Err(_) => DEFAULT_NPM_RETRIES,
+    };
+
+    let base_package = match options.package.clone().or_else(|| {
+        env::var("BASE_PACKAGE")
+            .ok()
+            .filter(|package| !package.trim().is_empty())
+    }) {
+        Some(package) => {
+            let package = package.trim().to_string();
+            if !is_valid_composer_package(&package) {
+                return Err(AppError::Validation(format!(
+                    "Pacote base inválido: '{}'. Use o formato vendor/pacote (ex: myorg/laravel-starter).",
+                    package
+                )));
+            }
+            if package != DEFAULT_BASE_PACKAGE {
+                println!("Pacote base personalizado: '{}'", package);
+            }
+            package
+        }
+        None => DEFAULT_BASE_PACKAGE.to_string(),
+    };
+
+    let octane_port = match env::var("OCTANE_PORT") {
+        Ok(port_str) if !port_str.trim().is_empty() => match port_str.trim().parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "OCTANE_PORT ('{}') inválido.",
+                    port_str.trim()
+                )));
+            }
+        },
+        _ => DEFAULT_OCTANE_PORT,
+    };
+    let octane_host = match env::var("PHP_FPM_HOST") {
+        Ok(host) if !host.trim().is_empty() => host.trim().to_string(),
+        _ => DEFAULT_PHP_FPM_HOST.to_string(),
+    };
+    let octane_target = format!("{}:{}", octane_host, octane_port);
+
+    let octane_server = match env::var("OCTANE_SERVER") {
+        Ok(server) if !server.trim().is_empty() => {
+            let server = server.trim().to_lowercase();
+            if !["frankenphp", "roadrunner", "swoole"].contains(&server.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "OCTANE_SERVER ('{}') inválido. Use frankenphp, roadrunner ou swoole.",
+                    server
+                )));
+            }
+            server
+        }
+        _ => DEFAULT_OCTANE_SERVER.to_string(),
+    };
+
+    let http_port = options.http_port.unwrap_or(DEFAULT_VHOST_HTTP_PORT);
+    let https_port = options.https_port.unwrap_or(DEFAULT_VHOST_HTTPS_PORT);
+
+    if http_port == https_port {
+        return Err(AppError::Validation(format!(
+            "--http-port e --https-port não podem usar a mesma porta ({}).",
+            http_port
+        )));
+    }
+
+    validate_vhost_ports_against_compose(http_port);
+
+    let ssl = options.ssl || env_flag("SSL").unwrap_or(false);
+    let behind_proxy = options.behind_proxy || options.trusted_proxies.is_some();
+    if behind_proxy && ssl {
+        return Err(AppError::Validation(
+            "--behind-proxy não pode ser combinado com --ssl: o TLS é encerrado no proxy externo."
+                .to_string(),
+        ));
+    }
+
+    let trusted_proxies = options
+        .trusted_proxies
+        .clone()
+        .or_else(|| env::var("TRUSTED_PROXIES").ok())
+        .map(|proxies| proxies.trim().to_string())
+        .filter(|proxies| !proxies.is_empty())
+        .unwrap_or_else(|| "*".to_string());
+    if !trusted_proxies
+        .chars()
+        .all(|c| c.is_ascii_hexdigit() || matches!(c, '.' | ':' | '/' | ',' | '*'))
+    {
+        return Err(AppError::Validation(format!(
+            "TRUSTED_PROXIES inválido: '{}'. Use '*' ou uma lista de IPs/CIDRs separados por vírgula.",
+            trusted_proxies
+        )));
+    }
+
+    let sql_safe = |value: &str| {
+        !value.is_empty()
+            && !value
+                .chars()
+                .any(|c| matches!(c, '\'' | '"' | '\\' | '`' | '/'))
+    };
+
+    let db_app_user = env::var("DB_APP_USER")
+        .ok()
+        .map(|user| user.trim().to_string())
+        .filter(|user| !user.is_empty());
+    let db_app_password = env::var("DB_APP_PASSWORD").ok();
+
+    if options.create_db_user {
+        if let Some(user) = &db_app_user
+            && (!sql_safe(user) || user.len() > 32)
+        {
+            return Err(AppError::Validation(format!(
+                "DB_APP_USER ('{}') inválido. Use até 32 caracteres, sem aspas, barras ou crases.",
+                user
+            )));
+        }
+        match &db_app_password {
+            Some(password) if sql_safe(password) => {}
+            Some(_) => {
+                return Err(AppError::Validation(
+                    "DB_APP_PASSWORD não pode conter aspas, barras ou crases.".to_string(),
+                ));
+            }
+            None => {
+                return Err(AppError::Validation(
+                    "--create-db-user exige DB_APP_PASSWORD definido no .env.".to_string(),
+                ));
+            }
+        }
+    }
+
+    let label = match &options.label {
+        Some(label) => Some(validate_label(label)?),
+        None => None,
+    };
+    let project_group = resolve_project_group(options, label.as_deref())?;
+
+    let server_https_port = match env::var("SERVER_HTTPS_PORT") {
+        Ok(port_str) => match port_str.trim().parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "SERVER_HTTPS_PORT ('{}') inválido. Usando default: {}",
+                            port_str.trim(),
+                            DEFAULT_SERVER_HTTPS_PORT
+                        )
+                    )
+                );
+                DEFAULT_SERVER_HTTPS_PORT
+            }
+        },
+        Err(_) => DEFAULT_SERVER_HTTPS_PORT,
+    };
+
+    println!(
+        "Configurações base carregadas (Contêiner PHP: {}, Porta Apache: {})",
+        php_container_name, server_port
+    );
+
+    Ok(AppConfig {
+        php_container_name,
+        php_service,
+        node_container_name,
+        apache_container_name,
+        auto_container_name,
+        docker_network,
+        db_container_name,
+        db_root_password,
+        server_port,
+        db_port,
+        db_container_port: db_container_port(),
+        patch_vite,
+        template_dir,
+        overwrite_template: options.overwrite_template,
+        wait_strategy,
+        wait_timeout_secs,
+        apache_settle_secs,
+        strict: options.strict,
+        php_fpm_handler,
+        octane: options.octane || env_flag("OCTANE").unwrap_or(false),
+        octane_target,
+        octane_server,
+        http_port,
+        force: options.force,
+        assume_yes: options.assume_yes,
+        out_dir: options.out_dir.clone(),
+        skip_deps,
+        offline: options.offline,
+        name_from_git: options.name_from_git,
+        skip_npm,
+        require_node: options.require_node,
+        skip_migrate,
+        seed: options.seed,
+        allow_remote_db: options.allow_remote_db,
+        no_app_seeders: options.no_app_seeders,
+        baseline_seeders,
+        artisan_steps,
+        // Em --resume o projeto já existia antes da execução: nunca removê-lo
+        rollback: !options.no_rollback && !options.resume,
+        compose_profiles,
+        dump_compose_logs: options.dump_compose_logs
+            || env_flag("DUMP_COMPOSE_LOGS").unwrap_or(false),
+        shared_db,
+        pull: options.pull || options.pull_ignore_failure,
+        pull_ignore_failure: options.pull_ignore_failure,
+        compose_build: options.compose_build || options.build_no_cache,
+        build_no_cache: options.build_no_cache,
+        with_data: options.with_data || options.with_volumes,
+        with_volumes: options.with_volumes,
+        optimize: options.optimize,
+        env_strategy,
+        naming_style,
+        app_env,
+        app_debug: options.app_debug.or_else(|| env_flag("PROJECT_APP_DEBUG")),
+        ssl,
+        behind_proxy,
+        trusted_proxies,
+        https_port,
+        server_https_port,
+        label,
+        auto_update_composer: options.auto_update_composer,
+        no_scripts: options.no_scripts,
+        hosts_helper: options
+            .hosts_helper
+            .clone()
+            .or_else(|| env::var("HOSTS_HELPER").ok())
+            .map(|helper| helper.trim().to_string())
+            .filter(|helper| !helper.is_empty()),
+        no_hosts: options.no_hosts,
+        hosts_ipv6: options.hosts_ipv6,
+        vhost_allow_override: vhost_directive(
+            "VHOST_ALLOW_OVERRIDE",
+            &options.vhost_allow_override,
+        )?
+        .unwrap_or_else(|| "All".to_string()),
+        vhost_require: vhost_directive("VHOST_REQUIRE", &options.vhost_require)?
+            .unwrap_or_else(|| "all granted".to_string()),
+        vhost_options: vhost_directive("VHOST_OPTIONS", &options.vhost_options)?,
+        vhost_mode,
+        installer,
+        vhost_server_admin: vhost_directive("VHOST_SERVER_ADMIN", &options.vhost_server_admin)?
+            .unwrap_or_else(|| DEFAULT_VHOST_SERVER_ADMIN.to_string()),
+        vhost_log_dir: if options.shared_vhost_logs {
+            None
+        } else {
+            Some(
+                vhost_directive("VHOST_LOG_DIR", &options.vhost_log_dir)?
+                    .map(|dir| dir.trim_end_matches('/').to_string())
+                    .unwrap_or_else(|| DEFAULT_VHOST_LOG_DIR.to_string()),
+            )
+        },
+        project_group,
+        app_subdir: match options
+            .app_subdir
+            .clone()
+            .or_else(|| env::var("PROJECT_APP_SUBDIR").ok())
+        {
+            Some(subdir) => validate_app_subdir(&subdir)?,
+            None => None,
+        },
+        create_db_user: options.create_db_user,
+        exec_user: resolve_exec_user(options)?,
+        fix_ownership: options.fix_ownership,
+        // O banco de testes só é útil se o .env.testing apontar para ele
+        with_testing_env: options.with_testing_env || options.with_test_db,
+        php_ini: parse_php_ini_settings(&options.php_ini)?,
+        with_test_db: options.with_test_db,
+        sync_env_example: options.sync_env_example,
+        defer_restart: options.defer_restart,
+        resume: options.resume,
+        no_restart_on_resume: options.no_restart_on_resume,
+        parallel: options.parallel,
+        stop_after: options.stop_after,
+        stop_if_started: options.stop_if_started,
+        with_make: options.with_make,
+        no_config_clear: options.no_config_clear,
+        db_app_user,
+        db_app_password,
+        base_package,
+        npm_retries,
+        composer_bin: container_binary("COMPOSER_BIN", "composer")?,
+        php_bin: container_binary("PHP_BIN", "php")?,
+    })
+}
+
+fn vhost_directive(name: &str, flag_value: &Option<String>) -> Result<Option<String>, AppError> {
+    let value = match flag_value.clone().or_else(|| env::var(name).ok()) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let value = value.trim();
+    if value.is_empty() || value.contains(['\n', '<', '>']) {
+        return Err(AppError::Validation(format!(
+            "{} inválido: '{}'. Informe uma diretiva Apache não vazia em uma única linha.",
+            name, value
+        )));
+    }
+    Ok(Some(value.to_string()))
+}
+
+fn is_safe_relative_dir(path: &str) -> bool {
+    !path.starts_with('/')
+        && path.split('/').all(|part| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+}
+
+// Agrupa projetos em ../src/<grupo>/<nome>; "{label}" é substituído pelo --label
+fn resolve_project_group(
+    options: &CliOptions,
+    label: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let group = match options
+        .project_group
+        .clone()
+        .or_else(|| env::var("PROJECT_GROUP").ok())
+    {
+        Some(group) => group,
+        None => return Ok(None),
+    };
+
+    let group = group.trim().trim_end_matches('/');
+    if group.is_empty() {
+        return Ok(None);
+    }
+    let group = if group.contains("{label}") {
+        match label {
+            Some(label) => group.replace("{label}", label),
+            None => {
+                return Err(AppError::Validation(format!(
+                    "O grupo de projetos '{}' usa {{label}}, mas nenhum --label foi informado.",
+                    group
+                )));
+            }
+        }
+    } else {
+        group.to_string()
+    };
+
+    if !is_safe_relative_dir(&group) {
+        return Err(AppError::Validation(format!(
+            "Grupo de projetos inválido: '{}'. Informe um caminho relativo (ex: feature-x ou times/api) sem '..' e usando apenas letras, números, '-', '_' e '.'.",
+            group
+        )));
+    }
+    Ok(Some(group))
+}
+
+fn project_dir_name(project_name: &str, config: &AppConfig) -> String {
+    match &config.project_group {
+        Some(group) => format!("{}/{}", group, project_name),
+        None => project_name.to_string(),
+    }
+}
+
+fn validate_app_subdir(subdir: &str) -> Result<Option<String>, AppError> {
+    let subdir = subdir.trim().trim_end_matches('/');
+    if subdir.is_empty() || subdir == "." {
+        return Ok(None);
+    }
+
+    if !is_safe_relative_dir(subdir) {
+        return Err(AppError::Validation(format!(
+            "Subdiretório da aplicação inválido: '{}'. Informe um caminho relativo (ex: app ou apps/web) sem '..' e usando apenas letras, números, '-', '_' e '.'.",
+            subdir
+        )));
+    }
+    Ok(Some(subdir.to_string()))
+}
+
+fn host_user_ids() -> Result<String, AppError> {
+    if let (Ok(uid), Ok(gid)) = (env::var("SUDO_UID"), env::var("SUDO_GID"))
+        && !uid.trim().is_empty()
+        && !gid.trim().is_empty()
+    {
+        return Ok(format!("{}:{}", uid.trim(), gid.trim()));
+    }
+
+    let id = |flag: &str| -> Result<String, AppError> {
+        let output = Command::new("id").arg(flag).output()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || value.is_empty() {
+            return Err(AppError::Validation(
+                "Não foi possível obter o uid/gid do usuário atual com 'id'.".to_string(),
+            ));
+        }
+        Ok(value)
+    };
+    Ok(format!("{}:{}", id("-u")?, id("-g")?))
+}
+
+fn resolve_exec_user(options: &CliOptions) -> Result<Option<String>, AppError> {
+    if options.exec_user.is_some() && options.match_host_user {
+        return Err(AppError::Validation(
+            "--exec-user não pode ser combinado com --match-host-user.".to_string(),
+        ));
+    }
+
+    let user = match &options.exec_user {
+        Some(user) => user.trim().to_string(),
+        None if options.match_host_user => return host_user_ids().map(Some),
+        None => match env::var("EXEC_USER") {
+            Ok(user) if !user.trim().is_empty() => user.trim().to_string(),
+            _ => return Ok(None),
+        },
+    };
+
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    let valid = match user.split_once(':') {
+        Some((uid, gid)) => valid_part(uid) && valid_part(gid),
+        None => valid_part(&user),
+    };
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Usuário de execução inválido: '{}'. Use <uid:gid> (ex: 1000:1000) ou <usuario[:grupo]>.",
+            user
+        )));
+    }
+    Ok(Some(user))
+}
+
+fn container_binary(name: &str, default: &str) -> Result<String, AppError> {
+    let value = match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
+        _ => return Ok(default.to_string()),
+    };
+
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == ' ' || matches!(c, '-' | '_' | '.' | '/' | '='))
+    {
+        return Err(AppError::Validation(format!(
+            "{} ('{}') contém caracteres inválidos. Informe o nome ou caminho do executável no contêiner.",
+            name, value
+        )));
+    }
+    Ok(value)
+}
+
+fn ensure_container_binaries(config: &AppConfig) -> Result<(), AppError> {
+    for (name, command) in [
+        ("COMPOSER_BIN", &config.composer_bin),
+        ("PHP_BIN", &config.php_bin),
+    ] {
+        let program = command.split_whitespace().next().unwrap_or(command);
+        let found = capture_command_in_container(
+            &config.php_container_name,
+            &["sh", "-c", &format!("command -v '{}' || true", program)],
+        )?;
+        if found.is_empty() {
+            return Err(AppError::Validation(format!(
+                "O executável '{}' ({}) não foi encontrado no contêiner '{}'. Ajuste {} no .env.",
+                program, name, config.php_container_name, name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_label(label: &str) -> Result<String, AppError> {
+    let label = label.trim();
+    if label.is_empty()
+        || !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AppError::Validation(format!(
+            "Label inválido: '{}'. Use apenas letras, números, '-', '_' e '.'.",
+            label
+        )));
+    }
+    Ok(label.to_string())
+}
+
+fn validate_container_override(flag: &str, name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(format!(
+            "O valor de {} não pode ser vazio.",
+            flag
+        )));
+    }
+
+    match check_container_is_running(name) {
+        Ok(true) => {
+            println!("Usando contêiner informado via {}: '{}'", flag, name);
+            Ok(name.to_string())
+        }
+        Ok(false) => Err(AppError::Docker(format!(
+            "O contêiner '{}' informado via {} não está em execução.",
+            name, flag
+        ))),
+        Err(e) => Err(AppError::Docker(format!(
+            "Falha ao verificar o status do contêiner '{}': {}",
+            name, e
+        ))),
+    }
+}
+
+fn normalize_container_name(input: &str) -> String {
+    let sanitized = input
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    sanitized
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+fn find_compose_file() -> Option<PathBuf> {
+    let project_root = find_project_root()?;
+    COMPOSE_FILE_NAMES
+        .iter()
+        .map(|name| project_root.join(name))
+        .find(|path| path.is_file())
+}
+
+fn compose_service_entries(compose_content: &str, service: &str, key: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut in_service = false;
+    let mut service_indent = 0;
+    let mut in_key = false;
+    let mut key_indent = 0;
+    let key_line = format!("{}:", key);
+
+    for line in compose_content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if trimmed == format!("{}:", service) {
+            in_service = true;
+            service_indent = indent;
+            in_key = false;
+            continue;
+        }
+
+        if !in_service {
+            continue;
+        }
+
+        if indent <= service_indent {
+            break;
+        }
+
+        if trimmed == key_line {
+            in_key = true;
+            key_indent = indent;
+            continue;
+        }
+
+        if in_key {
+            if indent > key_indent && trimmed.starts_with('-') {
+                let entry = trimmed
+                    .trim_start_matches('-')
+                    .trim()
+                    .trim_matches(|c| c == '"' || c == '\'');
+                entries.push(entry.to_string());
+            } else if indent <= key_indent {
+                in_key = false;
+            }
+        }
+    }
+
+    entries
+}
+
+fn compose_services(compose_content: &str) -> Vec<String> {
+    let mut services = Vec::new();
+    let mut in_services = false;
+    let mut service_indent = None;
+
+    for line in compose_content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if indent == 0 {
+            in_services = trimmed == "services:";
+            continue;
+        }
+
+        if !in_services {
+            continue;
+        }
+
+        let service_indent = *service_indent.get_or_insert(indent);
+        if indent == service_indent
+            && let Some(name) = trimmed.strip_suffix(':')
+        {
+            services.push(name.trim_matches(|c| c == '"' || c == '\'').to_string());
+        }
+    }
+
+    services
+}
+
+fn running_service_containers(config: &AppConfig, project_root: &Path) -> Vec<(String, String)> {
+    let output = compose_command(config)
+        .current_dir(project_root)
+        .args(["ps", "--format", "{{.Service}}\t{{.Name}}"])
+        .traced()
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(service, name)| (service.trim().to_string(), name.trim().to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn list_compose_services(config: &AppConfig) -> Result<(), AppError> {
+    let compose_file = find_compose_file().ok_or_else(|| {
+        AppError::Validation(
+            "Nenhum arquivo compose encontrado a partir do diretório atual.".to_string(),
+        )
+    })?;
+    let compose_content = fs::read_to_string(&compose_file)?;
+    let services = compose_services(&compose_content);
+    if services.is_empty() {
+        println!("Nenhum serviço declarado em {}.", compose_file.display());
+        return Ok(());
+    }
+
+    let project_root = compose_file.parent().unwrap_or(Path::new("."));
+    let running = running_service_containers(config, project_root);
+    // Quais opções do lara apontam hoje para cada contêiner
+    let roles = [
+        (&config.php_container_name, "--php-container"),
+        (&config.node_container_name, "--node-container"),
+        (&config.apache_container_name, "apache"),
+        (&config.db_container_name, "banco"),
+    ];
+
+    let rows: Vec<(String, String, String)> = services
+        .iter()
+        .map(|service| {
+            let container = running
+                .iter()
+                .find(|(name, _)| name == service)
+                .map(|(_, container)| container.clone());
+            let mut uses: Vec<&str> = Vec::new();
+            if *service == config.php_service {
+                uses.push("--php-service");
+            }
+            if let Some(container) = &container {
+                uses.extend(
+                    roles
+                        .iter()
+                        .filter(|(name, _)| *name == container)
+                        .map(|(_, role)| *role),
+                );
+            }
+            (
+                service.clone(),
+                container.unwrap_or_else(|| "(não está rodando)".to_string()),
+                uses.join(", "),
+            )
+        })
+        .collect();
+
+    let service_width = rows
+        .iter()
+        .map(|(service, _, _)| service.len())
+        .max()
+        .unwrap_or(0)
+        .max("SERVIÇO".chars().count());
+    let container_width = rows
+        .iter()
+        .map(|(_, container, _)| container.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("CONTÊINER".chars().count());
+
+    println!("Serviços em {}:", compose_file.display());
+    println!(
+        "{:<sw$}  {:<cw$}  USADO POR",
+        "SERVIÇO",
+        "CONTÊINER",
+        sw = service_width,
+        cw = container_width
+    );
+    for (service, container, uses) in rows {
+        let line = format!(
+            "{:<sw$}  {:<cw$}  {}",
+            service,
+            container,
+            uses,
+            sw = service_width,
+            cw = container_width
+        );
+        println!("{}", line.trim_end());
+    }
+    if running.is_empty() {
+        println!(
+            "A stack não está rodando; suba com 'docker compose up -d' para ver os contêineres."
+        );
+    }
+    Ok(())
+}
+
+fn compose_service_container_ports(compose_content: &str, service: &str) -> Vec<u16> {
+    let mut ports = Vec::new();
+    let mut in_services = false;
+    let mut service_indent = None;
+    let mut in_target = false;
+    let mut ports_indent = None;
+
+    for line in compose_content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if indent == 0 {
+            in_services = trimmed == "services:";
+            continue;
+        }
+        if !in_services {
+            continue;
+        }
+
+        let service_indent = *service_indent.get_or_insert(indent);
+        if indent == service_indent {
+            in_target = trimmed
+                .strip_suffix(':')
+                .map(|name| name.trim_matches(|c| c == '"' || c == '\''))
+                == Some(service);
+            ports_indent = None;
+            continue;
+        }
+        if !in_target {
+            continue;
+        }
+
+        match ports_indent {
+            Some(key_indent) if indent > key_indent => {
+                // Sintaxe curta ("8080:3306") ou longa ("target: 3306")
+                let entry = trimmed.trim_start_matches('-').trim();
+                let container_side = match entry.strip_prefix("target:") {
+                    Some(target) => target,
+                    None if trimmed.starts_with('-') && !entry.contains(": ") => {
+                        entry.rsplit(':').next().unwrap_or(entry)
+                    }
+                    None => continue,
+                };
+                if let Ok(port) = container_side
+                    .trim_matches(|c| c == '"' || c == '\'' || c == ' ')
+                    .trim_end_matches("/tcp")
+                    .parse::<u16>()
+                {
+                    ports.push(port);
+                }
+            }
+            _ => {
+                ports_indent = (trimmed == "ports:").then_some(indent);
+            }
+        }
+    }
+
+    ports
+}
+
+fn db_container_port() -> u16 {
+    let compose_content = match find_compose_file().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(content) => content,
+        None => return DEFAULT_DB_PORT,
+    };
+
+    match compose_service_container_ports(&compose_content, "mariadb").first() {
+        Some(port) => *port,
+        None => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: O serviço 'mariadb' não publica portas no arquivo compose. Usando a porta interna default: {}",
+                        DEFAULT_DB_PORT
+                    )
+                )
+            );
+            DEFAULT_DB_PORT
+        }
+    }
+}
+
+fn validate_php_service(service: &str) -> Result<String, AppError> {
+    let service = service.trim();
+    if service.is_empty()
+        || !service
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AppError::Validation(format!(
+            "--php-service inválido: '{}'. Use o nome de um serviço do arquivo compose.",
+            service
+        )));
+    }
+
+    let compose_content = match find_compose_file().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(content) => content,
+        None => return Ok(service.to_string()),
+    };
+
+    let services = compose_services(&compose_content);
+    if !services.iter().any(|name| name == service) {
+        let php_services: Vec<&str> = services
+            .iter()
+            .map(String::as_str)
+            .filter(|name| name.starts_with("php"))
+            .collect();
+        return Err(AppError::Validation(format!(
+            "O serviço '{}' não está declarado no arquivo compose. Serviços PHP disponíveis: {}",
+            service,
+            if php_services.is_empty() {
+                "nenhum".to_string()
+            } else {
+                php_services.join(", ")
+            }
+        )));
+    }
+
+    Ok(service.to_string())
+}
+
+fn compose_declared_profiles(compose_content: &str) -> Vec<String> {
+    let mut profiles = Vec::new();
+    let mut in_profiles = false;
+    let mut profiles_indent = 0;
+
+    for line in compose_content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if let Some(rest) = trimmed.strip_prefix("profiles:") {
+            let inline = rest.trim().trim_start_matches('[').trim_end_matches(']');
+            profiles.extend(
+                inline
+                    .split(',')
+                    .map(|profile| profile.trim().trim_matches(|c| c == '"' || c == '\''))
+                    .filter(|profile| !profile.is_empty())
+                    .map(str::to_string),
+            );
+            in_profiles = true;
+            profiles_indent = indent;
+            continue;
+        }
+
+        if in_profiles {
+            if indent >= profiles_indent && trimmed.starts_with('-') {
+                let profile = trimmed
+                    .trim_start_matches('-')
+                    .trim()
+                    .trim_matches(|c| c == '"' || c == '\'');
+                profiles.push(profile.to_string());
+            } else {
+                in_profiles = false;
+            }
+        }
+    }
+
+    profiles
+}
+
+fn resolve_compose_profiles(options: &CliOptions) -> Result<Vec<String>, AppError> {
+    let requested: Vec<String> = if options.compose_profiles.is_empty() {
+        env::var("COMPOSE_PROFILES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|profile| profile.trim().to_string())
+            .filter(|profile| !profile.is_empty())
+            .collect()
+    } else {
+        options
+            .compose_profiles
+            .iter()
+            .flat_map(|profile| profile.split(','))
+            .map(|profile| profile.trim().to_string())
+            .filter(|profile| !profile.is_empty())
+            .collect()
+    };
+
+    if requested.is_empty() {
+        return Ok(requested);
+    }
+
+    let compose_content = match find_compose_file().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(content) => content,
+        None => return Ok(requested),
+    };
+
+    let declared = compose_declared_profiles(&compose_content);
+    if let Some(unknown) = requested.iter().find(|profile| !declared.contains(profile)) {
+        return Err(AppError::Validation(format!(
+            "O profile '{}' não está declarado no arquivo compose. Profiles disponíveis: {}",
+            unknown,
+            if declared.is_empty() {
+                "nenhum".to_string()
+            } else {
+                declared.join(", ")
+            }
+        )));
+    }
+
+    Ok(requested)
+}
+
+fn container_side_port(mapping: &str) -> Option<u16> {
+    let without_protocol = mapping.split('/').next()?;
+    without_protocol
+        .rsplit(':')
+        .next()?
+        .trim()
+        .parse::<u16>()
+        .ok()
+}
+
+fn validate_vhost_ports_against_compose(http_port: u16) {
+    let compose_content = match find_compose_file().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(content) => content,
+        None => return,
+    };
+
+    let published: Vec<u16> = compose_service_entries(&compose_content, "apache", "ports")
+        .iter()
+        .filter_map(|mapping| container_side_port(mapping))
+        .collect();
+
+    if !published.is_empty() && !published.contains(&http_port) {
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: O Vhost escutará na porta {}, mas o serviço 'apache' do docker-compose.yml publica apenas a(s) porta(s) interna(s) {:?}.",
+                    http_port, published
+                )
+            )
+        );
+    }
+}
+
+fn compose_bind_mount_source(service: &str, container_path: &str) -> Option<PathBuf> {
+    let compose_path = find_compose_file()?;
+    let compose_content = fs::read_to_string(&compose_path).ok()?;
+
+    compose_service_entries(&compose_content, service, "volumes")
+        .iter()
+        .find_map(|volume| {
+            let mut parts = volume.split(':');
+            let source = parts.next()?;
+            let target = parts.next()?;
+            let is_bind_mount = source.starts_with('.') || source.starts_with('/');
+            if is_bind_mount && target.trim_end_matches('/') == container_path {
+                let base = compose_path.parent()?;
+                Some(base.join(source))
+            } else {
+                None
+            }
+        })
+}
+
+fn report_project_location(input: &ProjectInput, config: &AppConfig) {
+    match compose_bind_mount_source(&config.php_service, CONTAINER_WEB_ROOT) {
+        Some(source) => println!(
+            "Arquivos do projeto no host: {}",
+            source.join(&input.project_dir).display()
+        ),
+        None => println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Nenhum bind mount para {} encontrado no docker-compose.yml. O projeto existe apenas dentro do contêiner '{}'.",
+                    CONTAINER_WEB_ROOT, config.php_container_name
+                )
+            )
+        ),
+    }
+}
+
+fn copy_project_to_out_dir(
+    input: &ProjectInput,
+    config: &AppConfig,
+    out_dir: &Path,
+) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Copiando o projeto para {}...", out_dir.display())
+        )
+    );
+    fs::create_dir_all(out_dir)?;
+
+    let status = Command::new("docker")
+        .arg("cp")
+        .arg(format!(
+            "{}:{}/{}",
+            config.php_container_name, CONTAINER_WEB_ROOT, input.project_dir
+        ))
+        .arg(out_dir)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker cp': {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao copiar o projeto do contêiner para '{}'. Status: {:?}",
+            out_dir.display(),
+            status
+        )));
+    }
+
+    println!(
+        "Projeto copiado para {}",
+        out_dir.join(&input.project_name).display()
+    );
+    Ok(())
+}
+
+fn is_valid_composer_package(package: &str) -> bool {
+    let is_valid_part = |part: &str| {
+        !part.is_empty()
+            && part.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+            && part.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_' || c == '.'
+            })
+    };
+
+    match package.split_once('/') {
+        Some((vendor, name)) => is_valid_part(vendor) && is_valid_part(name),
+        None => false,
+    }
+}
+
+fn resolve_php_fpm_handler(php_service: Option<&str>) -> Result<String, AppError> {
+    if let Ok(socket) = env::var("PHP_FPM_SOCKET") {
+        let socket = socket.trim();
+        if !socket.is_empty() {
+            if !socket.starts_with('/')
+                || socket.contains(char::is_whitespace)
+                || socket.contains('|')
+            {
+                return Err(AppError::Validation(format!(
+                    "PHP_FPM_SOCKET ('{}') deve ser um caminho absoluto sem espaços (ex: /run/php/php-fpm.sock).",
+                    socket
+                )));
+            }
+            return Ok(format!("proxy:unix:{}|fcgi://localhost", socket));
+        }
+    }
+
+    let host = match (php_service, env::var("PHP_FPM_HOST")) {
+        (Some(service), _) => service.trim().to_string(),
+        (None, Ok(host)) if !host.trim().is_empty() => host.trim().to_string(),
+        _ => DEFAULT_PHP_FPM_HOST.to_string(),
+    };
+
+    let valid_host = host
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_');
+    if !valid_host {
+        return Err(AppError::Validation(format!(
+            "PHP_FPM_HOST ('{}') contém caracteres inválidos.",
+            host
+        )));
+    }
+
+    let port = match env::var("PHP_FPM_PORT") {
+        Ok(port_str) if !port_str.trim().is_empty() => match port_str.trim().parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "PHP_FPM_PORT ('{}') inválido.",
+                    port_str.trim()
+                )));
+            }
+        },
+        _ => DEFAULT_PHP_FPM_PORT,
+    };
+
+    Ok(format!("proxy:fcgi://{}:{}", host, port))
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    let value = env::var(name).ok()?;
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let flag = parse_bool_value(value);
+    if flag.is_none() {
+        println!(
+            "{} ('{}') inválido. Usando o comportamento padrão.",
+            name, value
+        );
+    }
+    flag
+}
+
+fn parse_bool_value(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "y" | "on" => Some(true),
+        "0" | "false" | "no" | "n" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn validate_dns_host(host: &str) -> Result<(), String> {
+    if host.len() > 253 {
+        return Err(format!(
+            "o hostname tem {} caracteres (máximo 253).",
+            host.len()
+        ));
+    }
+
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!(
+                "o trecho '{}' deve ter entre 1 e 63 caracteres.",
+                label
+            ));
+        }
+        if !label
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(format!(
+                "o trecho '{}' só pode conter letras minúsculas, números e '-'.",
+                label
+            ));
+        }
+        if !label.starts_with(|c: char| c.is_ascii_lowercase()) {
+            return Err(format!("o trecho '{}' deve começar com uma letra.", label));
+        }
+        if label.ends_with('-') {
+            return Err(format!("o trecho '{}' não pode terminar com '-'.", label));
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt_laravel_version() -> Result<String, AppError> {
+    loop {
+        println!("---");
+        println!(
+            "Versões de Laravel Comuns: {} (LTS), 11 (Mínimo aceito: {}). Use --version-list para ver todas.",
+            DEFAULT_LARAVEL_VERSION, MINIMAL_LARAVEL_VERSION
+        );
+        print!(
+            "Digite a versão do Laravel (ex: {ver}, ENTER={ver}, Min={min}): ",
+            ver = DEFAULT_LARAVEL_VERSION,
+            min = MINIMAL_LARAVEL_VERSION
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let version_str = input.trim().to_string();
+
+        if version_str.is_empty() {
+            let default_version = DEFAULT_LARAVEL_VERSION.to_string();
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!("Usando default: {}.", default_version)
+                )
+            );
+            return Ok(default_version);
+        }
+
+        match parse_laravel_version(&version_str) {
+            Ok(version_num) => {
+                if version_num > DEFAULT_LARAVEL_VERSION {
+                    let (versions, source) = available_laravel_versions();
+                    let latest = versions
+                        .iter()
+                        .copied()
+                        .max()
+                        .unwrap_or(DEFAULT_LARAVEL_VERSION);
+                    if version_num > latest {
+                        eprintln!(
+                            "{}",
+                            paint(
+                                Tone::Error,
+                                &format!(
+                                    "ERRO: A versão informada ({}) não existe. A versão mais recente conhecida é {} (fonte: {}).",
+                                    version_num, latest, source
+                                )
+                            )
+                        );
+                        continue;
+                    }
+                }
+
+                if version_num >= MINIMAL_LARAVEL_VERSION {
+                    return Ok(version_num.to_string());
+                } else {
+                    eprintln!(
+                        "{}",
+                        paint(
+                            Tone::Error,
+                            &format!(
+                                "ERRO: A versão informada ({}) é inválida. O campo é obrigatório e a versão mínima aceita é {}.",
+                                version_num, MINIMAL_LARAVEL_VERSION
+                            )
+                        )
+                    );
+                    continue;
+                }
+            }
+            Err(reason) => {
+                eprintln!(
+                    "{}",
+                    paint(
+                        Tone::Error,
+                        &format!(
+                            "ERRO: O dado informado ('{}') é inválido: {} (ex: {ver}, ENTER={ver}).",
+                            version_str,
+                            reason,
+                            ver = DEFAULT_LARAVEL_VERSION
+                        )
+                    )
+                );
+                continue;
+            }
+        }
+    }
+}
+
+fn get_user_input(config: &AppConfig) -> Result<ProjectInput, AppError> {
+    let src_dir = resolved_src_dir();
+    // O nome vindo do git passa pelas mesmas validações; se for recusado, volta a perguntar
+    let mut git_name = if config.name_from_git {
+        Some(project_name_from_git()?)
+    } else {
+        None
+    };
+    let project_name = 'project_loop: loop {
+        let raw_name = match git_name.take() {
+            Some(name) => {
+                println!("Nome do projeto derivado do git: '{}'.", name);
+                name
+            }
+            None => {
+                print!("Digite o NOME do novo projeto (ex: example-app): ");
+                io::stdout().flush()?;
+
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                if config.naming_style == NamingStyle::Preserve {
+                    input.trim().to_string()
+                } else {
+                    input.trim().to_lowercase()
+                }
+            }
+        };
+
+        if raw_name.is_empty() {
+            eprintln!("O nome do projeto não pode ser vazio.");
+            continue;
+        }
+        let name = format_project_name(&raw_name, config.naming_style);
+
+        if name.is_empty() {
+            eprintln!(
+                "A entrada original resultou em um nome vazio após a formatação. Tente novamente."
+            );
+            continue;
+        }
+
+        if name != raw_name {
+            println!(
+                "Formatado: '{}' alterado para '{}' ({}).",
+                raw_name,
+                name,
+                config.naming_style.describe()
+            );
+        }
+
+        if let Err(reason) = validate_dns_host(&project_host_for(&name)) {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Error,
+                    &format!(
+                        "ERRO DE VALIDAÇÃO: '{}' não é um hostname válido: {}",
+                        project_host_for(&name),
+                        reason
+                    )
+                )
+            );
+            continue;
+        }
+
+        let dir = project_dir_name(&name, config);
+        if let Some(entry) = registry_conflict(&name, &project_host_for(&name), &dir) {
+            let message = registry_conflict_message(&entry, &name);
+            if config.force {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "AVISO: {} --force informado; o registro será substituído.",
+                            message
+                        )
+                    )
+                );
+            } else {
+                eprintln!(
+                    "{}",
+                    paint(Tone::Error, &format!("ERRO DE VALIDAÇÃO: {}", message))
+                );
+                if confirm("Deseja tentar outro nome de projeto?", true)? {
+                    continue 'project_loop;
+                }
+                return Err(AppError::Interrupted(
+                    "O usuário optou por encerrar a aplicação.".to_string(),
+                ));
+            }
+        }
+
+        let project_path_check = src_dir.join(&dir);
+        if project_path_check.exists() && config.force {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: --force informado. O diretório existente de '{}' será removido.",
+                        name
+                    )
+                )
+            );
+            if remove_project_dir(&project_path_check, &src_dir, config.assume_yes)? {
+                break name;
+            }
+            continue;
+        }
+
+        let exists_on_host = project_path_check.exists();
+        let exists_in_container = !exists_on_host && container_project_dir_in_use(config, &dir);
+
+        if exists_in_container && config.force {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: --force informado. O diretório {}/{} será removido dentro do contêiner.",
+                        CONTAINER_WEB_ROOT, dir
+                    )
+                )
+            );
+            if remove_container_project_dir(config, &dir)? {
+                break name;
+            }
+            continue;
+        }
+
+        if (exists_on_host || exists_in_container) && config.resume {
+            println!("Retomando o projeto existente '{}' (--resume).", name);
+            break name;
+        }
+
+        if exists_on_host || exists_in_container {
+            let message = if exists_on_host {
+                format!("ERRO DE VALIDAÇÃO: O diretório ../src/{} já existe.", dir)
+            } else {
+                format!(
+                    "ERRO DE VALIDAÇÃO: O diretório {}/{} já existe e não está vazio no contêiner '{}'.",
+                    CONTAINER_WEB_ROOT, dir, config.php_container_name
+                )
+            };
+            eprintln!("{}", paint(Tone::Error, &message));
+
+            if confirm("Deseja tentar outro nome de projeto?", true)? {
+                continue 'project_loop;
+            }
+            return Err(AppError::Interrupted(
+                "O usuário optou por encerrar a aplicação.".to_string(),
+            ));
+        }
+        break name;
+    };
+
+    let laravel_version = prompt_laravel_version()?;
+
+    let project_host = project_host_for(&project_name);
+    let project_dir = project_dir_name(&project_name, config);
+    let project_path = format!("../src/{}", project_dir);
+
+    println!("---");
+    println!(
+        "Entradas válidas: Projeto='{}', Host='{}', Versão='{}'",
+        project_name, project_host, laravel_version
+    );
+    println!("---");
+
+    Ok(ProjectInput {
+        project_name,
+        project_host,
+        project_dir,
+        project_path,
+        laravel_version,
+        octane: false,
+        label: config.label.clone(),
+    })
+}
+
+#[derive(Debug)]
+struct ProjectSpec {
+    name: String,
+    laravel_version: String,
+}
+
+fn spec_separator(spec_path: &Path) -> Result<char, AppError> {
+    match spec_path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => Ok('='),
+        Some("yaml") | Some("yml") => Ok(':'),
+        _ => Err(AppError::Validation(format!(
+            "Formato de spec não suportado: '{}'. Use .toml ou .yaml.",
+            spec_path.display()
+        ))),
+    }
+}
+
+fn parse_spec_entries(spec_path: &Path) -> Result<Vec<(usize, String, String)>, AppError> {
+    let content = fs::read_to_string(spec_path).map_err(|e| {
+        AppError::Validation(format!(
+            "Não foi possível ler o spec '{}': {}",
+            spec_path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    let separator = spec_separator(spec_path)?;
+
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+
+        // O parser é plano: tabelas, listas e aninhamento viram erro em vez de serem ignorados
+        let not_flat = |what: &str| {
+            AppError::Validation(format!(
+                "{} linha {}: {} não são suportados; o spec aceita só linhas planas 'chave {} valor' (repita compose_profile em uma linha por perfil).",
+                spec_path.display(),
+                index + 1,
+                what,
+                separator
+            ))
+        };
+        if trimmed.starts_with('[') {
+            return Err(not_flat("cabeçalhos de tabela"));
+        }
+        if separator == ':' && (trimmed.starts_with("- ") || trimmed == "-") {
+            return Err(not_flat("itens de lista"));
+        }
+        if separator == ':' && line.starts_with([' ', '\t']) {
+            return Err(not_flat("campos aninhados"));
+        }
+
+        let (key, value) = trimmed.split_once(separator).ok_or_else(|| {
+            AppError::Validation(format!(
+                "{} linha {}: esperado 'chave {} valor', encontrado '{}'.",
+                spec_path.display(),
+                index + 1,
+                separator,
+                trimmed
+            ))
+        })?;
+
+        let value = value.trim();
+        let value = match value.split_once(" #") {
+            Some((value, _)) => value.trim(),
+            None => value,
+        };
+        if value.starts_with('[') {
+            return Err(not_flat("arrays"));
+        }
+        if value.starts_with('{') {
+            return Err(not_flat("tabelas inline"));
+        }
+        if separator == ':' && value.is_empty() {
+            return Err(not_flat("campos aninhados"));
+        }
+
+        entries.push((
+            index + 1,
+            key.trim().to_string(),
+            value.trim_matches(|c| c == '"' || c == '\'').to_string(),
+        ));
+    }
+
+    Ok(entries)
+}
+
+fn apply_project_spec(spec_path: &Path, options: &mut CliOptions) -> Result<ProjectSpec, AppError> {
+    let mut name = None;
+    let mut laravel_version = None;
+
+    for (line, key, value) in parse_spec_entries(spec_path)? {
+        apply_spec_entry(
+            spec_path,
+            line,
+            &key,
+            value,
+            options,
+            &mut name,
+            &mut laravel_version,
+        )?;
+    }
+
+    let spec = finish_project_spec(spec_path, name, laravel_version)?;
+    options.assume_yes = true;
+    Ok(spec)
+}
+
+fn apply_spec_entry(
+    spec_path: &Path,
+    line: usize,
+    key: &str,
+    value: String,
+    options: &mut CliOptions,
+    name: &mut Option<String>,
+    laravel_version: &mut Option<String>,
+) -> Result<(), AppError> {
+    let field_error = |message: &str| {
+        AppError::Validation(format!(
+            "{} linha {}: campo '{}' {}\n{}",
+            spec_path.display(),
+            line,
+            key,
+            message,
+            SPEC_SCHEMA
+        ))
+    };
+    let flag =
+        |value: &str| parse_bool_value(value).ok_or_else(|| field_error("deve ser true ou false."));
+
+    match key {
+        "name" => *name = Some(value),
+        "laravel_version" => *laravel_version = Some(value),
+        "package" => options.package = Some(value),
+        "label" => options.label = Some(value),
+        "php_container" => options.php_container = Some(value),
+        "php_service" => options.php_service = Some(value),
+        "node_container" => options.node_container = Some(value),
+        "template" => options.template_dir = Some(PathBuf::from(value)),
+        "out_dir" => options.out_dir = Some(PathBuf::from(value)),
+        "compose_profile" => options.compose_profiles.push(value),
+        "http_port" => {
+            options.http_port = Some(
+                parse_port_arg("http_port", &value)
+                    .map_err(|_| field_error("deve ser uma porta válida."))?,
+            )
+        }
+        "octane" => options.octane = flag(&value)?,
+        "ssl" => options.ssl = flag(&value)?,
+        "seed" => options.seed = flag(&value)?,
+        "no_deps" => options.no_deps = flag(&value)?,
+        "skip_npm" => options.skip_npm = flag(&value)?,
+        "no_migrate" => options.no_migrate = flag(&value)?,
+        "no_vite" => options.no_vite = flag(&value)?,
+        "force" => options.force = flag(&value)?,
+        "naming_style" => match value.as_str() {
+            "kebab" | "snake" | "preserve" => options.naming_style = Some(value),
+            _ => return Err(field_error("deve ser kebab, snake ou preserve.")),
+        },
+        _ => return Err(field_error("é desconhecido.")),
+    }
+    Ok(())
+}
+
+fn finish_project_spec(
+    spec_path: &Path,
+    name: Option<String>,
+    laravel_version: Option<String>,
+) -> Result<ProjectSpec, AppError> {
+    let name = name.filter(|name| !name.trim().is_empty()).ok_or_else(|| {
+        AppError::Validation(format!(
+            "{}: campo obrigatório 'name' ausente.\n{}",
+            spec_path.display(),
+            SPEC_SCHEMA
+        ))
+    })?;
+
+    let laravel_version = match laravel_version {
+        None => DEFAULT_LARAVEL_VERSION.to_string(),
+        Some(version) => {
+            let version_num = parse_laravel_version(&version).map_err(|reason| {
+                AppError::Validation(format!(
+                    "{}: campo 'laravel_version' inválido ('{}'): {}.",
+                    spec_path.display(),
+                    version,
+                    reason
+                ))
+            })?;
+            let latest = available_laravel_versions()
+                .0
+                .iter()
+                .copied()
+                .max()
+                .unwrap_or(DEFAULT_LARAVEL_VERSION);
+            if version_num < MINIMAL_LARAVEL_VERSION || version_num > latest {
+                return Err(AppError::Validation(format!(
+                    "{}: campo 'laravel_version' ({}) fora do intervalo aceito ({}..={}).",
+                    spec_path.display(),
+                    version_num,
+                    MINIMAL_LARAVEL_VERSION,
+                    latest
+                )));
+            }
+            version_num.to_string()
+        }
+    };
+
+    Ok(ProjectSpec {
+        name,
+        laravel_version,
+    })
+}
+
+fn spec_problem(error: AppError) -> String {
+    match error.kind() {
+        AppError::Validation(message) => {
+            message.trim_end_matches(SPEC_SCHEMA).trim_end().to_string()
+        }
+        _ => error.to_string(),
+    }
+}
+
+// Valida o spec inteiro sem criar nada, reportando todos os problemas encontrados
+fn validate_spec_file(spec_path: &Path) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Validando {}...", spec_path.display())
+        )
+    );
+
+    let mut options = CliOptions::default();
+    let mut name = None;
+    let mut laravel_version = None;
+    let mut seen: Vec<(String, usize)> = Vec::new();
+    let mut problems: Vec<String> = Vec::new();
+
+    for (line, key, value) in parse_spec_entries(spec_path)? {
+        if key != "compose_profile"
+            && let Some((_, first_line)) = seen.iter().find(|(seen_key, _)| seen_key == &key)
+        {
+            problems.push(format!(
+                "{} linha {}: campo '{}' repetido (já definido na linha {}).",
+                spec_path.display(),
+                line,
+                key,
+                first_line
+            ));
+            continue;
+        }
+        seen.push((key.clone(), line));
+
+        if let Err(e) = apply_spec_entry(
+            spec_path,
+            line,
+            &key,
+            value,
+            &mut options,
+            &mut name,
+            &mut laravel_version,
+        ) {
+            problems.push(spec_problem(e));
+        }
+    }
+
+    match finish_project_spec(spec_path, name, laravel_version) {
+        Ok(spec) if problems.is_empty() => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Success,
+                    &format!(
+                        "✅ {} válido: Projeto='{}', Versão='{}'.",
+                        spec_path.display(),
+                        spec.name,
+                        spec.laravel_version
+                    )
+                )
+            );
+            return Ok(());
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(spec_problem(e)),
+    }
+
+    for problem in &problems {
+        eprintln!("{} {}", paint(Tone::Error, "❌"), problem);
+    }
+    eprintln!("{}", SPEC_SCHEMA);
+    Err(AppError::Validation(format!(
+        "{} problema(s) encontrado(s) em {}.",
+        problems.len(),
+        spec_path.display()
+    )))
+}
+
+fn render_project_spec(separator: char, input: &ProjectInput, config: &AppConfig) -> String {
+    let mut entries: Vec<(&str, String)> = vec![
+        ("name", format!("\"{}\"", input.project_name)),
+        ("laravel_version", format!("\"{}\"", input.laravel_version)),
+        ("package", format!("\"{}\"", config.base_package)),
+    ];
+
+    if let Some(label) = &input.label {
+        entries.push(("label", format!("\"{}\"", label)));
+    }
+    if let Some(template_dir) = &config.template_dir {
+        entries.push(("template", format!("\"{}\"", template_dir.display())));
+    }
+    if let Some(out_dir) = &config.out_dir {
+        entries.push(("out_dir", format!("\"{}\"", out_dir.display())));
+    }
+    for profile in &config.compose_profiles {
+        entries.push(("compose_profile", format!("\"{}\"", profile)));
+    }
+
+    entries.extend([
+        ("http_port", config.http_port.to_string()),
+        ("octane", input.octane.to_string()),
+        ("ssl", config.ssl.to_string()),
+        ("seed", config.seed.to_string()),
+        ("no_deps", config.skip_deps.to_string()),
+        ("skip_npm", config.skip_npm.to_string()),
+        ("no_migrate", config.skip_migrate.to_string()),
+        ("no_vite", (!config.patch_vite).to_string()),
+    ]);
+
+    let key_suffix = if separator == '=' { " =" } else { ":" };
+    let mut spec = String::from("# Gerado por lara --export-spec\n");
+    for (key, value) in entries {
+        spec.push_str(&format!("{}{} {}\n", key, key_suffix, value));
+    }
+    spec
+}
+
+fn export_project_spec(
+    spec_path: &Path,
+    input: &ProjectInput,
+    config: &AppConfig,
+) -> Result<(), AppError> {
+    let separator = spec_separator(spec_path)?;
+
+    fs::write(spec_path, render_project_spec(separator, input, config))?;
+    println!("Spec do projeto exportado para {}", spec_path.display());
+    Ok(())
+}
+
+fn input_from_spec(spec: ProjectSpec, config: &AppConfig) -> Result<ProjectInput, AppError> {
+    let project_name = format_project_name(spec.name.trim(), config.naming_style);
+    if project_name.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Campo 'name' do spec ('{}') resultou em um nome vazio.",
+            spec.name
+        )));
+    }
+
+    let project_host = project_host_for(&project_name);
+    validate_dns_host(&project_host).map_err(|reason| {
+        AppError::Validation(format!(
+            "Campo 'name' do spec gera um hostname inválido ('{}'): {}",
+            project_host, reason
+        ))
+    })?;
+
+    let src_dir = resolved_src_dir();
+    let project_dir = project_dir_name(&project_name, config);
+    if let Some(entry) = registry_conflict(&project_name, &project_host, &project_dir) {
+        let message = registry_conflict_message(&entry, &project_name);
+        if !config.force {
+            return Err(AppError::Validation(format!(
+                "{} Use outro 'name' ou 'force = true' no spec para substituí-lo.",
+                message
+            )));
+        }
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: {} --force informado; o registro será substituído.",
+                    message
+                )
+            )
+        );
+    }
+
+    let project_path_check = src_dir.join(&project_dir);
+    if project_path_check.exists() && config.resume {
+        println!(
+            "Retomando o projeto existente '{}' (--resume).",
+            project_name
+        );
+    } else if project_path_check.exists() {
+        if !config.force || !remove_project_dir(&project_path_check, &src_dir, config.assume_yes)? {
+            return Err(AppError::Validation(format!(
+                "O diretório ../src/{} já existe. Use 'force = true' no spec para substituí-lo.",
+                project_dir
+            )));
+        }
+    } else if container_project_dir_in_use(config, &project_dir)
+        && (!config.force || !remove_container_project_dir(config, &project_dir)?)
+    {
+        return Err(AppError::Validation(format!(
+            "O diretório {}/{} já existe no contêiner '{}'. Use 'force = true' no spec para substituí-lo.",
+            CONTAINER_WEB_ROOT, project_dir, config.php_container_name
+        )));
+    }
+
+    println!("---");
+    println!(
+        "Spec carregado: Projeto='{}', Host='{}', Versão='{}'",
+        project_name, project_host, spec.laravel_version
+    );
+    println!("---");
+
+    Ok(ProjectInput {
+        project_path: format!("../src/{}", project_dir),
+        project_name,
+        project_host,
+        project_dir,
+        laravel_version: spec.laravel_version,
+        octane: false,
+        label: config.label.clone(),
+    })
+}
+
+fn cache_file_path(name: &str) -> Option<PathBuf> {
+    let cache_home = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache_home.join("laravel-maker").join(name))
+}
+
+fn version_cache_path() -> Option<PathBuf> {
+    cache_file_path("laravel-versions")
+}
+
+fn read_cached_versions(cache_path: &Path) -> Option<Vec<u8>> {
+    let modified = fs::metadata(cache_path).ok()?.modified().ok()?;
+    if modified.elapsed().ok()? > Duration::from_secs(VERSION_CACHE_TTL_SECS) {
+        return None;
+    }
+
+    let versions: Vec<u8> = fs::read_to_string(cache_path)
+        .ok()?
+        .split(',')
+        .filter_map(|version| version.trim().parse::<u8>().ok())
+        .collect();
+
+    if versions.is_empty() {
+        None
+    } else {
+        Some(versions)
+    }
+}
+
+fn parse_packagist_majors(metadata: &str) -> Vec<u8> {
+    let marker = "\"version_normalized\":\"";
+    let mut majors: Vec<u8> = metadata
+        .match_indices(marker)
+        .filter_map(|(index, _)| {
+            let rest = &metadata[index + marker.len()..];
+            let version = &rest[..rest.find('"')?];
+            if version.contains('-') {
+                return None;
+            }
+            version.split('.').next()?.parse::<u8>().ok()
+        })
+        .collect();
+
+    majors.sort_unstable();
+    majors.dedup();
+    majors
+}
+
+fn parse_laravel_version(raw: &str) -> Result<u8, String> {
+    let version = raw.trim();
+    if version.is_empty() {
+        return Err("a versão está vazia".to_string());
+    }
+    if version.starts_with(['v', 'V']) {
+        return Err("não use o prefixo 'v', informe apenas o número".to_string());
+    }
+    if version.contains('.') {
+        return Err("informe apenas a versão major, sem '.' (ex: 12 em vez de 12.0)".to_string());
+    }
+    if !version.chars().all(|c| c.is_ascii_digit()) {
+        return Err("digite apenas o número inteiro da versão".to_string());
+    }
+    if version.len() > 1 && version.starts_with('0') {
+        return Err("a versão não pode ter zeros à esquerda".to_string());
+    }
+    version
+        .parse::<u8>()
+        .map_err(|_| "o número da versão está fora do intervalo".to_string())
+}
+
+fn fetch_laravel_versions() -> Option<Vec<u8>> {
+    let output = Command::new("curl")
+        .arg("-fsSL")
+        .arg("--max-time")
+        .arg("10")
+        .arg(PACKAGIST_LARAVEL_URL)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let majors = parse_packagist_majors(&String::from_utf8_lossy(&output.stdout));
+    if majors.is_empty() {
+        None
+    } else {
+        Some(majors)
+    }
+}
+
+fn parse_release_tag(metadata: &str) -> Option<String> {
+    let marker = "\"tag_name\"";
+    let rest = &metadata[metadata.find(marker)? + marker.len()..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+    let tag = rest[..rest.find('"')?].trim();
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag.to_string())
+    }
+}
+
+fn parse_release_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let version = tag.trim().trim_start_matches(['v', 'V']);
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+fn fetch_latest_release_tag() -> Option<String> {
+    let output = Command::new("curl")
+        .arg("-fsSL")
+        .arg("--max-time")
+        .arg("3")
+        .arg("-H")
+        .arg("Accept: application/vnd.github+json")
+        .arg(LATEST_RELEASE_URL)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_release_tag(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn latest_release_tag() -> Option<String> {
+    let cache_path = cache_file_path("latest-release");
+
+    if let Some(cache_path) = &cache_path
+        && let Ok(modified) = fs::metadata(cache_path).and_then(|meta| meta.modified())
+        && modified
+            .elapsed()
+            .is_ok_and(|age| age <= Duration::from_secs(VERSION_CACHE_TTL_SECS))
+        && let Ok(tag) = fs::read_to_string(cache_path)
+        && !tag.trim().is_empty()
+    {
+        return Some(tag.trim().to_string());
+    }
+
+    // Sem rede a verificação é opcional: não vale nem o aviso de stub.
+    if OFFLINE.get().copied().unwrap_or(false) {
+        return None;
+    }
+
+    let tag = fetch_latest_release_tag()?;
+    if let Some(cache_path) = &cache_path {
+        if let Some(parent) = cache_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(cache_path, &tag);
+    }
+    Some(tag)
+}
+
+fn print_update_check() {
+    let installed = env!("CARGO_PKG_VERSION");
+    let Some(tag) = latest_release_tag() else {
+        println!("Não foi possível verificar atualizações agora (lara {installed}).");
+        return;
+    };
+
+    match (
+        parse_release_version(&tag),
+        parse_release_version(installed),
+    ) {
+        (Some(latest), Some(current)) if latest > current => {
+            println!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!("Atualização disponível: {installed} -> {tag}")
+                )
+            );
+            println!("Novidades: {RELEASES_PAGE_URL}/tag/{tag}");
+        }
+        (Some(_), Some(_)) => {
+            println!(
+                "{}",
+                paint(Tone::Success, &format!("lara {installed} está atualizado."))
+            );
+        }
+        _ => println!("Não foi possível comparar '{tag}' com a versão instalada ({installed})."),
+    }
+}
+
+fn available_laravel_versions() -> (Vec<u8>, &'static str) {
+    let cache_path = version_cache_path();
+
+    if let Some(versions) = cache_path.as_deref().and_then(read_cached_versions) {
+        return (versions, "cache");
+    }
+
+    if OFFLINE.get().copied().unwrap_or(false) {
+        print_offline_stub("Consulta de versões no Packagist");
+    } else if let Some(versions) = fetch_laravel_versions() {
+        if let Some(cache_path) = &cache_path {
+            let serialized = versions
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            if let Some(parent) = cache_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(cache_path, serialized);
+        }
+        return (versions, "Packagist");
+    }
+
+    (
+        (MINIMAL_LARAVEL_VERSION..=DEFAULT_LARAVEL_VERSION).collect(),
+        "lista interna (offline)",
+    )
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+fn running_in_wsl() -> bool {
+    env::var_os("WSL_DISTRO_NAME").is_some()
+        || fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+fn os_description() -> String {
+    let pretty_name = fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|name| name.trim_matches('"').to_string())
+            })
+        });
+    match pretty_name {
+        Some(name) => format!("{} ({} {})", name, env::consts::OS, env::consts::ARCH),
+        None => format!("{} {}", env::consts::OS, env::consts::ARCH),
+    }
+}
+
+// Só leitura: não cria .env, não sobe contêineres e não altera nada no host
+fn print_env_report(json: bool) -> Result<(), AppError> {
+    let env_path = find_env_path(ENV_FILE);
+    if let Some(path) = &env_path {
+        let _ = dotenv::from_path(path);
+    }
+
+    let docker = command_stdout("docker", &["version", "--format", "{{.Server.Version}}"])
+        .or_else(|| command_stdout("docker", &["--version"]));
+    let compose = command_stdout("docker", &["compose", "version", "--short"])
+        .map(|version| format!("v2 ({})", version))
+        .or_else(|| {
+            command_stdout("docker-compose", &["version", "--short"])
+                .map(|version| format!("v1 ({})", version))
+        });
+
+    let container_name = env::var("CONTAINER_NAME")
+        .ok()
+        .map(|name| normalize_container_name(name.trim()))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_CONTAINER_NAME.to_string());
+    let containers: Vec<(String, bool)> = ["php", "mariadb", "apache", "node"]
+        .iter()
+        .map(|service| {
+            let name = format!("{}_{}", container_name, service);
+            let running = matches!(check_container_is_running(&name), Ok(true));
+            (name, running)
+        })
+        .collect();
+
+    let env_display = env_path.as_deref().map(|path| {
+        fs::canonicalize(path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .display()
+            .to_string()
+    });
+    let fields = [
+        ("tool_version", Some(env!("CARGO_PKG_VERSION").to_string())),
+        ("os", Some(os_description())),
+        ("wsl", Some(running_in_wsl().to_string())),
+        ("docker", docker),
+        ("compose", compose),
+        ("env_file", env_display),
+    ];
+
+    if json {
+        let mut entries: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) if *key == "wsl" => format!("\"{}\":{}", key, value),
+                Some(value) => format!("\"{}\":\"{}\"", key, json_escape(value)),
+                None => format!("\"{}\":null", key),
+            })
+            .collect();
+        entries.push(format!(
+            "\"containers\":[{}]",
+            containers
+                .iter()
+                .map(|(name, running)| format!(
+                    "{{\"name\":\"{}\",\"running\":{}}}",
+                    json_escape(name),
+                    running
+                ))
+                .collect::<Vec<String>>()
+                .join(",")
+        ));
+        println!("{{{}}}", entries.join(","));
+        return Ok(());
+    }
+
+    println!("Relatório do ambiente (anexe ao abrir uma issue):");
+    for (key, value) in &fields {
+        println!(
+            "  {:<14} {}",
+            key,
+            value.as_deref().unwrap_or("não encontrado")
+        );
+    }
+    println!("  containers");
+    for (name, running) in &containers {
+        println!(
+            "    {:<32} {}",
+            name,
+            if *running {
+                "em execução"
+            } else {
+                "parado/ausente"
+            }
+        );
+    }
+    Ok(())
+}
+
+fn print_laravel_version_list() -> Result<(), AppError> {
+    let (versions, source) = available_laravel_versions();
+
+    println!(
+        "Versões estáveis do Laravel disponíveis (fonte: {}):",
+        source
+    );
+    for version in versions
+        .iter()
+        .rev()
+        .filter(|version| **version >= MINIMAL_LARAVEL_VERSION)
+    {
+        if *version == DEFAULT_LARAVEL_VERSION {
+            println!("  {} (LTS, default)", version);
+        } else {
+            println!("  {}", version);
+        }
+    }
+    println!("Versão mínima aceita: {}", MINIMAL_LARAVEL_VERSION);
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NamingStyle {
+    Kebab,
+    Snake,
+    Preserve,
+}
+
+impl NamingStyle {
+    fn describe(self) -> &'static str {
+        match self {
+            NamingStyle::Kebab => "kebab-case",
+            NamingStyle::Snake => "snake_case",
+            NamingStyle::Preserve => "caixa preservada",
+        }
+    }
+}
+
+// Branch atual em kebab-case ('feature/login' -> 'feature-login'); em HEAD destacado ou
+// nos branches padrão (main/master) usa o nome do repositório
+fn project_name_from_git() -> Result<String, AppError> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .stderr(Stdio::null())
+            .traced()
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let branch = git(&["symbolic-ref", "--short", "HEAD"])
+        .filter(|branch| !matches!(branch.as_str(), "main" | "master"));
+    let source = branch.or_else(|| {
+        git(&["rev-parse", "--show-toplevel"]).and_then(|top_level| {
+            Path::new(&top_level)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+    });
+
+    match source.map(|source| format_to_kebab_case(&source)) {
+        Some(name) if !name.is_empty() => Ok(name),
+        _ => Err(AppError::Validation(
+            "--name-from-git: não foi possível obter o branch nem o repositório git do diretório atual."
+                .to_string(),
+        )),
+    }
+}
+
+fn format_project_name(input: &str, style: NamingStyle) -> String {
+    match style {
+        NamingStyle::Kebab => format_to_kebab_case(input),
+        NamingStyle::Snake => format_to_kebab_case(input).replace('-', "_"),
+        NamingStyle::Preserve => input
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("-")
+            .trim_matches(|c| c == '-' || c == '_')
+            .to_string(),
+    }
+}
+
+// O host continua em kebab-case minúsculo independentemente do NAMING_STYLE
+fn project_host_for(project_name: &str) -> String {
+    format!("{}.test", format_to_kebab_case(project_name))
+}
+
+fn format_to_kebab_case(input: &str) -> String {
+    let lower = input.to_lowercase();
+    let mut result = lower
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>();
+
+    result = result.split_whitespace().collect::<Vec<&str>>().join("-");
+
+    while result.contains("--") {
+        result = result.replace("--", "-");
+    }
+
+    result.trim_matches('-').to_string()
+}
+
+fn find_project_root() -> Option<PathBuf> {
+    let mut current = env::current_dir().ok()?;
+
+    for _ in 0..=MAX_PROJECT_ROOT_DEPTH {
+        if current.join("docker").is_dir() {
+            return Some(current);
+        }
+
+        if !current.pop() {
+            break;
+        }
+    }
+    None
+}
+
+fn host_src_dir() -> PathBuf {
+    match find_project_root() {
+        Some(project_root) => project_root.join("src"),
+        None => PathBuf::from("../src"),
+    }
+}
+
+// ../src pode ser um link simbólico ou submódulo; verificações e remoções usam o caminho real.
+fn resolved_src_dir() -> PathBuf {
+    let src_dir = host_src_dir();
+    let is_symlink = fs::symlink_metadata(&src_dir)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+
+    match fs::canonicalize(&src_dir) {
+        Ok(real_path) => {
+            if is_symlink {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "AVISO: {} é um link simbólico para {}. Usando o caminho real.",
+                            src_dir.display(),
+                            real_path.display()
+                        )
+                    )
+                );
+            }
+            real_path
+        }
+        Err(_) => {
+            if is_symlink {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!("AVISO: {} é um link simbólico quebrado.", src_dir.display())
+                    )
+                );
+            }
+            src_dir
+        }
+    }
+}
+
+fn host_project_dir(input: &ProjectInput) -> PathBuf {
+    match find_project_root() {
+        Some(_) => host_src_dir().join(&input.project_dir),
+        None => PathBuf::from(&input.project_path),
+    }
+}
+
+fn app_relative_path(input: &ProjectInput, config: &AppConfig) -> String {
+    match &config.app_subdir {
+        Some(subdir) => format!("{}/{}", input.project_dir, subdir),
+        None => input.project_dir.clone(),
+    }
+}
+
+fn container_app_dir(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "{}/{}",
+        CONTAINER_WEB_ROOT,
+        app_relative_path(input, config)
+    )
+}
+
+fn container_project_dir_in_use(config: &AppConfig, name: &str) -> bool {
+    if !matches!(
+        check_container_is_running(&config.php_container_name),
+        Ok(true)
+    ) {
+        return false;
+    }
+
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, name);
+    capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "[ -d '{dir}' ] && [ -n \"$(ls -A '{dir}')\" ] && echo in-use || true",
+                dir = project_dir
+            ),
+        ],
+    )
+    .map(|output| output == "in-use")
+    .unwrap_or(false)
+}
+
+fn remove_container_project_dir(config: &AppConfig, name: &str) -> Result<bool, AppError> {
+    if !is_safe_relative_dir(name) {
+        return Err(AppError::Validation(format!(
+            "Remoção recusada: nome de projeto inválido '{}'.",
+            name
+        )));
+    }
+
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, name);
+
+    if !config.assume_yes
+        && !confirm(
+            &format!(
+                "Remover permanentemente '{}' no contêiner '{}'?",
+                project_dir, config.php_container_name
+            ),
+            false,
+        )?
+    {
+        println!("Remoção cancelada.");
+        return Ok(false);
+    }
+
+    execute_command_in_container(&config.php_container_name, &["rm", "-rf", &project_dir])?;
+    println!("Diretório removido no contêiner: {}", project_dir);
+    Ok(true)
+}
+
+fn confirm(prompt: &str, default: bool) -> Result<bool, AppError> {
+    let hint = if default {
+        "(Y/n, ENTER=Y)"
+    } else {
+        "(y/N, ENTER=N)"
+    };
+
+    loop {
+        print!("{} {}: ", prompt, hint);
+        io::stdout().flush()?;
+
+        let mut decision = String::new();
+        if io::stdin().read_line(&mut decision)? == 0 {
+            println!();
+            return Ok(default);
+        }
+
+        match decision.trim().to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            choice => eprintln!("Escolha inválida ('{}'). Digite 'y' ou 'n'.", choice),
+        }
+    }
+}
+
+fn ensure_path_inside(target: &Path, base_dir: &Path) -> Result<PathBuf, AppError> {
+    let canonical_base = fs::canonicalize(base_dir)?;
+    let canonical_target = fs::canonicalize(target)?;
+
+    if canonical_target == canonical_base || !canonical_target.starts_with(&canonical_base) {
+        return Err(AppError::Validation(format!(
+            "Remoção recusada: '{}' não está dentro de '{}'.",
+            canonical_target.display(),
+            canonical_base.display()
+        )));
+    }
+
+    Ok(canonical_target)
+}
+
+fn remove_project_dir(target: &Path, base_dir: &Path, assume_yes: bool) -> Result<bool, AppError> {
+    if fs::symlink_metadata(target)?.file_type().is_symlink() {
+        return Err(AppError::Validation(format!(
+            "Remoção recusada: '{}' é um link simbólico.",
+            target.display()
+        )));
+    }
+
+    let canonical_target = ensure_path_inside(target, base_dir)?;
+
+    if !assume_yes
+        && !confirm(
+            &format!("Remover permanentemente '{}'?", canonical_target.display()),
+            false,
+        )?
+    {
+        println!("Remoção cancelada.");
+        return Ok(false);
+    }
+
+    fs::remove_dir_all(&canonical_target)?;
+    println!("Diretório removido: {}", canonical_target.display());
+    Ok(true)
+}
+
+fn available_disk_space_mb(path: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().nth(1)?;
+    let available_kb = line.split_whitespace().nth(3)?.parse::<u64>().ok()?;
+    Some(available_kb / 1024)
+}
+
+fn check_disk_space(config: &AppConfig) -> Result<(), AppError> {
+    let src_dir = host_src_dir();
+
+    let available_mb = match available_disk_space_mb(&src_dir) {
+        Some(available_mb) => available_mb,
+        None => {
+            println!(
+                "Não foi possível verificar o espaço livre em {}. Verificação ignorada.",
+                src_dir.display()
+            );
+            return Ok(());
+        }
+    };
+
+    if available_mb >= MIN_FREE_DISK_MB {
+        println!(
+            "Espaço livre em {}: {} MB.",
+            src_dir.display(),
+            available_mb
+        );
+        return Ok(());
+    }
+
+    let message = format!(
+        "Espaço livre insuficiente em {}: {} MB disponíveis, recomendado pelo menos {} MB para composer e npm.",
+        src_dir.display(),
+        available_mb,
+        MIN_FREE_DISK_MB
+    );
+
+    if config.strict {
+        return Err(AppError::Validation(message));
+    }
+
+    eprintln!("{}", paint(Tone::Warning, &format!("AVISO: {}", message)));
+    Ok(())
+}
+
+fn apply_project_template(
+    input: &ProjectInput,
+    template_dir: &Path,
+    overwrite: bool,
+) -> Result<(), AppError> {
+    println!("---");
+    println!(
+        "Copiando arquivos do template '{}'...",
+        template_dir.display()
+    );
+
+    if !template_dir.is_dir() {
+        return Err(AppError::Validation(format!(
+            "O diretório de template '{}' não existe.",
+            template_dir.display()
+        )));
+    }
+
+    let project_dir = host_project_dir(input);
+    let (copied, skipped) = copy_template_dir(template_dir, &project_dir, input, overwrite)?;
+
+    println!(
+        "Template aplicado: {} arquivo(s) copiado(s), {} ignorado(s) por já existirem.",
+        copied, skipped
+    );
+    if skipped > 0 && !overwrite {
+        println!("Use --overwrite-template para sobrescrever arquivos existentes.");
+    }
+
+    Ok(())
+}
+
+fn copy_template_dir(
+    source: &Path,
+    destination: &Path,
+    input: &ProjectInput,
+    overwrite: bool,
+) -> Result<(usize, usize), AppError> {
+    let mut copied = 0;
+    let mut skipped = 0;
+
+    fs::create_dir_all(destination)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let source_path = entry.path();
+        let destination_path = destination.join(entry.file_name());
+
+        if source_path.is_dir() {
+            let (sub_copied, sub_skipped) =
+                copy_template_dir(&source_path, &destination_path, input, overwrite)?;
+            copied += sub_copied;
+            skipped += sub_skipped;
+            continue;
+        }
+
+        if destination_path.exists() && !overwrite {
+            skipped += 1;
+            continue;
+        }
+
+        match fs::read_to_string(&source_path) {
+            Ok(content) => {
+                let rendered = content
+                    .replace("{{project_name}}", &input.project_name)
+                    .replace("{{host}}", &input.project_host);
+                fs::write(&destination_path, rendered)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                fs::copy(&source_path, &destination_path)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        copied += 1;
+    }
+
+    Ok((copied, skipped))
+}
+
+fn render_vhost(input: &ProjectInput, config: &AppConfig) -> String {
+    let handler_block = if input.octane {
+        format!(
+            r#"    # Laravel Octane atende as requisições no próprio servidor HTTP
+    ProxyPreserveHost On
+    ProxyPass / http://{target}/
+    ProxyPassReverse / http://{target}/"#,
+            target = config.octane_target
+        )
+    } else {
+        format!(
+            r#"    <FilesMatch \.php$>
+        SetHandler "{}"
+    </FilesMatch>"#,
+            config.php_fpm_handler
+        )
+    };
+
+    // Logs por projeto; sem diretório configurado usa os logs padrão do contêiner
+    let log_block = config
+        .vhost_log_dir
+        .as_ref()
+        .map(|dir| {
+            format!(
+                "    ErrorLog {dir}/{name}-error.log\n    CustomLog {dir}/{name}-access.log combined\n\n",
+                dir = dir,
+                name = input.project_name
+            )
+        })
+        .unwrap_or_default();
+
+    let site_body = format!(
+        r#"    # Nome do host que será usado (ex: minha-app.test)
+    ServerName {}
+    ServerAdmin {}
+
+    # Diretório raiz do projeto Laravel (montado em /var/www/html/)
+    DocumentRoot {}/public
+
+{}    <Directory {}/public>
+{}        AllowOverride {}
+         Require {}
+        DirectoryIndex index.php index.html
+    </Directory>
+
+{}"#,
+        input.project_host,
+        config.vhost_server_admin,
+        container_app_dir(input, config),
+        log_block,
+        container_app_dir(input, config),
+        config
+            .vhost_options
+            .as_ref()
+            .map(|options| format!("        Options {}\n", options))
+            .unwrap_or_default(),
+        config.vhost_allow_override,
+        config.vhost_require,
+        handler_block
+    );
+
+    let mut vhost = format!(
+        "{}<VirtualHost *:{}>\n{}\n</VirtualHost>",
+        listen_block(config.http_port, 80),
+        config.http_port,
+        site_body
+    );
+
+    if config.ssl {
+        vhost.push_str(&format!(
+            r#"
+
+{}<VirtualHost *:{}>
+    SSLEngine on
+    SSLCertificateFile {certs}/{host}.crt
+    SSLCertificateKeyFile {certs}/{host}.key
+
+{}
+</VirtualHost>"#,
+            listen_block(config.https_port, 0),
+            config.https_port,
+            site_body,
+            certs = format!("{}/certs", CONTAINER_VHOSTS_DIR),
+            host = input.project_host
+        ));
+    }
+
+    vhost
+}
+
+fn listen_block(port: u16, default_port: u16) -> String {
+    if port == default_port {
+        return String::new();
+    }
+
+    format!(
+        r#"<IfDefine !lara_listen_{port}>
+    Define lara_listen_{port}
+    Listen {port}
+</IfDefine>
+
+"#,
+        port = port
+    )
+}
+
+fn generate_ssl_certificate(project_root: &Path, input: &ProjectInput) -> Result<bool, AppError> {
+    let certs_dir = project_root.join(VHOSTS_DIR).join("certs");
+    let cert_path = certs_dir.join(format!("{}.crt", input.project_host));
+    let key_path = certs_dir.join(format!("{}.key", input.project_host));
+
+    if cert_path.is_file() && key_path.is_file() {
+        println!(
+            "Certificado SSL já existe para {}: {}",
+            input.project_host,
+            cert_path.display()
+        );
+        return Ok(false);
+    }
+
+    fs::create_dir_all(&certs_dir)?;
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Gerando certificado autoassinado para {}...",
+                input.project_host
+            )
+        )
+    );
+
+    let status = Command::new("openssl")
+        .args([
+            "req", "-x509", "-nodes", "-newkey", "rsa:2048", "-days", "825",
+        ])
+        .arg("-subj")
+        .arg(format!("/CN={}", input.project_host))
+        .arg("-addext")
+        .arg(format!("subjectAltName=DNS:{}", input.project_host))
+        .arg("-keyout")
+        .arg(&key_path)
+        .arg("-out")
+        .arg(&cert_path)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Validation(format!(
+                "Falha ao executar 'openssl' (necessário para --ssl): {}",
+                e
+            ))
+            .with_source(command_context(e))
+        })?;
+
+    if !status.success() {
+        return Err(AppError::Validation(format!(
+            "Falha ao gerar o certificado SSL para {}. Status: {:?}",
+            input.project_host, status
+        )));
+    }
+
+    println!("Certificado SSL criado: {}", cert_path.display());
+    Ok(true)
+}
+
+fn vhost_file_content(input: &ProjectInput, config: &AppConfig) -> String {
+    let vhost_content = render_vhost(input, config);
+    match &input.label {
+        Some(label) => format!("{}{}\n{}", LABEL_MARKER, label, vhost_content),
+        None => vhost_content,
+    }
+}
+
+fn create_vhost_file(input: &ProjectInput, config: &AppConfig) -> Result<bool, AppError> {
+    println!("Criando arquivo de configuração Vhost...");
+
+    let project_root = find_project_root().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "Não foi possível determinar o diretório raiz do projeto {}.",
+                input.project_name
+            ),
+        )
+    })?;
+
+    let vhosts_dir = project_root.join(VHOSTS_DIR);
+    let vhost_filename = format!("{}.conf", input.project_host);
+    let vhost_path = vhosts_dir.join(&vhost_filename);
+
+    let certificate_created = config.ssl && generate_ssl_certificate(&project_root, input)?;
+
+    let vhost_content = vhost_file_content(input, config);
+
+    if config.vhost_mode == VhostMode::SingleInclude {
+        let changed = write_vhost_include_block(&vhosts_dir, input, &vhost_content)?;
+        return Ok(changed || certificate_created);
+    }
+
+    let existing = fs::read_to_string(&vhost_path).ok();
+    if existing.as_deref() == Some(vhost_content.as_str()) {
+        println!("Vhost já está atualizado: {}", vhost_path.display());
+        return Ok(certificate_created);
+    }
+
+    if let Some(owner) = existing.as_deref().and_then(vhost_app_dir)
+        && owner != input.project_dir
+        && !owner.starts_with(&format!("{}/", input.project_dir))
+    {
+        let message = format!(
+            "O Vhost {} já aponta para {}/{}, não para o projeto '{}'.",
+            vhost_path.display(),
+            CONTAINER_WEB_ROOT,
+            owner,
+            input.project_name
+        );
+        if !config.force {
+            return Err(AppError::Validation(format!(
+                "{} Use --force para sobrescrevê-lo.",
+                message
+            )));
+        }
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!("AVISO: {} Sobrescrevendo (--force).", message)
+            )
+        );
+    }
+
+    fs::write(&vhost_path, vhost_content)?;
+
+    println!("Vhost criado com sucesso: {}", vhost_path.display());
+
+    Ok(true)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VhostMode {
+    PerProject,
+    SingleInclude,
+}
+
+fn vhost_block_markers(project_name: &str) -> (String, String) {
+    (
+        format!("# BEGIN {}", project_name),
+        format!("# END {}", project_name),
+    )
+}
+
+fn extract_vhost_block(content: &str, project_name: &str) -> Option<String> {
+    let (begin, end) = vhost_block_markers(project_name);
+    let mut lines = content.lines().skip_while(|line| line.trim() != begin);
+    lines.next()?;
+    let block: Vec<&str> = lines.take_while(|line| line.trim() != end).collect();
+    Some(block.join("\n"))
+}
+
+// Remove o bloco do projeto e, se informado, grava o novo conteúdo no mesmo lugar (ou no fim)
+fn replace_vhost_block(content: &str, project_name: &str, block: Option<&str>) -> String {
+    let (begin, end) = vhost_block_markers(project_name);
+    let mut result: Vec<String> = Vec::new();
+    let mut inside = false;
+    let mut replaced = false;
+
+    for line in content.lines() {
+        if !inside && line.trim() == begin {
+            inside = true;
+            if let Some(block) = block {
+                result.push(begin.clone());
+                result.push(block.trim_end().to_string());
+                result.push(end.clone());
+                replaced = true;
+            }
+            continue;
+        }
+        if inside {
+            inside = line.trim() != end;
+            continue;
+        }
+        result.push(line.to_string());
+    }
+
+    if let Some(block) = block
+        && !replaced
+    {
+        if result.last().is_some_and(|line| !line.trim().is_empty()) {
+            result.push(String::new());
+        }
+        result.push(begin);
+        result.push(block.trim_end().to_string());
+        result.push(end);
+    }
+
+    let mut output = result.join("\n");
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+fn write_vhost_include_block(
+    vhosts_dir: &Path,
+    input: &ProjectInput,
+    vhost_content: &str,
+) -> Result<bool, AppError> {
+    let include_path = vhosts_dir.join(VHOST_INCLUDE_FILE);
+    let existing = fs::read_to_string(&include_path).unwrap_or_default();
+
+    if extract_vhost_block(&existing, &input.project_name).as_deref()
+        == Some(vhost_content.trim_end())
+    {
+        println!(
+            "Vhost já está atualizado: bloco '{}' em {}",
+            input.project_name,
+            include_path.display()
+        );
+        return Ok(false);
+    }
+
+    fs::write(
+        &include_path,
+        replace_vhost_block(&existing, &input.project_name, Some(vhost_content)),
+    )?;
+    println!(
+        "Vhost gravado no bloco '{}' de {}",
+        input.project_name,
+        include_path.display()
+    );
+    Ok(true)
+}
+
+fn remove_vhost_include_block(vhosts_dir: &Path, project_name: &str) -> Result<bool, AppError> {
+    let include_path = vhosts_dir.join(VHOST_INCLUDE_FILE);
+    let existing = match fs::read_to_string(&include_path) {
+        Ok(existing) => existing,
+        Err(_) => return Ok(false),
+    };
+    if extract_vhost_block(&existing, project_name).is_none() {
+        return Ok(false);
+    }
+
+    fs::write(
+        &include_path,
+        replace_vhost_block(&existing, project_name, None),
+    )?;
+    println!(
+        "Bloco '{}' removido de {}",
+        project_name,
+        include_path.display()
+    );
+    Ok(true)
+}
+
+fn project_vhost_content(input: &ProjectInput, config: &AppConfig) -> Option<String> {
+    match config.vhost_mode {
+        VhostMode::PerProject => {
+            vhost_file_path(input).and_then(|path| fs::read_to_string(path).ok())
+        }
+        VhostMode::SingleInclude => find_project_root()
+            .and_then(|root| {
+                fs::read_to_string(root.join(VHOSTS_DIR).join(VHOST_INCLUDE_FILE)).ok()
+            })
+            .and_then(|content| extract_vhost_block(&content, &input.project_name)),
+    }
+}
+
+fn vhost_location(input: &ProjectInput, config: &AppConfig) -> String {
+    match config.vhost_mode {
+        VhostMode::PerProject => format!("{}/{}.conf", VHOSTS_DIR, input.project_host),
+        VhostMode::SingleInclude => format!(
+            "{}/{} (bloco '{}')",
+            VHOSTS_DIR, VHOST_INCLUDE_FILE, input.project_name
+        ),
+    }
+}
+
+fn check_container_is_running(name: &str) -> Result<bool, io::Error> {
+    let output = Command::new("docker")
+        .arg("ps")
+        .arg("-q")
+        .arg("-f")
+        .arg(format!("name={}", name))
+        .traced()
+        .output()?;
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    Ok(!status.trim().is_empty())
+}
+
+fn container_compose_project(name: &str) -> Option<String> {
+    let output = Command::new("docker")
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{index .Config.Labels \"com.docker.compose.project\"}}")
+        .arg(name)
+        .traced()
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let project = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!project.is_empty() && project != "<no value>").then_some(project)
+}
+
+// Retorna false quando a stack alheia foi derrubada e a correta precisa ser iniciada
+// Projeto Compose do contêiner PHP em execução, quando ele não é a stack deste diretório
+fn foreign_compose_project(config: &AppConfig) -> Option<String> {
+    let expected = compose_project_name(&find_project_root()?);
+    container_compose_project(&config.php_container_name).filter(|actual| *actual != expected)
+}
+
+fn stack_down_args(compose_project: &str) -> [&str; 4] {
+    ["compose", "-p", compose_project, "down"]
+}
+
+fn ensure_container_in_expected_stack(config: &AppConfig) -> Result<bool, AppError> {
+    let actual = match foreign_compose_project(config) {
+        Some(actual) => actual,
+        None => return Ok(true),
+    };
+    let expected = find_project_root()
+        .map(|project_root| compose_project_name(&project_root))
+        .unwrap_or_default();
+
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            &format!(
+                "AVISO: O contêiner '{}' em execução pertence ao projeto Compose '{}', não a '{}'.",
+                config.php_container_name, actual, expected
+            )
+        )
+    );
+
+    if !confirm(
+        &format!(
+            "Parar a stack '{}' (docker compose down, volumes preservados) e iniciar a stack deste diretório?",
+            actual
+        ),
+        false,
+    )? {
+        return Err(AppError::Validation(format!(
+            "O contêiner '{}' pertence a outra stack ('{}'). Use CONTAINER_NAME ou --php-container para apontar para a stack correta.",
+            config.php_container_name, actual
+        )));
+    }
+
+    let status = Command::new("docker")
+        .args(stack_down_args(&actual))
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker compose down': {}", e))
+                .with_source(command_context(e))
+        })?;
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao parar a stack '{}'. Status: {:?}",
+            actual, status
+        )));
+    }
+    Ok(false)
+}
+
+fn inspect_container_health(name: &str) -> Result<String, AppError> {
+    let output = Command::new("docker")
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{if .State.Health}}{{.State.Health.Status}}{{else}}none{{end}}")
+        .arg(name)
+        .traced()
+        .output()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker inspect': {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !output.status.success() {
+        return Ok("missing".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn wait_for_container_health(name: &str, timeout_secs: u64) -> Result<(), AppError> {
+    let poll_interval = std::time::Duration::from_secs(3);
+    let started = std::time::Instant::now();
+
+    loop {
+        let health = inspect_container_health(name)?;
+
+        match health.as_str() {
+            "healthy" => {
+                clear_wait_line()?;
+                println!("Contêiner '{}' saudável (healthcheck).", name);
+                return Ok(());
+            }
+            "none" => {
+                clear_wait_line()?;
+                println!(
+                    "Contêiner '{}' não define healthcheck. Usando verificação por 'docker ps'.",
+                    name
+                );
+                return match check_container_is_running(name) {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(AppError::Docker(format!(
+                        "O contêiner '{}' não está em execução.",
+                        name
+                    ))),
+                    Err(e) => Err(AppError::Docker(format!(
+                        "Falha ao verificar o status do contêiner: {}",
+                        e
+                    ))),
+                };
+            }
+            _ => {}
+        }
+
+        if started.elapsed().as_secs() >= timeout_secs {
+            clear_wait_line()?;
+            return Err(AppError::Docker(format!(
+                "O contêiner '{}' não ficou saudável em {}s (último estado: {}).",
+                name, timeout_secs, health
+            )));
+        }
+
+        print_wait_progress(
+            &format!(
+                "Aguardando healthcheck do contêiner '{}' (estado: {})",
+                name, health
+            ),
+            started.elapsed(),
+            timeout_secs,
+        )?;
+        std::thread::sleep(poll_interval);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WaitStrategy {
+    Ps,
+    Exec,
+    Health,
+    Auto,
+}
+
+// 'ping' responde mesmo sem credenciais: só confirma que o servidor aceita conexões
+const DB_READY_PROBE: &[&str] = &[
+    "sh",
+    "-c",
+    "mariadb-admin ping --silent 2>/dev/null || mysqladmin ping --silent",
+];
+
+fn wait_for_container_ready(
+    name: &str,
+    probe: &[&str],
+    config: &AppConfig,
+) -> Result<(), AppError> {
+    let strategy = match config.wait_strategy {
+        WaitStrategy::Auto if inspect_container_health(name)? == "none" => WaitStrategy::Exec,
+        WaitStrategy::Auto => WaitStrategy::Health,
+        strategy => strategy,
+    };
+
+    match strategy {
+        // O 'docker ps' já foi conferido ao subir a stack
+        WaitStrategy::Ps | WaitStrategy::Auto => Ok(()),
+        WaitStrategy::Health => wait_for_container_health(name, config.wait_timeout_secs),
+        WaitStrategy::Exec => wait_for_exec_probe(name, probe, config.wait_timeout_secs),
+    }
+}
+
+fn wait_for_exec_probe(name: &str, probe: &[&str], timeout_secs: u64) -> Result<(), AppError> {
+    let poll_interval = Duration::from_secs(2);
+    let started = Instant::now();
+
+    loop {
+        let ready = Command::new("docker")
+            .arg("exec")
+            .arg(name)
+            .args(probe)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .traced()
+            .status()
+            .is_ok_and(|status| status.success());
+        if ready {
+            clear_wait_line()?;
+            return Ok(());
+        }
+
+        if started.elapsed().as_secs() >= timeout_secs {
+            clear_wait_line()?;
+            return Err(AppError::Docker(format!(
+                "O contêiner '{}' não respondeu ao comando de verificação ({}) em {}s.",
+                name,
+                probe.join(" "),
+                timeout_secs
+            )));
+        }
+
+        print_wait_progress(
+            &format!("Aguardando o contêiner '{}' responder", name),
+            started.elapsed(),
+            timeout_secs,
+        )?;
+        thread::sleep(poll_interval);
+    }
+}
+
+fn print_wait_progress(label: &str, elapsed: Duration, limit_secs: u64) -> io::Result<()> {
+    let line = format!(
+        "{}... {}s decorridos, limite {}s",
+        label,
+        elapsed.as_secs(),
+        limit_secs
+    );
+
+    if io::stdout().is_terminal() {
+        print!("\r\x1b[2K{}", line);
+    } else {
+        println!("{}", line);
+    }
+    io::stdout().flush()
+}
+
+fn clear_wait_line() -> io::Result<()> {
+    if io::stdout().is_terminal() {
+        print!("\r\x1b[2K");
+        io::stdout().flush()?;
+    }
+    Ok(())
+}
+
+// A stack pode ter subido só agora, então o plano não tinha como ver um projeto existente
+fn install_laravel_project(input: &mut ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    if config.resume && project_has_artisan(input, config)? {
+        print_resume_skip("Projeto Laravel");
+        return Ok(());
+    }
+    let laravel_installer = use_laravel_installer(input, config)?;
+    // Versão inexistente no Packagist: pergunta outra sem derrubar a stack já iniciada
+    while !timed_step("create_project", || {
+        if laravel_installer {
+            run_laravel_new(input, config)
+        } else {
+            run_composer_create_project(input, config)
+        }
+    })? {
+        input.laravel_version = prompt_laravel_version()?;
+    }
+    Ok(())
+}
+
+fn resume_skip_reason(artifact: &str) -> String {
+    format!("{} já existe, pulando (--resume).", artifact)
+}
+
+fn print_resume_skip(artifact: &str) {
+    println!(
+        "{}",
+        paint(Tone::Step, &format!(">> {}", resume_skip_reason(artifact)))
+    );
+}
+
+fn project_has_artisan(input: &ProjectInput, config: &AppConfig) -> Result<bool, AppError> {
+    let output = capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "[ -f '{}/artisan' ] && echo ok || true",
+                container_app_dir(input, config)
+            ),
+        ],
+    )?;
+    Ok(output == "ok")
+}
+
+fn vhost_file_path(input: &ProjectInput) -> Option<PathBuf> {
+    find_project_root().map(|root| {
+        root.join(VHOSTS_DIR)
+            .join(format!("{}.conf", input.project_host))
+    })
+}
+
+fn validate_compose_file(config: &AppConfig) -> Result<(), AppError> {
+    let compose_file = find_compose_file().ok_or_else(|| {
+        AppError::Docker(format!(
+            "Nenhum arquivo compose encontrado na raiz do projeto (procurados: {}).",
+            COMPOSE_FILE_NAMES.join(", ")
+        ))
+    })?;
+
+    let mut command = compose_command(config);
+    if let Some(project_root) = compose_file.parent() {
+        command.current_dir(project_root);
+    }
+
+    let output = command
+        .arg("-f")
+        .arg(&compose_file)
+        .arg("config")
+        .arg("-q")
+        .traced()
+        .output()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker compose config': {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !output.status.success() {
+        return Err(AppError::Docker(format!(
+            "O arquivo '{}' é inválido: {}",
+            compose_file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+fn compose_project_name(project_root: &Path) -> String {
+    if let Ok(name) = env::var("COMPOSE_PROJECT_NAME")
+        && !name.trim().is_empty()
+    {
+        return name.trim().to_string();
+    }
+
+    project_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+fn compose_working_dirs(filter: &str) -> Vec<PathBuf> {
+    let output = Command::new("docker")
+        .args(["ps", "-a", "--filter", filter, "--format"])
+        .arg("{{.Label \"com.docker.compose.project.working_dir\"}}")
+        .traced()
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn check_compose_project_collision(config: &AppConfig) {
+    let project_root = match find_project_root().and_then(|root| root.canonicalize().ok()) {
+        Some(root) => root,
+        None => return,
+    };
+
+    let project_name = compose_project_name(&project_root);
+    let same_root = |dir: &PathBuf| dir.canonicalize().ok().as_ref() == Some(&project_root);
+
+    let foreign_dirs: Vec<PathBuf> = compose_working_dirs(&format!(
+        "label=com.docker.compose.project={}",
+        project_name
+    ))
+    .into_iter()
+    .filter(|dir| !same_root(dir))
+    .collect();
+
+    if let Some(foreign_dir) = foreign_dirs.first() {
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: O projeto Compose '{}' já é usado por contêineres de outro diretório ({}). Defina COMPOSE_PROJECT_NAME no .env para separar as stacks.",
+                    project_name,
+                    foreign_dir.display()
+                )
+            )
+        );
+    }
+
+    let php_dirs = compose_working_dirs(&format!("name=^{}$", config.php_container_name));
+    if let Some(php_dir) = php_dirs.iter().find(|dir| !same_root(dir)) {
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: O contêiner '{}' pertence à stack de {}, não a {}. Use CONTAINER_NAME ou --php-container para apontar para a stack correta.",
+                    config.php_container_name,
+                    php_dir.display(),
+                    project_root.display()
+                )
+            )
+        );
+    }
+}
+
+fn validate_generated_config(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Validando configurações geradas (--validate-only)..."
+        )
+    );
+
+    let mut failures = Vec::new();
+
+    println!("Atualizações do .env:");
+    for (from, to) in env_replacements(input, config) {
+        println!("  '{}' -> '{}'", from, to);
+    }
+    for line in env_appended_lines(input, config) {
+        println!("  + '{}'", line);
+    }
+
+    match validate_compose_file(config) {
+        Ok(()) => println!("docker compose config: ok"),
+        Err(e) => {
+            println!("docker compose config: falhou");
+            failures.push(e.to_string());
+        }
+    }
+
+    let check_dir = env::temp_dir().join(format!("lara-validate-{}", std::process::id()));
+    fs::create_dir_all(&check_dir)?;
+    fs::write(
+        check_dir.join(format!("{}.conf", input.project_host)),
+        render_vhost(input, config),
+    )?;
+
+    let mut command = compose_command(config);
+    if let Some(project_root) = find_project_root() {
+        command.current_dir(project_root);
+    }
+
+    let output = command
+        .args(["run", "--rm", "--no-deps", "-T", "-v"])
+        .arg(format!("{}:/lara-check:ro", check_dir.display()))
+        .args([
+            "apache",
+            "httpd",
+            "-t",
+            "-c",
+            "IncludeOptional /lara-check/*.conf",
+        ])
+        .traced()
+        .output();
+    let _ = fs::remove_dir_all(&check_dir);
+
+    match output {
+        Ok(output) if output.status.success() => println!("apachectl configtest: ok"),
+        Ok(output) => {
+            println!("apachectl configtest: falhou");
+            failures.push(format!(
+                "Vhost inválido: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Err(e) => {
+            println!("apachectl configtest: não executado");
+            failures.push(format!("Falha ao executar o teste do Apache: {}", e));
+        }
+    }
+
+    if failures.is_empty() {
+        println!(
+            "{}",
+            paint(
+                Tone::Success,
+                "Configurações válidas. Nenhum projeto foi criado."
+            )
+        );
+        Ok(())
+    } else {
+        Err(AppError::Validation(failures.join("\n")))
+    }
+}
+
+fn compose_command(config: &AppConfig) -> Command {
+    let mut command = Command::new("docker");
+    command.arg("compose");
+    // O docker-compose.yml lê ${CONTAINER_NAME}; repassa o nome derivado para manter os contêineres alinhados
+    if let Some(name) = &config.auto_container_name {
+        command.env("CONTAINER_NAME", name);
+    }
+    command.args(compose_profile_args(config));
+    command
+}
+
+fn compose_profile_args(config: &AppConfig) -> Vec<&str> {
+    config
+        .compose_profiles
+        .iter()
+        .flat_map(|profile| ["--profile", profile.as_str()])
+        .collect()
+}
+
+// Linha exibida no plano para um comando que roda via compose_command
+fn compose_display(config: &AppConfig, args: &[&str]) -> String {
+    ["docker", "compose"]
+        .into_iter()
+        .chain(compose_profile_args(config))
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn compose_build_args(config: &AppConfig) -> Vec<&'static str> {
+    if config.build_no_cache {
+        vec!["build", "--no-cache"]
+    } else {
+        vec!["build"]
+    }
+}
+
+fn compose_up_args(config: &AppConfig) -> Vec<&'static str> {
+    // Sem rede, usa só as imagens já presentes em vez de tentar baixá-las
+    if config.offline {
+        vec!["up", "-d", "--pull", "never"]
+    } else {
+        vec!["up", "-d"]
+    }
+}
+
+fn pull_compose_images(config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Atualizando imagens (docker compose pull)..."
+        )
+    );
+
+    let failure = match compose_command(config)
+        .args(COMPOSE_PULL_ARGS)
+        .traced()
+        .status()
+    {
+        Ok(status) if status.success() => return Ok(()),
+        Ok(status) => format!("'docker compose pull' falhou. Status: {:?}", status),
+        Err(e) => format!("Falha ao executar 'docker compose pull': {}", e),
+    };
+
+    if config.pull_ignore_failure {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: {}. Continuando com as imagens em cache (--pull-ignore-failure).",
+                    failure
+                )
+            )
+        );
+        Ok(())
+    } else {
+        Err(AppError::Docker(format!(
+            "{}. Use --pull-ignore-failure para continuar com as imagens em cache.",
+            failure
+        )))
+    }
+}
+
+fn build_compose_images(config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            if config.build_no_cache {
+                ">> Reconstruindo imagens sem cache (docker compose build --no-cache)..."
+            } else {
+                ">> Reconstruindo imagens (docker compose build)..."
+            }
+        )
+    );
+
+    let mut build = compose_command(config);
+    build.args(compose_build_args(config));
+
+    let (status, output) = stream_command(&mut build, "'docker compose build'")?;
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "'docker compose build' falhou. Status: {:?}. Últimas linhas:\n{}",
+            status,
+            output_tail(&output, 20)
+        )));
+    }
+    Ok(())
+}
+
+// Retorna os contêineres que foram iniciados por esta execução
+fn ensure_stack_running(config: &AppConfig) -> Result<Vec<String>, AppError> {
+    if config.pull {
+        pull_compose_images(config)?;
+    }
+    let started_containers = start_compose_stack(config)?;
+    wait_for_stack_ready(config)?;
+    Ok(started_containers)
+}
+
+fn start_compose_stack(config: &AppConfig) -> Result<Vec<String>, AppError> {
+    if matches!(
+        check_container_is_running(&config.php_container_name),
+        Ok(true)
+    ) && !config.compose_build
+        && ensure_container_in_expected_stack(config)?
+    {
+        println!("Contêiner PHP ativo.");
+        return Ok(Vec::new());
+    }
+
+    println!(
+        "Contêiner PHP '{}' não está ativo. Iniciando o ambiente Docker Compose...",
+        config.php_container_name
+    );
+    let was_running: Vec<&String> = stack_containers(config)
+        .into_iter()
+        .filter(|container| matches!(check_container_is_running(container), Ok(true)))
+        .collect();
+    let mut up = compose_command(config);
+    up.args(compose_up_args(config));
+    let up_status = up.traced().status().map_err(|e| {
+        AppError::Docker(format!("Falha ao executar 'docker compose up -d': {}", e))
+            .with_source(command_context(e))
+    })?;
+
+    if !up_status.success() {
+        return Err(AppError::Docker(
+            "Falha ao iniciar o ambiente Docker Compose. Verifique as configurações.".to_string(),
+        ));
+    }
+
+    let base_delay = Duration::from_secs(CONTAINER_START_DELAY_SECS);
+    let budget_secs: u64 = (1..CONTAINER_START_ATTEMPTS)
+        .map(|attempt| backoff_delay(base_delay, attempt).as_secs())
+        .sum();
+    let started = Instant::now();
+
+    retry_with_backoff(CONTAINER_START_ATTEMPTS, base_delay, |attempt| {
+        print_wait_progress(
+            &format!(
+                "Aguardando inicialização do contêiner PHP (Tentativa {} de {})",
+                attempt, CONTAINER_START_ATTEMPTS
+            ),
+            started.elapsed(),
+            budget_secs,
+        )?;
+
+        match check_container_is_running(&config.php_container_name) {
+            Ok(true) => {
+                clear_wait_line()?; // Limpa a linha
+                println!("Contêiner PHP ativo e pronto.");
+                Ok(())
+            }
+            Ok(false) => Err(AppError::Docker(format!(
+                "O contêiner PHP '{}' falhou ao iniciar após {} tentativas.",
+                config.php_container_name, attempt
+            ))),
+            Err(e) => Err(AppError::Docker(format!(
+                "Falha ao verificar o status do contêiner: {}",
+                e
+            ))),
+        }
+    })
+    .map_err(|e| {
+        let _ = clear_wait_line();
+        attach_compose_logs(config, &config.php_service, e)
+    })?;
+
+    ensure_expected_services_running(config)?;
+
+    let started_containers = stack_containers(config)
+        .into_iter()
+        .filter(|container| {
+            !was_running.contains(container)
+                && matches!(check_container_is_running(container), Ok(true))
+        })
+        .cloned()
+        .collect();
+    Ok(started_containers)
+}
+
+fn wait_for_stack_ready(config: &AppConfig) -> Result<(), AppError> {
+    wait_for_container_ready(&config.php_container_name, &["true"], config)
+        .map_err(|e| attach_compose_logs(config, &config.php_service, e))?;
+    wait_for_container_ready(&config.db_container_name, DB_READY_PROBE, config)
+        .map_err(|e| attach_compose_logs(config, "mariadb", e))?;
+
+    if let Some(network) = &config.docker_network {
+        connect_stack_to_network(config, network)?;
+    }
+
+    Ok(())
+}
+
+fn is_valid_network_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+fn ensure_docker_network(network: &str) -> Result<(), AppError> {
+    let exists = Command::new("docker")
+        .arg("network")
+        .arg("inspect")
+        .arg(network)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced()
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false);
+    if exists {
+        return Ok(());
+    }
+
+    println!("Rede Docker '{}' não encontrada. Criando...", network);
+    let status = Command::new("docker")
+        .arg("network")
+        .arg("create")
+        .arg(network)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao criar a rede '{}': {}", network, e))
+                .with_source(command_context(e))
+        })?;
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao criar a rede Docker '{}'. Status: {:?}",
+            network, status
+        )));
+    }
+    Ok(())
+}
+
+fn network_containers(network: &str) -> Vec<String> {
+    Command::new("docker")
+        .arg("network")
+        .arg("inspect")
+        .arg("--format")
+        .arg("{{range .Containers}}{{.Name}} {{end}}")
+        .arg(network)
+        .traced()
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Conecta os contêineres da stack à rede extra; a rede do compose continua existindo para o vhost
+fn connect_stack_to_network(config: &AppConfig, network: &str) -> Result<(), AppError> {
+    ensure_docker_network(network)?;
+
+    let connected = network_containers(network);
+    for container in stack_containers(config) {
+        if connected.contains(container)
+            || !matches!(check_container_is_running(container), Ok(true))
+        {
+            continue;
+        }
+
+        println!("Conectando '{}' à rede '{}'...", container, network);
+        let status = Command::new("docker")
+            .arg("network")
+            .arg("connect")
+            .arg(network)
+            .arg(container)
+            .traced()
+            .status()
+            .map_err(|e| {
+                AppError::Docker(format!(
+                    "Falha ao conectar '{}' à rede '{}': {}",
+                    container, network, e
+                ))
+                .with_source(command_context(e))
+            })?;
+        if !status.success() {
+            return Err(AppError::Docker(format!(
+                "Falha ao conectar '{}' à rede Docker '{}'. Status: {:?}",
+                container, network, status
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Fora da rede do compose o alias 'mariadb' não existe; o nome do contêiner resolve em redes customizadas
+fn db_host(config: &AppConfig) -> &str {
+    match &config.docker_network {
+        Some(_) => &config.db_container_name,
+        None => "mariadb",
+    }
+}
+
+fn stack_containers(config: &AppConfig) -> [&String; 4] {
+    [
+        &config.php_container_name,
+        &config.db_container_name,
+        &config.apache_container_name,
+        &config.node_container_name,
+    ]
+}
+
+fn stop_stack_after_setup(config: &AppConfig, started: &[String]) -> Result<(), AppError> {
+    let mut command = if config.stop_after {
+        println!(
+            "{}",
+            paint(
+                Tone::Step,
+                ">> Parando a stack Docker Compose (--stop-after)..."
+            )
+        );
+        let mut command = compose_command(config);
+        if let Some(project_root) = find_project_root() {
+            command.current_dir(project_root);
+        }
+        command.arg("stop");
+        command
+    } else if config.stop_if_started && !started.is_empty() {
+        println!(
+            "{}",
+            paint(
+                Tone::Step,
+                &format!(
+                    ">> Parando os contêineres iniciados por esta execução: {}",
+                    started.join(", ")
+                )
+            )
+        );
+        let mut command = Command::new("docker");
+        command.arg("stop").args(started);
+        command
+    } else {
+        if config.stop_if_started {
+            println!("A stack já estava ativa antes da execução. Nenhum contêiner parado.");
+        }
+        return Ok(());
+    };
+
+    let status = command.traced().status().map_err(|e| {
+        AppError::Docker(format!("Falha ao parar os contêineres: {}", e))
+            .with_source(command_context(e))
+    })?;
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao parar os contêineres. Status: {:?}",
+            status
+        )));
+    }
+    Ok(())
+}
+
+// 'docker compose up -d' pode sair com 0 tendo iniciado só parte dos serviços
+fn ensure_expected_services_running(config: &AppConfig) -> Result<(), AppError> {
+    let mut expected = vec![
+        (config.php_service.as_str(), &config.php_container_name),
+        ("mariadb", &config.db_container_name),
+        ("apache", &config.apache_container_name),
+    ];
+    // Stacks só de backend não têm Node; a ausência vira aviso no npm install
+    if !config.skip_npm && config.require_node {
+        expected.push(("node", &config.node_container_name));
+    }
+
+    let missing: Vec<String> = expected
+        .into_iter()
+        .filter(|(_, container)| !matches!(check_container_is_running(container), Ok(true)))
+        .map(|(service, container)| format!("{} ({})", service, container))
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    Err(AppError::Docker(format!(
+        "O Docker Compose iniciou apenas parte do ambiente. Serviços sem contêiner em execução: {}. Verifique os depends_on e 'docker compose ps -a'.",
+        missing.join(", ")
+    )))
+}
+
+fn attach_compose_logs(config: &AppConfig, service: &str, error: AppError) -> AppError {
+    if !config.dump_compose_logs {
+        return error;
+    }
+
+    append_service_logs(error, service, None)
+}
+
+fn append_service_logs(error: AppError, service: &str, step: Option<&str>) -> AppError {
+    let since = step_started_at(step);
+    match (error, compose_logs_tail(service, since)) {
+        (error, Some(logs)) => error.map_docker_message(|message| {
+            format!(
+                "{}\n{} {}{} ---\n{}",
+                message,
+                COMPOSE_LOGS_HEADER,
+                since.map_or(String::new(), |since| format!("--since {} ", since)),
+                service,
+                logs
+            )
+        }),
+        (error, None) => error,
+    }
+}
+
+// Com --since o trecho fica restrito à etapa que falhou; --tail limita o tamanho mesmo assim
+fn compose_logs_tail(service: &str, since: Option<u64>) -> Option<String> {
+    let mut command = Command::new("docker");
+    if let Some(project_root) = find_project_root() {
+        command.current_dir(project_root);
+    }
+
+    command.args(["compose", "logs", "--no-color"]);
+    if let Some(since) = since {
+        command.arg("--since").arg(since.to_string());
+    }
+    let output = command
+        .args(["--tail", "50", service])
+        .traced()
+        .output()
+        .ok()?;
+
+    let logs = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let logs = logs.trim();
+
+    if logs.is_empty() {
+        None
+    } else {
+        Some(logs.to_string())
+    }
+}
+
+fn container_composer_version(config: &AppConfig) -> Result<(u32, u32, u32), AppError> {
+    let mut args: Vec<&str> = config.composer_bin.split_whitespace().collect();
+    args.push("--version");
+    let output = capture_command_in_container(&config.php_container_name, &args)?;
+
+    output
+        .lines()
+        .find(|line| line.contains("Composer version"))
+        .and_then(|line| line.split("Composer version").nth(1))
+        .and_then(parse_version_triplet)
+        .ok_or_else(|| {
+            AppError::Docker(format!(
+                "Não foi possível identificar a versão do Composer no contêiner '{}': {}",
+                config.php_container_name, output
+            ))
+        })
+}
+
+fn check_composer_version(config: &AppConfig) -> Result<(), AppError> {
+    let (major, minor, patch) = container_composer_version(config)?;
+    let (min_major, min_minor) = MIN_COMPOSER_VERSION;
+
+    if (major, minor) >= (min_major, min_minor) {
+        return Ok(());
+    }
+
+    let current = format!("{}.{}.{}", major, minor, patch);
+    if !config.auto_update_composer {
+        return Err(AppError::Docker(format!(
+            "O Composer do contêiner '{}' está na versão {}, mas a mínima suportada é {}.{}. Execute 'docker exec {} composer self-update' ou use --auto-update-composer.",
+            config.php_container_name, current, min_major, min_minor, config.php_container_name
+        )));
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Composer {} abaixo do mínimo ({}.{}). Executando composer self-update...",
+                current, min_major, min_minor
+            )
+        )
+    );
+    let mut args: Vec<&str> = config.composer_bin.split_whitespace().collect();
+    args.push("self-update");
+    execute_command_in_container(&config.php_container_name, &args)?;
+
+    let (major, minor, patch) = container_composer_version(config)?;
+    if (major, minor) < (min_major, min_minor) {
+        return Err(AppError::Docker(format!(
+            "O Composer continua na versão {}.{}.{} após o self-update. Atualize a imagem do contêiner PHP.",
+            major, minor, patch
+        )));
+    }
+
+    println!("Composer atualizado para {}.{}.{}.", major, minor, patch);
+    Ok(())
+}
+
+fn composer_version_unavailable(output: &str) -> bool {
+    let output = output.to_lowercase();
+    output.contains("could not find package") || output.contains("could not find a version")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Installer {
+    Composer,
+    Laravel,
+}
+
+// O 'laravel new' sempre instala a última versão do laravel/laravel; fora disso volta ao composer
+fn use_laravel_installer(input: &ProjectInput, config: &AppConfig) -> Result<bool, AppError> {
+    if config.installer != Installer::Laravel {
+        return Ok(false);
+    }
+
+    match laravel_installer_unavailable(input, config, true)? {
+        Some(reason) => {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!("AVISO: {} Usando composer create-project.", reason)
+                )
+            );
+            Ok(false)
+        }
+        None => Ok(true),
+    }
+}
+
+// Motivo para não usar o 'laravel new'; sem a stack no ar o plano não tem como procurar o binário
+fn laravel_installer_unavailable(
+    input: &ProjectInput,
+    config: &AppConfig,
+    check_container: bool,
+) -> Result<Option<String>, AppError> {
+    if config.base_package != DEFAULT_BASE_PACKAGE {
+        return Ok(Some(format!(
+            "O instalador 'laravel new' não suporta o pacote base '{}'.",
+            config.base_package
+        )));
+    }
+
+    let latest = available_laravel_versions().0.into_iter().max();
+    if latest.is_some_and(|latest| latest.to_string() != input.laravel_version) {
+        return Ok(Some(format!(
+            "O instalador 'laravel new' só instala a versão mais recente ({}), não a {}.",
+            latest.unwrap_or(DEFAULT_LARAVEL_VERSION),
+            input.laravel_version
+        )));
+    }
+
+    if check_container
+        && capture_command_in_container(
+            &config.php_container_name,
+            &["sh", "-c", "command -v laravel || true"],
+        )?
+        .is_empty()
+    {
+        return Ok(Some(format!(
+            "O instalador 'laravel' não foi encontrado no contêiner '{}' (composer global require laravel/installer).",
+            config.php_container_name
+        )));
+    }
+
+    Ok(None)
+}
+
+fn laravel_new_command(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "cd {} && laravel new '{}' --no-interaction",
+        CONTAINER_WEB_ROOT,
+        app_relative_path(input, config)
+    )
+}
+
+fn run_laravel_new(input: &ProjectInput, config: &AppConfig) -> Result<bool, AppError> {
+    let command = laravel_new_command(input, config);
+    let (status, output) =
+        stream_command_in_container(&config.php_container_name, &["sh", "-c", &command])?;
+
+    if !status.success() {
+        if config.rollback {
+            rollback_partial_project(input, config);
+        }
+        return Err(AppError::Docker(format!(
+            "'laravel new' falhou ao criar o projeto. Últimas linhas:\n{}",
+            output_tail(&output, 20)
+        )));
+    }
+
+    println!(
+        "Projeto Laravel '{}' criado com 'laravel new' em {}",
+        input.project_name, input.project_path
+    );
+    Ok(true)
+}
+
+fn composer_create_project_args(input: &ProjectInput, config: &AppConfig) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    if config.offline {
+        args.extend(["env".to_string(), "COMPOSER_DISABLE_NETWORK=1".to_string()]);
+    }
+    args.extend(config.composer_bin.split_whitespace().map(str::to_string));
+    args.push("create-project".to_string());
+    if config.no_scripts {
+        args.push("--no-scripts".to_string());
+    }
+    args.extend([
+        config.base_package.clone(),
+        app_relative_path(input, config),
+        input.laravel_version.clone(),
+    ]);
+    args
+}
+
+// Retorna false quando a versão pedida não existe e o usuário pode escolher outra
+fn run_composer_create_project(input: &ProjectInput, config: &AppConfig) -> Result<bool, AppError> {
+    let args = composer_create_project_args(input, config);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let (status, output) = run_composer_with_memory_fallback(config, &args, || {
+        rollback_partial_project(input, config)
+    })?;
+
+    if !status.success() {
+        if config.rollback {
+            rollback_partial_project(input, config);
+        }
+        if composer_version_unavailable(&output) {
+            let message = format!(
+                "O Composer não encontrou a versão '{}' de {}.",
+                input.laravel_version, config.base_package
+            );
+            if !config.assume_yes && io::stdin().is_terminal() {
+                eprintln!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!("AVISO: {} Escolha outra versão.", message)
+                    )
+                );
+                return Ok(false);
+            }
+            return Err(AppError::Validation(format!(
+                "{} Use --version-list para ver as versões disponíveis.",
+                message
+            )));
+        }
+        return Err(AppError::Docker(format!(
+            "Composer falhou ao criar o projeto.{}{}",
+            if config.offline {
+                " No modo --offline o pacote e suas dependências precisam estar no cache local do Composer."
+            } else {
+                ""
+            },
+            composer_failure_hint(&output)
+        )));
+    }
+
+    println!(
+        "Projeto Laravel '{}' criado com sucesso em {}",
+        input.project_name, input.project_path
+    );
+    Ok(true)
+}
+
+fn rollback_partial_project(input: &ProjectInput, config: &AppConfig) {
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, input.project_dir);
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Removendo diretório parcial {}...", project_dir)
+        )
+    );
+
+    match execute_command_in_container(&config.php_container_name, &["rm", "-rf", &project_dir]) {
+        Ok(()) => println!("Diretório parcial removido. Você pode executar novamente."),
+        Err(e) => eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: Não foi possível remover '{}': {}. Remova manualmente antes de tentar novamente.",
+                    project_dir, e
+                )
+            )
+        ),
+    }
+}
+
+fn restart_apache_container(config: &AppConfig) -> Result<(), AppError> {
+    println!("---");
+    println!("Reiniciando o contêiner Apache para carregar o novo Vhost...");
+
+    let mut command = Command::new("docker");
+    if let Some(project_root) = find_project_root() {
+        command.current_dir(project_root);
+    }
+
+    let status = command
+        .args(APACHE_RESTART_ARGS)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker compose restart': {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao reiniciar o contêiner Apache. Verifique se o serviço 'apache' está correto no docker-compose.yml. Status: {:?}",
+            status
+        )));
+    }
+
+    // Um vhost inválido derruba o Apache alguns instantes após o restart, mesmo com o comando
+    // bem-sucedido: só conta como ok depois de APACHE_SETTLE_SECS verificações seguidas de pé
+    let check_apache = || match check_container_is_running(&config.apache_container_name) {
+        Ok(true) => match inspect_container_health(&config.apache_container_name)?.as_str() {
+            "healthy" | "none" => Ok(()),
+            health => Err(AppError::Docker(format!(
+                "O contêiner Apache '{}' não ficou saudável após o restart (estado: {}).",
+                config.apache_container_name, health
+            ))),
+        },
+        Ok(false) => Err(AppError::Docker(format!(
+            "O contêiner Apache '{}' não voltou a executar após o restart. Verifique a sintaxe do vhost.",
+            config.apache_container_name
+        ))),
+        Err(e) => Err(AppError::Docker(format!(
+            "Falha ao verificar o status do contêiner: {}",
+            e
+        ))),
+    };
+
+    // O intervalo é fixo: sem ele as checagens rodariam antes de um vhost inválido derrubar o Apache
+    let required = config.apache_settle_secs.max(1);
+    let mut stable = 0;
+    let mut last_error = None;
+    for _ in 0..u64::from(APACHE_RESTART_ATTEMPTS) + required {
+        thread::sleep(APACHE_POLL_INTERVAL);
+        match check_apache() {
+            Ok(()) => stable += 1,
+            Err(e) => {
+                stable = 0;
+                last_error = Some(e);
+            }
+        }
+        if stable >= required {
+            break;
+        }
+    }
+    if stable < required {
+        let error = last_error.unwrap_or_else(|| {
+            AppError::Docker(format!(
+                "O contêiner Apache '{}' não se manteve estável após o restart.",
+                config.apache_container_name
+            ))
+        });
+        return Err(append_service_logs(error, "apache", None));
+    }
+
+    println!("Contêiner Apache reiniciado com sucesso.");
+    Ok(())
+}
+
+fn privileged_command(config: &AppConfig) -> Command {
+    if let Some(helper) = &config.hosts_helper {
+        let mut parts = helper.split_whitespace();
+        let mut command = Command::new(parts.next().unwrap_or("sudo"));
+        command.args(parts);
+        return command;
+    }
+
+    let mut command = Command::new("sudo");
+    if env::var_os("SUDO_ASKPASS").is_some() {
+        command.arg("-A");
+    }
+    command
+}
+
+fn running_as_root() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        .unwrap_or(false)
+}
+
+fn writes_hosts_directly(config: &AppConfig) -> bool {
+    config.hosts_helper.is_none() && running_as_root()
+}
+
+fn privileged_spawn_error(err: io::Error, config: &AppConfig) -> AppError {
+    if err.kind() != io::ErrorKind::NotFound {
+        return AppError::Io(err);
+    }
+
+    let program = config
+        .hosts_helper
+        .as_deref()
+        .and_then(|helper| helper.split_whitespace().next())
+        .unwrap_or("sudo")
+        .to_string();
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            &format!(
+                "AVISO: '{}' não está instalado. Use --no-hosts para pular o /etc/hosts, --hosts-helper para indicar outro programa ou execute o lara como root.",
+                program
+            )
+        )
+    );
+    AppError::MissingBinary(program)
+}
+
+fn hosts_addresses(config: &AppConfig) -> &'static [&'static str] {
+    if config.hosts_ipv6 {
+        &LOOPBACK_ADDRESSES
+    } else {
+        &LOOPBACK_ADDRESSES[..1]
+    }
+}
+
+// Devolve os endereços gravados nesta chamada, para o rollback desfazer só essas linhas
+fn update_etc_hosts(
+    input: &ProjectInput,
+    config: &AppConfig,
+) -> Result<Vec<&'static str>, AppError> {
+    let addresses = hosts_addresses(config);
+    let host_entry = format!("127.0.0.1 {}", input.project_host);
+    let hosts_file_path = "/etc/hosts";
+
+    println!("---");
+    let direct = writes_hosts_directly(config);
+    if !direct {
+        println!(
+            "O próximo passo exige permissão de administrador (sudo) para atualizar o /etc/hosts."
+        );
+    }
+
+    // Cada endereço é conferido por palavra inteira: 'app.test' não conta como 'minha-app.test'
+    let missing: Vec<&str> = match fs::read_to_string(hosts_file_path) {
+        Ok(content) => addresses
+            .iter()
+            .copied()
+            .filter(|address| !hosts_content_has_address(&content, address, &input.project_host))
+            .collect(),
+        Err(e) => {
+            println!(
+                "Não foi possível ler /etc/hosts para verificação: {}. Tentando escrever com sudo.",
+                e
+            );
+            addresses.to_vec()
+        }
+    };
+    if missing.is_empty() {
+        println!(
+            "✅ Entrada de host '{}' já existe em /etc/hosts.",
+            input.project_host
+        );
+        return Ok(Vec::new());
+    }
+
+    let hosts_lines: Vec<String> = missing
+        .iter()
+        .map(|address| {
+            let entry = format!("{} {}", address, input.project_host);
+            match &input.label {
+                Some(label) => format!("{} {}{}", entry, LABEL_MARKER, label),
+                None => entry,
+            }
+        })
+        .collect();
+
+    if direct {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(hosts_file_path)
+            .and_then(|mut file| {
+                hosts_lines
+                    .iter()
+                    .try_for_each(|line| writeln!(file, "{}", line))
+            })
+            .map_err(|e| {
+                if hosts_file_is_immutable(hosts_file_path) {
+                    hosts_immutable_error(&host_entry, hosts_file_path)
+                } else {
+                    AppError::Io(e)
+                }
+            })?;
+    } else {
+        let command_string = hosts_lines
+            .iter()
+            .map(|line| format!("echo '{}' >> {}", line, hosts_file_path))
+            .collect::<Vec<String>>()
+            .join(" && ");
+
+        let status = privileged_command(config)
+            .arg("sh")
+            .arg("-c")
+            .arg(command_string)
+            .status()
+            .map_err(|e| privileged_spawn_error(e, config))?;
+
+        if !status.success() {
+            if hosts_file_is_immutable(hosts_file_path) {
+                return Err(hosts_immutable_error(&host_entry, hosts_file_path));
+            }
+            return Err(AppError::Validation(format!(
+                "Falha ao executar '{}'. Verifique se você digitou a senha corretamente. Status: {:?}",
+                config.hosts_helper.as_deref().unwrap_or("sudo"),
+                status
+            )));
+        }
+    }
+
+    let written = fs::read_to_string(hosts_file_path).map_err(|e| {
+        AppError::Validation(format!(
+            "Não foi possível reler {} para confirmar a entrada '{}': {}",
+            hosts_file_path, host_entry, e
+        ))
+        .with_source(e)
+    })?;
+
+    // A escrita "funcionou" mas não persistiu: arquivo imutável ou gerenciado por outra ferramenta
+    if !missing
+        .iter()
+        .all(|address| hosts_content_has_address(&written, address, &input.project_host))
+    {
+        if hosts_file_is_immutable(hosts_file_path) {
+            return Err(hosts_immutable_error(&host_entry, hosts_file_path));
+        }
+        return Err(AppError::Validation(format!(
+            "A entrada '{}' não foi encontrada em {} após a escrita, embora o comando tenha terminado com sucesso. O arquivo parece gerenciado por outra ferramenta que reverte alterações (NetworkManager, WSL generateHosts, mount somente leitura de contêiner etc.). Configure a resolução de *.test por DNS (ex.: dnsmasq) ou pela ferramenta que gerencia o arquivo e rode novamente com --no-hosts.",
+            host_entry, hosts_file_path
+        )));
+    }
+
+    println!("Host '{}' adicionado a /etc/hosts.", input.project_host);
+    Ok(missing)
+}
+
+fn hosts_file_is_immutable(path: &str) -> bool {
+    Command::new("lsattr")
+        .arg("-d")
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            output.status.success()
+                && String::from_utf8_lossy(&output.stdout)
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|flags| flags.contains('i'))
+        })
+}
+
+fn hosts_immutable_error(host_entry: &str, path: &str) -> AppError {
+    AppError::Validation(format!(
+        "{} está marcado como imutável (chattr +i), então a entrada '{}' não pode ser gravada nem com sudo. Remova a flag com 'sudo chattr -i {}' se o arquivo for seu, ou configure a resolução de *.test por DNS (ex.: dnsmasq) e rode novamente com --no-hosts.",
+        path, host_entry, path
+    ))
+}
+
+fn hosts_content_has_entry(content: &str, project_host: &str) -> bool {
+    hosts_content_has_address(content, "127.0.0.1", project_host)
+}
+
+fn hosts_content_has_address(content: &str, address: &str, project_host: &str) -> bool {
+    content.lines().any(|line| {
+        let mut fields = line.split_whitespace();
+        fields.next() == Some(address) && fields.any(|host| host == project_host)
+    })
+}
+
+fn verify_final_state(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!("{}", paint(Tone::Step, ">> Verificando o estado final..."));
+
+    let mut checks: Vec<(String, bool)> = Vec::new();
+
+    let vhost_ok = project_vhost_content(input, config)
+        .is_some_and(|content| content.contains(&format!("ServerName {}", input.project_host)));
+    checks.push((format!("Vhost {}", vhost_location(input, config)), vhost_ok));
+
+    if !config.no_hosts {
+        let hosts_ok = fs::read_to_string("/etc/hosts")
+            .is_ok_and(|content| hosts_content_has_entry(&content, &input.project_host));
+        checks.push((
+            format!("Entrada '{}' em /etc/hosts", input.project_host),
+            hosts_ok,
+        ));
+    }
+
+    for container in [&config.php_container_name, &config.db_container_name] {
+        checks.push((
+            format!("Contêiner '{}' em execução", container),
+            matches!(check_container_is_running(container), Ok(true)),
+        ));
+    }
+
+    let app_dir = container_app_dir(input, config);
+    let dir_ok = capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!("[ -d '{}' ] && echo ok || true", app_dir),
+        ],
+    )
+    .is_ok_and(|output| output == "ok");
+    checks.push((format!("Diretório do projeto {}", app_dir), dir_ok));
+
+    let expected_url = format!("APP_URL={}", app_url(input, config));
+    let env_ok = capture_command_in_container(
+        &config.php_container_name,
+        &["cat", &format!("{}/.env", app_dir)],
+    )
+    .is_ok_and(|content| content.lines().any(|line| line.trim() == expected_url));
+    checks.push((format!(".env contém {}", expected_url), env_ok));
+
+    checks.push((
+        ".env contém APP_KEY válida (base64, 32 bytes)".to_string(),
+        ensure_valid_app_key(input, config),
+    ));
+
+    for (description, ok) in &checks {
+        println!("{} {}", if *ok { "✅" } else { "❌" }, description);
+    }
+
+    let failed = checks.iter().filter(|(_, ok)| !ok).count();
+    if failed > 0 {
+        return Err(AppError::Validation(format!(
+            "Verificação final: {} de {} itens não conferem com o estado esperado.",
+            failed,
+            checks.len()
+        )));
+    }
+    Ok(())
+}
+
+fn read_app_key(input: &ProjectInput, config: &AppConfig) -> Option<String> {
+    capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "grep '^APP_KEY=' '{}/.env' 2>/dev/null || true",
+                container_app_dir(input, config)
+            ),
+        ],
+    )
+    .ok()
+    .and_then(|line| {
+        line.lines()
+            .last()
+            .and_then(|line| line.strip_prefix("APP_KEY="))
+            .and_then(parse_env_value)
+    })
+}
+
+// O Laravel espera "base64:" seguido de 32 bytes codificados (44 caracteres)
+fn is_valid_app_key(key: &str) -> bool {
+    key.strip_prefix("base64:").is_some_and(|encoded| {
+        encoded.len() == 44
+            && encoded.ends_with('=')
+            && encoded
+                .trim_end_matches('=')
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+    })
+}
+
+fn ensure_valid_app_key(input: &ProjectInput, config: &AppConfig) -> bool {
+    if read_app_key(input, config).is_some_and(|key| is_valid_app_key(&key)) {
+        return true;
+    }
+
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            "AVISO: APP_KEY ausente ou inválida no .env. Executando artisan key:generate..."
+        )
+    );
+    let generated = execute_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd {} && {} artisan key:generate --force",
+                container_app_dir(input, config),
+                config.php_bin
+            ),
+        ],
+    );
+    generated.is_ok() && read_app_key(input, config).is_some_and(|key| is_valid_app_key(&key))
+}
+
+fn fix_project_ownership(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let owner = host_user_ids()?;
+    let project_dir = format!("{}/{}", CONTAINER_WEB_ROOT, input.project_dir);
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Ajustando o dono de {} para {}...", project_dir, owner)
+        )
+    );
+
+    let status = Command::new("docker")
+        .args(["exec", "-u", "0"])
+        .arg(&config.php_container_name)
+        .args(ownership_command(input, &owner))
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar 'docker exec chown': {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao ajustar o dono de {} no contêiner '{}'. Status: {:?}",
+            project_dir, config.php_container_name, status
+        )));
+    }
+
+    println!("Arquivos do projeto agora pertencem a uid:gid {}.", owner);
+    Ok(())
+}
+
+fn ownership_command(input: &ProjectInput, owner: &str) -> Vec<String> {
+    vec![
+        "chown".to_string(),
+        "-R".to_string(),
+        owner.to_string(),
+        format!("{}/{}", CONTAINER_WEB_ROOT, input.project_dir),
+    ]
+}
+
+trait TracedCommand {
+    fn traced(&mut self) -> &mut Self;
+}
+
+impl TracedCommand for Command {
+    fn traced(&mut self) -> &mut Self {
+        let line: Vec<String> = std::iter::once(self.get_program())
+            .chain(self.get_args())
+            .map(|part| shell_quote(&redact_secrets(&part.to_string_lossy())))
+            .collect();
+        let line = redact(&line.join(" "));
+        if VERBOSE_DOCKER.get().copied().unwrap_or(false) {
+            eprintln!("{}", paint(Tone::Step, &format!("$ {}", line)));
+        }
+        LAST_COMMAND.with(|last| *last.borrow_mut() = line);
+        self
+    }
+}
+
+fn register_secrets(config: &AppConfig) {
+    let secrets = std::iter::once(&config.db_root_password)
+        .chain(config.db_app_password.as_ref())
+        // A senha padrão é pública; mascará-la só apagaria a palavra 'password' da saída
+        .filter(|secret| {
+            secret.len() >= MIN_REDACTED_SECRET_LEN && secret.as_str() != DEFAULT_DB_ROOT_PASSWORD
+        })
+        .cloned()
+        .collect();
+    let _ = SECRETS.set(secrets);
+}
+
+// Segredos muito curtos não são mascarados para não corromper o restante da saída.
+fn redact(text: &str) -> String {
+    let mut redacted = text.to_string();
+    for secret in SECRETS.get().into_iter().flatten() {
+        redacted = mask_whole_value(&mask_whole_value(&redacted, &sed_escape(secret)), secret);
+    }
+    redacted
+}
+
+// Só o valor inteiro é mascarado (=segredo, -psegredo, 'segredo'): 'DB_PASSWORD' nunca vira 'DB_****'
+fn mask_whole_value(text: &str, value: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut masked = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, _) in text.match_indices(value) {
+        let end = start + value.len();
+        let starts_clean = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !is_word(c) || text[..start].ends_with("-p"));
+        let ends_clean = text[end..].chars().next().is_none_or(|c| !is_word(c));
+        if start >= copied && starts_clean && ends_clean {
+            masked.push_str(&text[copied..start]);
+            masked.push_str("****");
+            copied = end;
+        }
+    }
+    masked.push_str(&text[copied..]);
+    masked
+}
+
+fn redact_secrets(arg: &str) -> String {
+    if arg.len() > 2 && arg.starts_with("-p") && !arg.starts_with("--") {
+        return "-p****".to_string();
+    }
+
+    let mut redacted = arg.to_string();
+    for marker in ["PASSWORD=", "IDENTIFIED BY '"] {
+        let mut search_from = 0;
+        while let Some(pos) = redacted[search_from..].find(marker) {
+            let value_start = search_from + pos + marker.len();
+            let value_len = redacted[value_start..]
+                .find(|c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '/'))
+                .unwrap_or(redacted.len() - value_start);
+            if value_len > 0 {
+                redacted.replace_range(value_start..value_start + value_len, "****");
+            }
+            search_from = value_start;
+        }
+    }
+    redacted
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg.chars().all(|c| {
+            c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | ',')
+        })
+    {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn docker_exec_user_flags() -> &'static [String] {
+    EXEC_USER.get().map(Vec::as_slice).unwrap_or(&[])
+}
+
+fn docker_exec_tty_flags() -> &'static [&'static str] {
+    let mode = DOCKER_EXEC_MODE.get_or_init(|| {
+        let mode = env::var("DOCKER_EXEC_MODE")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        match mode.as_str() {
+            "" | "auto" => "auto".to_string(),
+            "interactive" | "detached" => mode,
+            other => {
+                println!(
+                    "{}",
+                    paint(
+                        Tone::Warning,
+                        &format!(
+                            "DOCKER_EXEC_MODE ('{}') inválido. Use interactive, detached ou auto. Usando default: auto",
+                            other
+                        )
+                    )
+                );
+                "auto".to_string()
+            }
+        }
+    });
+
+    match mode.as_str() {
+        "interactive" => &["-it"],
+        "detached" => &[],
+        _ if io::stdin().is_terminal() && io::stdout().is_terminal() => &["-it"],
+        _ if io::stdin().is_terminal() => &["-i"],
+        _ => &[],
+    }
+}
+
+fn execute_command_in_container(container_name: &str, args: &[&str]) -> Result<(), AppError> {
+    let status = Command::new("docker")
+        .arg("exec")
+        .args(docker_exec_user_flags())
+        .args(docker_exec_tty_flags())
+        .arg(container_name)
+        .args(args)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!(
+                "Falha ao executar comando no contênier '{}':{}",
+                container_name, e
+            ))
+            .with_source(command_context(e))
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(AppError::Docker(format!(
+            "Comando falho dentro do contêiner '{}'. Status: {:?}",
+            container_name, status,
+        )))
+    }
+}
+
+fn stream_command_in_container(
+    container_name: &str,
+    args: &[&str],
+) -> Result<(ExitStatus, String), AppError> {
+    stream_command(
+        Command::new("docker")
+            .arg("exec")
+            .args(docker_exec_user_flags())
+            .arg(container_name)
+            .args(args),
+        &format!("comando no contêiner '{}'", container_name),
+    )
+}
+
+// Repassa stdout/stderr ao terminal enquanto guarda a saída para mensagens de erro
+fn stream_command(
+    command: &mut Command,
+    description: &str,
+) -> Result<(ExitStatus, String), AppError> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .traced()
+        .spawn()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar {}: {}", description, e))
+                .with_source(command_context(e))
+        })?;
+
+    let collected = std::sync::Arc::new(Mutex::new(String::new()));
+
+    let stderr_reader = child.stderr.take().map(|stderr| {
+        let collected = std::sync::Arc::clone(&collected);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                eprintln!("{}", line);
+                if let Ok(mut collected) = collected.lock() {
+                    collected.push_str(&line);
+                    collected.push('\n');
+                }
+            }
+        })
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            println!("{}", line);
+            if let Ok(mut collected) = collected.lock() {
+                collected.push_str(&line);
+                collected.push('\n');
+            }
+        }
+    }
+
+    if let Some(reader) = stderr_reader {
+        let _ = reader.join();
+    }
+
+    let status = child.wait()?;
+    let output = collected
+        .lock()
+        .map(|collected| collected.clone())
+        .unwrap_or_default();
+
+    Ok((status, output))
+}
+
+fn output_tail(output: &str, lines: usize) -> String {
+    let all_lines: Vec<&str> = output.lines().collect();
+    let start = all_lines.len().saturating_sub(lines);
+    all_lines[start..].join("\n")
+}
+
+fn capture_command_in_container(container_name: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = Command::new("docker")
+        .arg("exec")
+        .args(docker_exec_user_flags())
+        .arg(container_name)
+        .args(args)
+        .traced()
+        .output()
+        .map_err(|e| {
+            AppError::Docker(format!(
+                "Falha ao executar comando no contêiner '{}': {}",
+                container_name, e
+            ))
+            .with_source(command_context(e))
+        })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(AppError::Docker(format!(
+            "Comando falho dentro do contêiner '{}'. Status: {:?}. Saída: {}",
+            container_name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn parse_version_triplet(input: &str) -> Option<(u32, u32, u32)> {
+    let cleaned = input
+        .trim()
+        .trim_start_matches(|c: char| !c.is_ascii_digit());
+    let mut parts = cleaned
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<u32>());
+
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    let patch = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+fn extract_engines_node(package_json: &str) -> Option<String> {
+    let engines_start = package_json.find("\"engines\"")?;
+    let engines_block = &package_json[engines_start..];
+    let block_end = engines_block.find('}')?;
+    let engines_block = &engines_block[..block_end];
+
+    let node_start = engines_block.find("\"node\"")?;
+    let after_key = &engines_block[node_start + "\"node\"".len()..];
+    let value_start = after_key.find('"')? + 1;
+    let value_end = after_key[value_start..].find('"')? + value_start;
+    Some(after_key[value_start..value_end].to_string())
+}
+
+fn node_version_satisfies(version: (u32, u32, u32), constraint: &str) -> bool {
+    constraint
+        .split("||")
+        .filter_map(|alternative| {
+            let minimum = alternative.split_whitespace().next()?;
+            parse_version_triplet(minimum)
+        })
+        .any(|minimum| version >= minimum)
+}
+
+fn check_node_version(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!("{}", paint(Tone::Step, ">> Verificando versão do Node..."));
+
+    let package_json = match capture_command_in_container(
+        &config.node_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd {} && cat package.json",
+                container_app_dir(input, config)
+            ),
+        ],
+    ) {
+        Ok(content) => content,
+        Err(_) => {
+            println!("package.json não encontrado. Verificação da versão do Node ignorada.");
+            return Ok(());
+        }
+    };
+
+    let constraint = match extract_engines_node(&package_json) {
+        Some(constraint) => constraint,
+        None => {
+            println!("package.json não define 'engines.node'. Verificação ignorada.");
+            return Ok(());
+        }
+    };
+
+    let node_version_str =
+        capture_command_in_container(&config.node_container_name, &["node", "-v"])?;
+
+    match parse_version_triplet(&node_version_str) {
+        Some(version) if node_version_satisfies(version, &constraint) => {
+            println!(
+                "Node {} atende ao requisito '{}' do package.json.",
+                node_version_str, constraint
+            );
+        }
+        Some(_) => {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Node {} no contêiner '{}' não atende ao requisito '{}' do package.json. Ajuste NODE_VERSION no .env e reconstrua o contêiner.",
+                        node_version_str, config.node_container_name, constraint
+                    )
+                )
+            );
+        }
+        None => {
+            eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Não foi possível interpretar a versão do Node ('{}').",
+                        node_version_str
+                    )
+                )
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn read_project_env(input: &ProjectInput, config: &AppConfig) -> String {
+    capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cat '{}/.env' 2>/dev/null || true",
+                container_app_dir(input, config)
+            ),
+        ],
+    )
+    .unwrap_or_default()
+}
+
+fn redact_env_line(line: &str) -> String {
+    match line.split_once('=') {
+        Some((key, value))
+            if !value.is_empty()
+                && ["PASSWORD", "KEY", "SECRET", "TOKEN"]
+                    .iter()
+                    .any(|marker| key.to_uppercase().contains(marker)) =>
+        {
+            format!("{}=****", key)
+        }
+        _ => redact(line),
+    }
+}
+
+// Diff por LCS de linhas; o .env tem poucas dezenas de linhas, então O(n*m) basta
+fn print_env_diff(before: &str, after: &str) {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("-{}", redact_env_line(old[i])));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", redact_env_line(new[j])));
+            j += 1;
+        }
+    }
+
+    println!(
+        "{}",
+        paint(Tone::Step, ">> Alterações no .env (--dump-env-diff):")
+    );
+    if lines.is_empty() {
+        println!("Nenhuma linha do .env foi alterada.");
+        return;
+    }
+    println!("--- .env (antes)\n+++ .env (depois)");
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+fn node_container_missing(config: &AppConfig) -> Result<bool, AppError> {
+    if matches!(
+        check_container_is_running(&config.node_container_name),
+        Ok(true)
+    ) {
+        return Ok(false);
+    }
+    if config.require_node {
+        return Err(AppError::Docker(format!(
+            "O contêiner do Node ({}) não está rodando e --require-node foi informado. Suba o serviço node ou remova a opção.",
+            config.node_container_name
+        )));
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Warning,
+            &format!(
+                "AVISO: contêiner do Node ({}) não encontrado; npm install e o ajuste do vite.config.js serão ignorados. Use --require-node para tratar isso como erro.",
+                config.node_container_name
+            )
+        )
+    );
+    Ok(true)
+}
+
+fn print_offline_stub(step: &str) {
+    println!(
+        "{}",
+        paint(Tone::Warning, &format!(">> {}", offline_stub(step)))
+    );
+}
+
+fn offline_stub(step: &str) -> String {
+    format!(
+        "[offline] {} simulado: etapa depende da rede e não foi executada.",
+        step
+    )
+}
+
+fn run_dependencies_in_parallel(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Executando composer update e npm install em paralelo (--parallel)..."
+        )
+    );
+
+    let (composer, npm) = thread::scope(|scope| {
+        let composer =
+            scope.spawn(|| timed_step("composer", || run_composer_update(input, config)));
+        let npm = scope.spawn(|| timed_step("npm", || run_npm_install(input, config)));
+        (
+            composer.join().unwrap_or_else(|_| {
+                Err(AppError::Docker(
+                    "a thread do composer update foi abortada.".to_string(),
+                ))
+            }),
+            npm.join().unwrap_or_else(|_| {
+                Err(AppError::Docker(
+                    "a thread do npm install foi abortada.".to_string(),
+                ))
+            }),
+        )
+    });
+
+    let mut failures: Vec<(&str, AppError)> = [
+        (config.php_container_name.as_str(), composer),
+        (config.node_container_name.as_str(), npm),
+    ]
+    .into_iter()
+    .filter_map(|(container, result)| result.err().map(|e| (container, e)))
+    .collect();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    if failures.len() == 1 {
+        let (container, e) = failures.remove(0);
+        return Err(
+            e.map_docker_message(|message| format!("Contêiner '{}': {}", container, message))
+        );
+    }
+
+    Err(AppError::Docker(format!(
+        "Falhas na execução paralela:\n{}",
+        failures
+            .iter()
+            .map(|(container, e)| format!("- Contêiner '{}': {}", container, e))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )))
+}
+
+fn database_name(input: &ProjectInput, config: &AppConfig) -> String {
+    config
+        .shared_db
+        .clone()
+        .unwrap_or_else(|| input.project_name.clone())
+}
+
+fn test_database_name(input: &ProjectInput, config: &AppConfig) -> String {
+    format!("{}_testing", database_name(input, config))
+}
+
+fn project_databases(input: &ProjectInput, config: &AppConfig) -> Vec<String> {
+    let mut databases = vec![database_name(input, config)];
+    if config.with_test_db {
+        databases.push(test_database_name(input, config));
+    }
+    databases
+}
+
+fn db_username(input: &ProjectInput, config: &AppConfig) -> String {
+    if !config.create_db_user {
+        return "root".to_string();
+    }
+
+    config.db_app_user.clone().unwrap_or_else(|| {
+        let mut user = table_prefix(&input.project_name);
+        user.pop();
+        user.truncate(32);
+        user
+    })
+}
+
+fn db_password(config: &AppConfig) -> &str {
+    match &config.db_app_password {
+        Some(password) if config.create_db_user => password,
+        _ => &config.db_root_password,
+    }
+}
+
+fn provision_db_user(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let databases = project_databases(input, config);
+    let username = db_username(input, config);
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Criando usuário '{}' com acesso a: {}...",
+                username,
+                databases.join(", ")
+            )
+        )
+    );
+
+    run_db_root_sql(config, &db_user_sql(input, config))
+}
+
+fn db_user_sql(input: &ProjectInput, config: &AppConfig) -> String {
+    let username = db_username(input, config);
+    let mut sql = format!(
+        "CREATE USER IF NOT EXISTS '{user}'@'%' IDENTIFIED BY '{password}'; ",
+        user = username,
+        password = db_password(config)
+    );
+    for database in project_databases(input, config) {
+        sql.push_str(&format!(
+            "CREATE DATABASE IF NOT EXISTS `{db}`; GRANT ALL PRIVILEGES ON `{db}`.* TO '{user}'@'%'; ",
+            db = database,
+            user = username
+        ));
+    }
+    sql.push_str("FLUSH PRIVILEGES;");
+    sql
+}
+
+fn create_test_database(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let database = test_database_name(input, config);
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Criando banco de testes '{}'...", database)
+        )
+    );
+
+    run_db_root_sql(config, &test_database_sql(input, config))
+}
+
+fn test_database_sql(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "CREATE DATABASE IF NOT EXISTS `{}`;",
+        test_database_name(input, config)
+    )
+}
+
+fn run_db_root_sql(config: &AppConfig, sql: &str) -> Result<(), AppError> {
+    let args = db_root_sql_args(config, sql);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    execute_command_in_container(&config.db_container_name, &args)
+}
+
+fn db_root_sql_args(config: &AppConfig, sql: &str) -> Vec<String> {
+    vec![
+        "mariadb".to_string(),
+        "-uroot".to_string(),
+        format!("-p{}", config.db_root_password),
+        "-e".to_string(),
+        sql.to_string(),
+    ]
+}
+
+fn table_prefix(project_name: &str) -> String {
+    let prefix: String = project_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_", prefix)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EnvStrategy {
+    Sed,
+    Rewrite,
+}
+
+// O .env.example do Laravel 10 traz as chaves DB_* ativas com mysql; a partir do 11 vem sqlite
+// com as demais chaves comentadas.
+fn env_db_defaults(laravel_version: &str) -> (&'static str, &'static str, &'static str) {
+    match laravel_version.parse::<u8>() {
+        Ok(major) if major <= 10 => ("DB_CONNECTION=mysql", "", "mysql"),
+        _ => ("DB_CONNECTION=sqlite", "# ", "mariadb"),
+    }
+}
+
+fn env_replacements(input: &ProjectInput, config: &AppConfig) -> Vec<(String, String)> {
+    let (connection, prefix, driver) = env_db_defaults(&input.laravel_version);
+
+    let mut replacements = vec![
+        (
+            "APP_URL=http://localhost".to_string(),
+            env_line("APP_URL", &app_url(input, config)),
+        ),
+        (connection.to_string(), env_line("DB_CONNECTION", driver)),
+        (
+            format!("{}DB_PORT=3306", prefix),
+            // DB_HOST é o nome do serviço na rede do compose: vale a porta interna, não a publicada
+            env_line("DB_PORT", &config.db_container_port.to_string()),
+        ),
+        (
+            format!("{}DB_DATABASE=laravel", prefix),
+            env_line("DB_DATABASE", &database_name(input, config)),
+        ),
+        (
+            format!("{}DB_HOST=127.0.0.1", prefix),
+            env_line("DB_HOST", db_host(config)),
+        ),
+        (
+            format!("{}DB_USERNAME=root", prefix),
+            env_line("DB_USERNAME", &db_username(input, config)),
+        ),
+        (
+            format!("{}DB_PASSWORD=", prefix),
+            env_line("DB_PASSWORD", db_password(config)),
+        ),
+    ];
+
+    if let Some(app_env) = &config.app_env {
+        replacements.push(("APP_ENV=local".to_string(), env_line("APP_ENV", app_env)));
+    }
+
+    if let Some(app_debug) = config.app_debug {
+        replacements.push((
+            "APP_DEBUG=true".to_string(),
+            format!("APP_DEBUG={}", app_debug),
+        ));
+    }
+
+    replacements
+}
+
+fn env_appended_lines(input: &ProjectInput, config: &AppConfig) -> Vec<String> {
+    let mut lines = Vec::new();
+    if config.behind_proxy {
+        lines.push(env_line("TRUSTED_PROXIES", &config.trusted_proxies));
+    }
+    if config.shared_db.is_some() {
+        lines.push(env_line(
+            "DB_TABLE_PREFIX",
+            &table_prefix(&input.project_name),
+        ));
+    }
+    lines
+}
+
+fn sed_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '/' | '&' | '.' | '*' | '[' | ']' | '^' | '$') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Aspas seguem o dotenv do Laravel: só valores com espaço, '#', aspas, '$', barra invertida ou quebra de linha precisam delas
+fn quote_env_value(value: &str) -> String {
+    if !value
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '\\' | '$'))
+    {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn env_line(key: &str, value: &str) -> String {
+    format!("{}={}", key, quote_env_value(value))
+}
+
+// Separa 'CHAVE=valor' (com 'export' opcional); comentários e linhas sem chave válida ficam de fora
+fn env_assignment(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, raw_value) = line.split_once('=')?;
+    let key = key.trim_end();
+    let mut chars = key.chars();
+    let valid_key = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    valid_key.then_some((key, raw_value))
+}
+
+// Valor de acordo com o dotenv: aspas simples são literais, aspas duplas aceitam escapes e
+// '#' só inicia comentário fora das aspas quando vem depois de um espaço. None se a aspa não fechar.
+fn parse_env_value(raw_value: &str) -> Option<String> {
+    let raw_value = raw_value.trim_start();
+    let mut chars = raw_value.chars();
+    match chars.next() {
+        Some('\'') => chars
+            .as_str()
+            .split_once('\'')
+            .map(|(value, _)| value.to_string()),
+        Some('"') => {
+            let mut value = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => return Some(value),
+                    '\\' => match chars.next()? {
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        escaped @ ('"' | '\\' | '$') => value.push(escaped),
+                        other => {
+                            value.push('\\');
+                            value.push(other);
+                        }
+                    },
+                    _ => value.push(c),
+                }
+            }
+            None
+        }
+        _ => {
+            let end = raw_value
+                .char_indices()
+                .find(|&(index, c)| {
+                    c == '#'
+                        && raw_value[..index]
+                            .chars()
+                            .next_back()
+                            .is_some_and(char::is_whitespace)
+                })
+                .map(|(index, _)| index)
+                .unwrap_or(raw_value.len());
+            Some(raw_value[..end].trim_end().to_string())
+        }
+    }
+}
+
+// Junta as linhas físicas de um valor entre aspas que continua nas linhas seguintes
+fn env_logical_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut pending: Option<String> = None;
+    for line in content.lines() {
+        let current = match pending.take() {
+            Some(open) => format!("{}\n{}", open, line),
+            None => line.to_string(),
+        };
+        let unterminated = env_assignment(&current)
+            .is_some_and(|(_, raw_value)| parse_env_value(raw_value).is_none());
+        if unterminated {
+            pending = Some(current);
+        } else {
+            lines.push(current);
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+fn env_value(content: &str, key: &str) -> Option<String> {
+    env_logical_lines(content).iter().rev().find_map(|line| {
+        env_assignment(line)
+            .filter(|(name, _)| *name == key)
+            .and_then(|(_, raw_value)| parse_env_value(raw_value))
+    })
+}
+
+// Troca 'de' por 'para' comparando chave e valor já interpretados, não o texto cru;
+// 'de' com prefixo '# ' só casa com a linha comentada do .env padrão do Laravel
+fn rewrite_env_content(
+    content: &str,
+    replacements: &[(String, String)],
+    appended: &[String],
+) -> String {
+    let mut lines = env_logical_lines(content);
+
+    for (from, to) in replacements {
+        let (from_commented, from_body) = match from.strip_prefix("# ") {
+            Some(body) => (true, body),
+            None => (false, from.as_str()),
+        };
+        let Some((from_key, from_raw)) = env_assignment(from_body) else {
+            continue;
+        };
+        let from_value = parse_env_value(from_raw);
+
+        for line in lines.iter_mut() {
+            let (commented, body) = match line.strip_prefix('#') {
+                Some(body) => (true, body.trim_start()),
+                None => (false, line.as_str()),
+            };
+            if from_commented && !commented {
+                continue;
+            }
+            let matches = env_assignment(body).is_some_and(|(key, raw_value)| {
+                key == from_key && parse_env_value(raw_value) == from_value
+            });
+            if matches {
+                *line = if commented && !from_commented {
+                    format!("# {}", to)
+                } else {
+                    to.clone()
+                };
+            }
+        }
+    }
+
+    lines.extend(appended.iter().cloned());
+
+    let mut rewritten = lines.join("\n");
+    rewritten.push('\n');
+    rewritten
+}
+
+fn configure_project_env(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    apply_env_replacements(input, config)?;
+
+    println!("{}", paint(Tone::Step, ">> Verificando APP_KEY..."));
+    execute_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd {} && if grep -q '^APP_KEY=.\\+' .env; then echo 'APP_KEY já definida.'; else {} artisan key:generate --force; fi",
+                container_app_dir(input, config),
+                config.php_bin
+            ),
+        ],
+    )
+}
+
+fn apply_env_replacements(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!("{}", paint(Tone::Step, ">> Configurando arquivo .env..."));
+
+    let replacements = env_replacements(input, config);
+    let appended = env_appended_lines(input, config);
+
+    match config.env_strategy {
+        EnvStrategy::Sed => update_env_with_sed(input, config, &replacements, &appended)?,
+        EnvStrategy::Rewrite => rewrite_env_file(input, config, &replacements, &appended)?,
+    }
+
+    println!("Arquivo .env configurado.");
+    Ok(())
+}
+
+fn run_config_clear(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    if config.no_config_clear {
+        println!("config:clear desativado (--no-config-clear). Etapa ignorada.");
+        return Ok(());
+    }
+
+    execute_command_in_container(
+        &config.php_container_name,
+        &["sh", "-c", &artisan_command(input, config, "config:clear")],
+    )
+}
+
+fn artisan_command(input: &ProjectInput, config: &AppConfig, command: &str) -> String {
+    format!(
+        "cd {} && {} artisan {}",
+        container_app_dir(input, config),
+        config.php_bin,
+        command
+    )
+}
+
+fn run_artisan_setup(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    if let Some(steps) = &config.artisan_steps {
+        return run_artisan_steps(input, config, steps);
+    }
+
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Executando comandos Artisan (config:clear, migrate)..."
+        )
+    );
+
+    run_config_clear(input, config)?;
+
+    if config.skip_migrate {
+        println!("Migrate desativado (--no-migrate). Etapa ignorada.");
+        return Ok(());
+    }
+
+    ensure_local_database(input, config)?;
+    execute_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &artisan_command(input, config, migration_command(config)),
+        ],
+    )?;
+
+    if config.no_app_seeders {
+        run_baseline_seeders(input, config)?;
+    }
+    Ok(())
+}
+
+fn is_local_db_host(host: &str, config: &AppConfig) -> bool {
+    let host = host
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_lowercase();
+    host.is_empty()
+        || host == "localhost"
+        || host == "::1"
+        || host.starts_with("127.")
+        || host == db_host(config)
+        || host == config.db_container_name.to_lowercase()
+        || ["mariadb", "mysql", "db", "database"].contains(&host.as_str())
+}
+
+// migrate --force contra um servidor real apaga/altera dados sem volta; exige opt-in explícito
+fn ensure_local_database(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    if config.allow_remote_db {
+        return Ok(());
+    }
+
+    let env_content = read_project_env(input, config);
+    if env_value(&env_content, "DB_CONNECTION").is_some_and(|connection| connection == "sqlite") {
+        return Ok(());
+    }
+
+    match env_value(&env_content, "DB_HOST") {
+        Some(host) if !is_local_db_host(&host, config) => Err(AppError::Validation(format!(
+            "RECUSADO: o .env do projeto aponta DB_HOST para '{}', que não é um serviço local nem loopback. Rodar migrations contra esse host pode destruir dados de um banco real. Corrija o DB_HOST ou, se for intencional, rode novamente com --allow-remote-db.",
+            host.trim()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn run_artisan_steps(
+    input: &ProjectInput,
+    config: &AppConfig,
+    steps: &[String],
+) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Executando comandos Artisan de ARTISAN_STEPS ({})...",
+                steps.join(", ")
+            )
+        )
+    );
+
+    if steps
+        .iter()
+        .any(|step| step.starts_with("migrate") || step.starts_with("db:"))
+    {
+        ensure_local_database(input, config)?;
+    }
+
+    for step in steps {
+        println!("php artisan {}", step);
+        execute_command_in_container(
+            &config.php_container_name,
+            &["sh", "-c", &artisan_command(input, config, step)],
+        )?;
+    }
+    Ok(())
+}
+
+fn run_baseline_seeders(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    if config.baseline_seeders.is_empty() {
+        println!(
+            "Nenhum seeder base configurado (BASELINE_SEEDERS). DatabaseSeeder não executado."
+        );
+        return Ok(());
+    }
+
+    let app_dir = container_app_dir(input, config);
+    for seeder in &config.baseline_seeders {
+        let seeder_file = format!("{}/database/seeders/{}.php", app_dir, seeder);
+        let present = capture_command_in_container(
+            &config.php_container_name,
+            &[
+                "sh",
+                "-c",
+                &format!("[ -f '{}' ] && echo ok || true", seeder_file),
+            ],
+        )? == "ok";
+        if !present {
+            println!(
+                "Seeder base '{}' não encontrado no projeto. Ignorado.",
+                seeder
+            );
+            continue;
+        }
+
+        println!(
+            "{}",
+            paint(
+                Tone::Step,
+                &format!(">> Executando seeder base {}...", seeder)
+            )
+        );
+        execute_command_in_container(
+            &config.php_container_name,
+            &["sh", "-c", &seeder_command(input, config, seeder)],
+        )?;
+    }
+    Ok(())
+}
+
+fn seeder_command(input: &ProjectInput, config: &AppConfig, seeder: &str) -> String {
+    artisan_command(
+        input,
+        config,
+        &format!("db:seed --class={} --force", seeder),
+    )
+}
+
+fn sed_env_updates(replacements: &[(String, String)], appended: &[String]) -> Vec<String> {
+    replacements
+        .iter()
+        .map(|(from, to)| format!("s/{}/{}/", sed_escape(from), sed_escape(to)))
+        .chain(appended.iter().map(|line| format!("$a {}", line)))
+        .collect()
+}
+
+fn update_env_with_sed(
+    input: &ProjectInput,
+    config: &AppConfig,
+    replacements: &[(String, String)],
+    appended: &[String],
+) -> Result<(), AppError> {
+    let mut noop_patterns = Vec::new();
+    for update in sed_env_updates(replacements, appended) {
+        // Ensaio sem -i: 's///p' só imprime se a expressão casar com alguma linha
+        if update.starts_with("s/")
+            && capture_command_in_container(
+                &config.php_container_name,
+                &[
+                    "sh",
+                    "-c",
+                    &format!(
+                        "cd {} && sed -n '{}p' .env",
+                        container_app_dir(input, config),
+                        update.replace('\'', "'\\''")
+                    ),
+                ],
+            )
+            .is_ok_and(|matched| matched.is_empty())
+        {
+            noop_patterns.push(update.clone());
+        }
+
+        let command_str = format!(
+            "cd {} && sed -i '{}' .env",
+            container_app_dir(input, config),
+            update.replace('\'', "'\\''")
+        );
+
+        let args: Vec<&str> = vec!["sh", "-c", command_str.as_str()];
+
+        let status = Command::new("docker")
+            .arg("exec")
+            .args(docker_exec_user_flags())
+            .args(docker_exec_tty_flags())
+            .arg(&config.php_container_name)
+            .args(&args)
+            .traced()
+            .status()
+            .map_err(|e| {
+                AppError::Docker(format!("Falha ao executar sed para .env: {}", e))
+                    .with_source(command_context(e))
+            })?;
+
+        if !status.success() {
+            return Err(AppError::Docker(format!(
+                "Falha ao atualizar o .env com: '{}'. Status: {:?}",
+                update, status
+            )));
+        }
+    }
+
+    if !noop_patterns.is_empty() {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: {} expressão(ões) sed não casaram com nenhuma linha do .env e não alteraram nada:\n  {}\nConfira o .env gerado ou use ENV_STRATEGY=rewrite.",
+                    noop_patterns.len(),
+                    noop_patterns
+                        .iter()
+                        .map(|pattern| redact(pattern))
+                        .collect::<Vec<String>>()
+                        .join("\n  ")
+                )
+            )
+        );
+    }
+
+    Ok(())
+}
+
+// Mesmos valores do .env, mas a senha fica como placeholder: o .env.example vai para o git
+fn sync_env_example(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Atualizando .env.example com os valores do ambiente Docker..."
+        )
+    );
+
+    let replacements: Vec<(String, String)> = env_replacements(input, config)
+        .into_iter()
+        .map(|(from, to)| {
+            if to.starts_with("DB_PASSWORD=") {
+                (from, "DB_PASSWORD=".to_string())
+            } else {
+                (from, to)
+            }
+        })
+        .collect();
+
+    let example_path = format!("{}/.env.example", container_app_dir(input, config));
+    let content =
+        capture_command_in_container(&config.php_container_name, &["cat", &example_path])?;
+    let appended: Vec<String> = env_appended_lines(input, config)
+        .into_iter()
+        .filter(|line| !content.lines().any(|existing| existing == line))
+        .collect();
+
+    write_container_file(
+        config,
+        &example_path,
+        &rewrite_env_content(&content, &replacements, &appended),
+    )?;
+    println!(".env.example atualizado (DB_PASSWORD mantida em branco).");
+    Ok(())
+}
+
+fn rewrite_env_file(
+    input: &ProjectInput,
+    config: &AppConfig,
+    replacements: &[(String, String)],
+    appended: &[String],
+) -> Result<(), AppError> {
+    let env_path = format!("{}/.env", container_app_dir(input, config));
+    let content = capture_command_in_container(&config.php_container_name, &["cat", &env_path])?;
+    let rewritten = rewrite_env_content(&content, replacements, appended);
+
+    write_container_file(config, &env_path, &rewritten)
+}
+
+fn write_container_file(config: &AppConfig, path: &str, content: &str) -> Result<(), AppError> {
+    let mut child = Command::new("docker")
+        .arg("exec")
+        .args(docker_exec_user_flags())
+        .arg("-i")
+        .arg(&config.php_container_name)
+        .args(["sh", "-c", &format!("cat > '{}'", path)])
+        .stdin(Stdio::piped())
+        .traced()
+        .spawn()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao gravar '{}' no contêiner: {}", path, e))
+                .with_source(command_context(e))
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao gravar '{}' no contêiner. Status: {:?}",
+            path, status
+        )));
+    }
+
+    Ok(())
+}
+
+fn testing_env_content(env_content: &str, test_database: Option<&str>) -> String {
+    // Com --with-test-db mantém a conexão do .env e troca só o banco
+    let overrides: Vec<(&str, &str)> = match test_database {
+        Some(database) => vec![("APP_ENV", "testing"), ("DB_DATABASE", database)],
+        None => vec![
+            ("APP_ENV", "testing"),
+            ("DB_CONNECTION", "sqlite"),
+            ("DB_DATABASE", ":memory:"),
+        ],
+    };
+
+    let mut lines: Vec<String> = env_logical_lines(env_content)
+        .into_iter()
+        .filter(|line| {
+            let key = env_assignment(line.trim_start_matches("# ")).map(|(key, _)| key);
+            !overrides.iter().any(|(name, _)| Some(*name) == key)
+        })
+        .collect();
+    lines.extend(overrides.iter().map(|(name, value)| env_line(name, value)));
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+fn write_testing_env(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let test_database = config
+        .with_test_db
+        .then(|| test_database_name(input, config));
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &match &test_database {
+                Some(database) => format!(">> Gerando .env.testing (banco '{}')...", database),
+                None => ">> Gerando .env.testing (sqlite em memória)...".to_string(),
+            }
+        )
+    );
+
+    let app_dir = container_app_dir(input, config);
+    let content = capture_command_in_container(
+        &config.php_container_name,
+        &["cat", &format!("{}/.env", app_dir)],
+    )?;
+    write_container_file(
+        config,
+        &format!("{}/.env.testing", app_dir),
+        &testing_env_content(&content, test_database.as_deref()),
+    )?;
+
+    println!(".env.testing criado em {}.", app_dir);
+    Ok(())
+}
+
+fn parse_php_ini_settings(raw: &[String]) -> Result<Vec<(String, String)>, AppError> {
+    let mut settings: Vec<(String, String)> = Vec::new();
+    for entry in raw {
+        let (key, value) = entry
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "--php-ini espera chave=valor (ex: memory_limit=512M), recebido '{}'.",
+                    entry
+                ))
+            })?;
+        if !key.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(AppError::Validation(format!(
+                "--php-ini: '{}' não é um nome de diretiva do PHP válido.",
+                key
+            )));
+        }
+        if value.contains(['"', '\n', '\r']) {
+            return Err(AppError::Validation(format!(
+                "--php-ini: o valor de '{}' não pode conter aspas nem quebras de linha.",
+                key
+            )));
+        }
+        // A última ocorrência vence, como no próprio php.ini
+        settings.retain(|(existing, _)| existing != key);
+        settings.push((key.to_string(), value.to_string()));
+    }
+    Ok(settings)
+}
+
+fn php_ini_content(settings: &[(String, String)]) -> String {
+    let mut content = String::from("; Gerado pelo lara (--php-ini)\n");
+    for (key, value) in settings {
+        let bare = value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/".contains(c));
+        if bare {
+            content.push_str(&format!("{} = {}\n", key, value));
+        } else {
+            content.push_str(&format!("{} = \"{}\"\n", key, value));
+        }
+    }
+    content
+}
+
+fn write_php_ini(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(
+                ">> Gravando public/.user.ini ({} diretiva(s) do PHP)...",
+                config.php_ini.len()
+            )
+        )
+    );
+    // Com Octane as requisições não passam pelo PHP-FPM, que é quem lê o .user.ini
+    if input.octane {
+        println!(
+            "{}",
+            paint(
+                Tone::Warning,
+                "AVISO: com Octane o .user.ini não é lido; ajuste o php.ini da imagem do servidor."
+            )
+        );
+    }
+
+    let path = format!("{}/public/.user.ini", container_app_dir(input, config));
+    write_container_file(config, &path, &php_ini_content(&config.php_ini))?;
+
+    println!("Ajustes do PHP gravados em {}.", path);
+    Ok(())
+}
+
+fn run_deferred_composer_scripts(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Executando scripts adiados (composer run-script post-root-package-install)..."
+        )
+    );
+
+    let result = execute_command_in_container(
+        &config.php_container_name,
+        &["sh", "-c", &deferred_scripts_command(input, config)],
+    );
+
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: post-root-package-install falhou ({}). Copiando .env.example para .env manualmente.",
+                    e
+                )
+            )
+        );
+        execute_command_in_container(
+            &config.php_container_name,
+            &["sh", "-c", &env_from_example_command(input, config)],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn deferred_scripts_command(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "cd {} && {} run-script post-root-package-install",
+        container_app_dir(input, config),
+        config.composer_bin
+    )
+}
+
+fn env_from_example_command(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "cd {} && [ -f .env ] || cp .env.example .env",
+        container_app_dir(input, config)
+    )
+}
+
+fn run_artisan_optimize(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            ">> Gerando caches (config:cache, route:cache, view:cache)..."
+        )
+    );
+
+    let mut built = Vec::new();
+    for cache in OPTIMIZE_CACHES {
+        let result = execute_command_in_container(
+            &config.php_container_name,
+            &[
+                "sh",
+                "-c",
+                &artisan_command(input, config, &format!("{}:cache", cache)),
+            ],
+        );
+
+        match result {
+            Ok(()) => built.push(cache),
+            Err(e) => eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!("AVISO: {}:cache falhou: {}", cache, e)
+                )
+            ),
+        }
+    }
+
+    println!(
+        "Caches gerados: {}",
+        if built.is_empty() {
+            "nenhum".to_string()
+        } else {
+            built.join(", ")
+        }
+    );
+    Ok(())
+}
+
+// Nome em minúsculas (com ':' e '-') e argumentos sem metacaracteres do shell
+fn is_plausible_artisan_step(step: &str) -> bool {
+    let mut parts = step.split_whitespace();
+    let command_ok = parts.next().is_some_and(|command| {
+        command.starts_with(|c: char| c.is_ascii_lowercase())
+            && command
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, ':' | '-'))
+    });
+    command_ok
+        && parts.all(|arg| {
+            arg.chars().all(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '=' | '.' | '/')
+            })
+        })
+}
+
+fn migration_command(config: &AppConfig) -> &'static str {
+    if config.seed {
+        "migrate:fresh --seed --force"
+    } else {
+        "migrate --force"
+    }
+}
+
+fn composer_update_command(input: &ProjectInput, config: &AppConfig) -> String {
+    format!(
+        "cd {} && {} update",
+        container_app_dir(input, config),
+        config.composer_bin
+    )
+}
+
+fn run_composer_update(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!("{}", paint(Tone::Step, ">> Executando composer update..."));
+    let command = composer_update_command(input, config);
+    let (status, output) =
+        run_composer_with_memory_fallback(config, &["sh", "-c", &command], || {})?;
+
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "composer update falhou. Status: {:?}.{}",
+            status,
+            composer_failure_hint(&output)
+        )));
+    }
+    Ok(())
+}
+
+fn composer_out_of_memory(output: &str) -> bool {
+    output.contains("Allowed memory size") && output.contains("exhausted")
+}
+
+fn composer_failure_hint(output: &str) -> String {
+    if composer_out_of_memory(output) {
+        " O Composer ficou sem memória mesmo com COMPOSER_MEMORY_LIMIT=-1: aumente a memória disponível para o contêiner PHP.".to_string()
+    } else {
+        format!(" Últimas linhas:\n{}", output_tail(output, 20))
+    }
+}
+
+fn run_composer_with_memory_fallback(
+    config: &AppConfig,
+    args: &[&str],
+    before_retry: impl FnOnce(),
+) -> Result<(ExitStatus, String), AppError> {
+    let (status, output) = stream_command_in_container(&config.php_container_name, args)?;
+    if status.success() || !composer_out_of_memory(&output) {
+        return Ok((status, output));
+    }
+
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            "AVISO: O Composer excedeu o limite de memória do PHP. Repetindo com COMPOSER_MEMORY_LIMIT=-1..."
+        )
+    );
+    before_retry();
+
+    stream_command_in_container(&config.php_container_name, &composer_memory_retry(args))
+}
+
+fn composer_memory_retry<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    ["env", "COMPOSER_MEMORY_LIMIT=-1"]
+        .into_iter()
+        .chain(args.iter().copied())
+        .collect()
+}
+
+fn composer_memory_retry_note(args: &[&str]) -> String {
+    format!(
+        "se o Composer ficar sem memória, repete com: {}",
+        composer_memory_retry(args).join(" ")
+    )
+}
+
+fn npm_install_command(input: &ProjectInput, config: &AppConfig) -> String {
+    format!("cd {} && npm install", container_app_dir(input, config))
+}
+
+fn run_npm_install(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    check_node_version(input, config)?;
+
+    let npm_install = npm_install_command(input, config);
+
+    let base_delay = Duration::from_secs(NPM_RETRY_DELAY_SECS);
+
+    retry_with_backoff(config.npm_retries, base_delay, |attempt| {
+        println!(
+            "{}",
+            paint(
+                Tone::Step,
+                &format!(
+                    ">> Executando npm install (Tentativa {} de {})...",
+                    attempt, config.npm_retries
+                )
+            )
+        );
+
+        let (status, output) =
+            stream_command_in_container(&config.node_container_name, &["sh", "-c", &npm_install])?;
+
+        if status.success() {
+            return Ok(());
+        }
+
+        if attempt == config.npm_retries {
+            return Err(AppError::Docker(format!(
+                "npm install falhou após {} tentativa(s). Status: {:?}. Últimas linhas:\n{}",
+                config.npm_retries,
+                status,
+                output_tail(&output, 20)
+            )));
+        }
+
+        eprintln!(
+            "{}",
+            paint(
+                Tone::Warning,
+                &format!(
+                    "AVISO: npm install falhou (Status: {:?}). Verificando o cache do npm e tentando novamente em {}s...",
+                    status,
+                    backoff_delay(base_delay, attempt).as_secs()
+                )
+            )
+        );
+
+        let _ = stream_command_in_container(
+            &config.node_container_name,
+            &[
+                "sh",
+                "-c",
+                &format!(
+                    "cd {} && npm cache verify",
+                    container_app_dir(input, config)
+                ),
+            ],
+        );
+        Err(AppError::Docker(format!(
+            "npm install falhou. Status: {:?}",
+            status
+        )))
+    })
+}
+
+fn detect_octane(input: &ProjectInput, config: &AppConfig) -> bool {
+    let composer_json = capture_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd {} && cat composer.json",
+                container_app_dir(input, config)
+            ),
+        ],
+    );
+
+    match composer_json {
+        Ok(content) if content.contains("\"laravel/octane\"") => {
+            println!("Laravel Octane detectado no composer.json.");
+            true
+        }
+        _ => false,
+    }
+}
+
+fn configure_octane_env(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!(
+        "{}",
+        paint(Tone::Step, ">> Configurando .env para Laravel Octane...")
+    );
+
+    execute_command_in_container(
+        &config.php_container_name,
+        &[
+            "sh",
+            "-c",
+            &format!(
+                "cd {} && (grep -q '^OCTANE_SERVER=' .env && sed -i 's/^OCTANE_SERVER=.*/OCTANE_SERVER={server}/' .env || echo 'OCTANE_SERVER={server}' >> .env)",
+                container_app_dir(input, config),
+                server = config.octane_server
+            ),
+        ],
+    )?;
+
+    println!(
+        "Octane configurado (servidor: {}). O Vhost fará proxy para http://{}.",
+        config.octane_server, config.octane_target
+    );
+    Ok(())
+}
+
+fn patch_vite_config(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    println!("{}", paint(Tone::Step, ">> Configurando vite.config.js..."));
+
+    let vite_update = "s|});$|\\tserver: {\\n\\t\\thost: '0.0.0.0'\\n\\t}\\n});|";
+
+    let command_str = format!(
+        "cd {} && sed -i \"{}\" vite.config.js",
+        container_app_dir(input, config),
+        vite_update
+    );
+
+    let args: Vec<&str> = vec!["sh", "-c", command_str.as_str()];
+
+    let status = Command::new("docker")
+        .arg("exec")
+        .args(docker_exec_user_flags())
+        .args(docker_exec_tty_flags())
+        .arg(&config.php_container_name)
+        .args(&args)
+        .traced()
+        .status()
+        .map_err(|e| {
+            AppError::Docker(format!("Falha ao executar sed para vite.config.js: {}", e))
+                .with_source(command_context(e))
+        })?;
+
+    if !status.success() {
+        return Err(AppError::Docker(format!(
+            "Falha ao atualizar o vite.config.js com: '{}'. Status: {:?}",
+            vite_update, status,
+        )));
+    }
+
+    println!("vite.config.js configurado com sucesso.");
+
+    Ok(())
+}
+
+fn fetch_http_status(url: &str) -> Option<u16> {
+    let output = Command::new("curl")
+        .arg("-s")
+        .arg("-k")
+        .arg("-o")
+        .arg("/dev/null")
+        .arg("-w")
+        .arg("%{http_code}")
+        .arg("--max-time")
+        .arg("5")
+        .arg(url)
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|code| *code != 0)
+}
+
+fn verify_project_http(input: &ProjectInput, config: &AppConfig) -> Result<(), AppError> {
+    let url = project_url(input, config);
+    println!(
+        "{}",
+        paint(
+            Tone::Step,
+            &format!(">> Verificando resposta HTTP de {}...", url)
+        )
+    );
+
+    let result = retry_with_backoff(
+        HTTP_VERIFY_ATTEMPTS,
+        Duration::from_secs(HTTP_VERIFY_DELAY_SECS),
+        |_| match fetch_http_status(&url) {
+            Some(200) => Ok(()),
+            Some(code) => Err(AppError::Docker(format!("HTTP {}", code))),
+            None => Err(AppError::Docker("sem resposta".to_string())),
+        },
+    );
+
+    let status_description = match result {
+        Ok(()) => {
+            println!(
+                "{}",
+                paint(Tone::Success, "O projeto respondeu com HTTP 200.")
+            );
+            return Ok(());
+        }
+        Err(AppError::Docker(description)) => description,
+        Err(e) => return Err(e),
+    };
+    eprintln!(
+        "{}",
+        paint(
+            Tone::Warning,
+            &format!(
+                "AVISO: {} retornou {} após {} tentativas. Verifique o Vhost em {} e os logs com 'docker compose logs apache php'.",
+                url, status_description, HTTP_VERIFY_ATTEMPTS, VHOSTS_DIR
+            )
+        )
+    );
+    Ok(())
+}
+
+fn open_project_in_editor(input: &ProjectInput) -> Result<(), AppError> {
+    let editor_cmd = env::var("EDITOR_CMD")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| "code".to_string());
+
+    let mut parts = editor_cmd.split_whitespace();
+    let program = parts.next().unwrap_or("code");
+
+    println!("Abrindo o projeto no editor '{}'...", editor_cmd);
+
+    match Command::new(program)
+        .args(parts)
+        .arg(&input.project_path)
+        .status()
+    {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => {
+            eprintln!(
+                "O editor '{}' terminou com falha (Status: {:?}). Abra manualmente: {}",
+                editor_cmd, status, input.project_path
+            );
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!(
+                "Editor '{}' não encontrado. Abra o projeto manualmente em: {}",
+                program, input.project_path
+            );
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn program_in_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|path| {
+        env::split_paths(&path).any(|dir| {
+            dir.join(program).is_file()
+                || (cfg!(windows) && dir.join(format!("{}.exe", program)).is_file())
+        })
+    })
+}
+
+// Nomes comuns viram os executáveis de cada plataforma; nomes desconhecidos são usados como estão
+fn browser_candidates(browser: &str) -> Vec<String> {
+    let name = browser.trim().to_lowercase();
+    let candidates: &[&str] = if cfg!(target_os = "macos") {
+        match name.as_str() {
+            "firefox" => &["Firefox"],
+            "chrome" | "google-chrome" => &["Google Chrome"],
+            "chromium" => &["Chromium"],
+            "edge" => &["Microsoft Edge"],
+            "brave" => &["Brave Browser"],
+            "safari" => &["Safari"],
+            _ => &[],
+        }
+    } else if cfg!(windows) {
+        match name.as_str() {
+            "chrome" | "google-chrome" => &["chrome"],
+            "edge" => &["msedge"],
+            _ => &[],
+        }
+    } else {
+        match name.as_str() {
+            "firefox" => &["firefox"],
+            "chrome" | "google-chrome" => &[
+                "google-chrome",
+                "google-chrome-stable",
+                "chromium",
+                "chromium-browser",
+            ],
+            "chromium" => &["chromium", "chromium-browser"],
+            "edge" => &["microsoft-edge", "microsoft-edge-stable"],
+            "brave" => &["brave-browser", "brave"],
+            _ => &[],
+        }
+    };
+
+    if candidates.is_empty() {
+        vec![browser.trim().to_string()]
+    } else {
+        candidates
+            .iter()
+            .map(|candidate| candidate.to_string())
+            .collect()
+    }
+}
+
+fn browser_command(url: &str, browser: Option<&str>) -> Command {
+    if let Some(browser) = browser {
+        let found = browser_candidates(browser).into_iter().find(|candidate| {
+            // No macOS o nome é de um .app; quem resolve é o 'open -a'
+            cfg!(target_os = "macos") || cfg!(windows) || program_in_path(candidate)
+        });
+        match found {
+            Some(app) if cfg!(target_os = "macos") => {
+                let mut command = Command::new("open");
+                command.arg("-a").arg(app).arg(url);
+                return command;
+            }
+            Some(program) if cfg!(windows) => {
+                let mut command = Command::new("cmd");
+                command.args(["/C", "start", "", &program, url]);
+                return command;
+            }
+            Some(program) => {
+                let mut command = Command::new(program);
+                command.arg(url);
+                return command;
+            }
+            None => eprintln!(
+                "{}",
+                paint(
+                    Tone::Warning,
+                    &format!(
+                        "AVISO: Navegador '{}' não encontrado. Usando o navegador padrão.",
+                        browser
+                    )
+                )
+            ),
+        }
+    }
+
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", url]);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    }
+}
+
+fn open_project_in_browser(url: &str, browser: Option<&str>) {
+    println!(
+        "Abrindo {} no navegador{}...",
+        url,
+        browser.map_or(String::new(), |browser| format!(" '{}'", browser))
+    );
+
+    // spawn sem esperar: o executável do navegador só retorna quando a janela é fechada
+    if let Err(e) = browser_command(url, browser)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        eprintln!(
+            "Não foi possível abrir o navegador ({}). Abra manualmente: {}",
+            e, url
+        );
+    }
+}
+
+// O Rust ignora SIGPIPE e transforma o pipe fechado (ex: '| head') em panic no println!.
+// Restaurar o comportamento padrão encerra o processo em silêncio; escritas em arquivos
+// continuam retornando erro normalmente, pois SIGPIPE só existe para pipes e sockets.
+#[cfg(unix)]
+fn reset_sigpipe() {
+    const SIGPIPE: i32 = 13;
+    const SIG_DFL: usize = 0;
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+    unsafe {
+        signal(SIGPIPE, SIG_DFL);
+    }
+}
+
+// Aponta o fd 1 para o stderr e devolve uma cópia do stdout original para uso posterior
+#[cfg(unix)]
+fn divert_stdout_to_stderr() -> Option<i32> {
+    unsafe extern "C" {
+        fn dup(fd: i32) -> i32;
+        fn dup2(old_fd: i32, new_fd: i32) -> i32;
+    }
+    let _ = io::stdout().flush();
+    let saved = unsafe { dup(1) };
+    if saved < 0 || unsafe { dup2(2, 1) } < 0 {
+        return None;
+    }
+    Some(saved)
+}
+
+#[cfg(not(unix))]
+fn divert_stdout_to_stderr() -> Option<i32> {
+    None
+}
+
+// Escreve no stdout original e volta a desviar o restante da saída para o stderr
+fn print_to_real_stdout(saved: Option<i32>, text: &str) {
+    let _ = io::stdout().flush();
+    #[cfg(unix)]
+    unsafe extern "C" {
+        fn dup2(old_fd: i32, new_fd: i32) -> i32;
+    }
+    #[cfg(unix)]
+    if let Some(fd) = saved {
+        unsafe {
+            dup2(fd, 1);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = saved;
+    print!("{}", text);
+    let _ = io::stdout().flush();
+    #[cfg(unix)]
+    if saved.is_some() {
+        unsafe {
+            dup2(2, 1);
+        }
+    }
+}
+
+fn main() {
+    #[cfg(unix)]
+    reset_sigpipe();
+
+    match run() {
+        Ok(RunOutcome::Created { json: false }) => {
+            println!(
+                "\n {}",
+                paint(Tone::Success, "Rotina concluída com sucesso.")
+            );
+        }
+        Ok(RunOutcome::Created { json: true }) => {
+            eprintln!(
+                "\n {}",
+                paint(Tone::Success, "Rotina concluída com sucesso.")
+            );
+        }
+        Ok(RunOutcome::Done) => {}
+        Err(e) => {
+            // Com --verbose a cadeia de causas (erro do SO, comando que falhou) vem junto
+            let message = if VERBOSE_DOCKER.get().copied().unwrap_or(false) {
+                format!("{:#}", e)
+            } else {
+                e.to_string()
+            };
+            eprintln!(
+                "\n {}",
+                paint(Tone::Error, &format!("Falha na execução: {}", message))
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    fn test_config() -> AppConfig {
+        get_app_config(&CliOptions::default()).unwrap()
+    }
+
+    fn test_input(laravel_version: &str) -> ProjectInput {
+        ProjectInput {
+            project_name: "demo-app".to_string(),
+            project_host: "demo-app.test".to_string(),
+            project_dir: "demo-app".to_string(),
+            project_path: "/var/www/html/demo-app".to_string(),
+            laravel_version: laravel_version.to_string(),
+            octane: false,
+            label: None,
+        }
+    }
+
+    // Aplica as expressões como o container faz: um 'sed -i' por expressão, em ordem
+    fn apply_with_sed(content: &str, updates: &[String], scratch: &Path) -> String {
+        let env_path = scratch.join(".env");
+        fs::write(&env_path, content).unwrap();
+        for update in updates {
+            let status = Command::new("sed")
+                .arg("-i")
+                .arg(update)
+                .arg(&env_path)
+                .status()
+                .unwrap();
+            assert!(status.success(), "sed falhou com '{}'", update);
+        }
+        fs::read_to_string(&env_path).unwrap()
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("lara-unit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn ensure_path_inside_accepts_a_child_directory() {
+        let base = scratch_dir("inside-ok");
+        fs::create_dir_all(base.join("src/app")).unwrap();
+        let resolved = ensure_path_inside(&base.join("src/app"), &base.join("src")).unwrap();
+        assert_eq!(resolved, fs::canonicalize(base.join("src/app")).unwrap());
+    }
+
+    #[test]
+    fn ensure_path_inside_rejects_a_dot_dot_escape() {
+        let base = scratch_dir("inside-dotdot");
+        fs::create_dir_all(base.join("src")).unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+        let escape = base.join("src/../outside");
+        assert!(matches!(
+            ensure_path_inside(&escape, &base.join("src")),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn ensure_path_inside_rejects_a_symlink_escape() {
+        let base = scratch_dir("inside-symlink");
+        fs::create_dir_all(base.join("src")).unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+        std::os::unix::fs::symlink(base.join("outside"), base.join("src/link")).unwrap();
+        assert!(matches!(
+            ensure_path_inside(&base.join("src/link"), &base.join("src")),
+            Err(AppError::Validation(_))
+        ));
+        assert!(remove_project_dir(&base.join("src/link"), &base.join("src"), true).is_err());
+        assert!(base.join("outside").is_dir());
+    }
+
+    #[test]
+    fn ensure_path_inside_rejects_the_base_itself() {
+        let base = scratch_dir("inside-base");
+        fs::create_dir_all(base.join("src")).unwrap();
+        assert!(matches!(
+            ensure_path_inside(&base.join("src/."), &base.join("src")),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn strip_hosts_entries_keeps_other_names_on_shared_lines() {
+        let content = "127.0.0.1 localhost app.test # meu\n127.0.0.1 app.test # laravel-maker:x\n::1 app.test\n10.0.0.1 app.test\n";
+        let stripped =
+            strip_hosts_entries(content, &["app.test".to_string()], &LOOPBACK_ADDRESSES).unwrap();
+        assert_eq!(stripped, "127.0.0.1 localhost # meu\n10.0.0.1 app.test\n");
+    }
+
+    #[test]
+    fn file_lock_is_exclusive_until_released() {
+        let lock_path = scratch_dir("file-lock").join(SHARED_LOCK_FILE);
+        let held = acquire_file_lock(&lock_path, Duration::ZERO).unwrap();
+        assert!(matches!(
+            acquire_file_lock(&lock_path, Duration::ZERO),
+            Err(AppError::Validation(message)) if message.contains("Tempo esgotado")
+        ));
+
+        drop(held);
+        assert!(acquire_file_lock(&lock_path, Duration::ZERO).is_ok());
+        assert!(lock_path.exists(), "o arquivo de lock nunca é removido");
+    }
+
+    #[test]
+    fn strip_hosts_entries_only_touches_the_given_addresses() {
+        let content = "127.0.0.1 app.test\n::1 app.test # laravel-maker:api\n";
+        assert_eq!(
+            strip_hosts_entries(content, &["app.test".to_string()], &["::1"]).unwrap(),
+            "127.0.0.1 app.test\n"
+        );
+        assert_eq!(
+            strip_hosts_entries(content, &["app.test".to_string()], &["127.0.0.1"]).unwrap(),
+            "::1 app.test # laravel-maker:api\n"
+        );
+    }
+
+    #[test]
+    fn strip_hosts_entries_ignores_partial_matches() {
+        let content = "127.0.0.1 minha-app.test\n";
+        assert_eq!(
+            strip_hosts_entries(content, &["app.test".to_string()], &LOOPBACK_ADDRESSES),
+            None
+        );
+    }
+
+    #[test]
+    fn sed_and_rewrite_strategies_produce_the_same_stock_env() {
+        let config = test_config();
+        let input = test_input("12");
+        let stock = fixture("laravel-12.env");
+        let replacements = env_replacements(&input, &config);
+        let appended = vec![env_line("TRUSTED_PROXIES", "*")];
+
+        let rewritten = rewrite_env_content(&stock, &replacements, &appended);
+        let sed = apply_with_sed(
+            &stock,
+            &sed_env_updates(&replacements, &appended),
+            &scratch_dir("sed-vs-rewrite"),
+        );
+        assert_eq!(sed, rewritten);
+        assert_ne!(rewritten, stock);
+    }
+
+    #[test]
+    fn exported_spec_round_trips_through_import() {
+        let fixture_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/demo-app.toml");
+        let mut options = CliOptions::default();
+        let spec = apply_project_spec(&fixture_path, &mut options).unwrap();
+        let config = get_app_config(&options).unwrap();
+        let exported = render_project_spec('=', &test_input(&spec.laravel_version), &config);
+
+        let exported_path = scratch_dir("spec-round-trip").join("demo-app.toml");
+        fs::write(&exported_path, &exported).unwrap();
+        let mut reimported_options = CliOptions::default();
+        let reimported = apply_project_spec(&exported_path, &mut reimported_options).unwrap();
+        assert_eq!(reimported.name, spec.name);
+        assert_eq!(reimported.laravel_version, spec.laravel_version);
+
+        let reimported_config = get_app_config(&reimported_options).unwrap();
+        assert_eq!(
+            render_project_spec(
+                '=',
+                &test_input(&reimported.laravel_version),
+                &reimported_config
+            ),
+            exported
+        );
+    }
+
+    #[test]
+    fn secrets_are_masked_only_as_whole_values() {
+        assert_eq!(
+            mask_whole_value(
+                "DB_PASSWORD=hunter2 mysql -phunter2 -e 'hunter2'",
+                "hunter2"
+            ),
+            "DB_PASSWORD=**** mysql -p**** -e '****'"
+        );
+        assert_eq!(
+            mask_whole_value("DB_PASSWORD=password", "PASSWORD"),
+            "DB_PASSWORD=password"
+        );
+        assert_eq!(
+            mask_whole_value("senha: abc abcd", "abc"),
+            "senha: **** abcd"
+        );
+    }
+
+    #[test]
+    fn command_arguments_hide_password_values() {
+        assert_eq!(redact_secrets("-psegredo"), "-p****");
+        assert_eq!(redact_secrets("--port=3306"), "--port=3306");
+        assert_eq!(
+            redact_secrets("DB_PASSWORD=segredo DB_HOST=mariadb"),
+            "DB_PASSWORD=**** DB_HOST=mariadb"
+        );
+        assert_eq!(
+            redact_secrets("CREATE USER 'app' IDENTIFIED BY 'segredo';"),
+            "CREATE USER 'app' IDENTIFIED BY '****';"
+        );
+    }
+
+    fn parse_args(args: &[&str]) -> Result<CliOptions, AppError> {
+        parse_cli_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn cli_values_come_inline_or_from_the_next_argument() {
+        let options = parse_args(&["--label=api", "--http-port", "8080", "--ssl", "-y"]).unwrap();
+        assert_eq!(options.label.as_deref(), Some("api"));
+        assert_eq!(options.http_port, Some(8080));
+        assert!(options.ssl && options.assume_yes);
+
+        let options = parse_args(&["delete", "api", "--with-data"]).unwrap();
+        assert_eq!(
+            options.command,
+            Some(Subcommand::Delete(Some("api".to_string())))
+        );
+        assert!(options.with_data);
+    }
+
+    #[test]
+    fn cli_rejects_inline_values_on_boolean_flags() {
+        for arg in ["--no-rollback=false", "--ssl=no", "--force=0"] {
+            match parse_args(&[arg]) {
+                Err(AppError::Validation(message)) => {
+                    assert!(message.contains("não aceita valor"), "{}", message)
+                }
+                other => panic!("{} deveria falhar, veio {:?}", arg, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn cli_reports_missing_values_and_unknown_flags() {
+        assert!(matches!(
+            parse_args(&["--label"]),
+            Err(AppError::Validation(message)) if message.contains("exige um valor")
+        ));
+        assert!(matches!(
+            parse_args(&["--http-port=0"]),
+            Err(AppError::Validation(message)) if message.contains("Porta inválida")
+        ));
+        assert!(matches!(
+            parse_args(&["--sem-isso"]),
+            Err(AppError::Validation(message)) if message.contains("Argumento desconhecido")
+        ));
+    }
+
+    #[test]
+    fn every_config_key_read_from_the_env_is_listed() {
+        // Variáveis do sistema, não do .env: ficam fora da configuração do lara
+        let not_config = ["COMPOSE_PROJECT_NAME", "EDITOR", "SUDO_GID", "SUDO_UID"];
+        let source = include_str!("main.rs");
+        let mut read_keys = Vec::new();
+        for reader in ["env::var(", "env_flag(", "vhost_directive("] {
+            for (position, _) in source.match_indices(reader) {
+                let rest = source[position + reader.len()..].trim_start();
+                let Some(quoted) = rest.strip_prefix('"') else {
+                    continue;
+                };
+                let key: String = quoted
+                    .chars()
+                    .take_while(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || *c == '_')
+                    .collect();
+                if !key.is_empty() && !not_config.contains(&key.as_str()) {
+                    read_keys.push(key);
+                }
+            }
+        }
+
+        assert!(read_keys.iter().any(|key| key == "VHOST_LOG_DIR"));
+        let missing: Vec<&String> = read_keys
+            .iter()
+            .filter(|key| !CONFIG_ENV_KEYS.contains(&key.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "fora de CONFIG_ENV_KEYS: {:?}", missing);
+    }
+
+    #[test]
+    fn user_config_accepts_quoted_and_bare_values_at_the_root() {
+        let path = Path::new("config.toml");
+        let entries = parse_user_config(
+            path,
+            "# defaults\nvhost_mode = \"single-include\"\nDB_PORT = 3307 # local\n\nsenha = \"a # b\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("vhost_mode".to_string(), "single-include".to_string()),
+                ("DB_PORT".to_string(), "3307".to_string()),
+                ("senha".to_string(), "a # b".to_string()),
+            ]
+        );
+
+        for invalid in ["[padrao]\n", "sem_valor\n", "= 1\n", "nome = \"aberta\n"] {
+            assert!(
+                matches!(
+                    parse_user_config(path, invalid),
+                    Err(AppError::Validation(message)) if message.contains("Linha 1")
+                ),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn every_planned_step_runs_in_phase_order() {
+        let mut config = test_config();
+        config.compose_build = true;
+        config.no_scripts = true;
+        config.with_test_db = true;
+        config.sync_env_example = true;
+        config.optimize = true;
+        config.octane = true;
+        config.fix_ownership = true;
+        config.out_dir = Some(PathBuf::from("/tmp/saida"));
+        config.template_dir = Some(PathBuf::from("/tmp/modelo"));
+
+        // O apply percorre as fases em ordem: uma etapa fora de ordem rodaria na fase errada
+        let plan = plan_project_actions(&test_input("12"), &config);
+        let phases: Vec<Phase> = plan.iter().map(|action| action.step.phase()).collect();
+        assert!(
+            phases.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{:?}",
+            plan.iter().map(|action| action.step).collect::<Vec<_>>()
+        );
+        for step in [
+            Step::ComposeUp,
+            Step::CreateProject,
+            Step::EnvConfig,
+            Step::Vhost,
+            Step::Hosts,
+            Step::Restart,
+        ] {
+            assert!(
+                plan.iter().any(|action| action.step == step),
+                "falta {}",
+                step.name()
+            );
+        }
+    }
+
+    #[test]
+    fn spec_parser_rejects_nested_syntax_and_accepts_flat_lines() {
+        let scratch = scratch_dir("spec-flat");
+        let parse = |file: &str, content: &str| {
+            let path = scratch.join(file);
+            fs::write(&path, content).unwrap();
+            parse_spec_entries(&path)
+        };
+
+        let flat = parse(
+            "flat.yaml",
+            "---\nname: demo-app\ncompose_profile: redis\ncompose_profile: mailpit\n",
+        )
+        .unwrap();
+        assert_eq!(flat.len(), 3);
+        assert_eq!(
+            flat[2],
+            (4, "compose_profile".to_string(), "mailpit".to_string())
+        );
+
+        for (file, content, what) in [
+            (
+                "table.toml",
+                "name = \"demo\"\n[options]\nssl = true\n",
+                "cabeçalhos de tabela",
+            ),
+            ("array.toml", "compose_profile = [\"redis\"]\n", "arrays"),
+            (
+                "inline.toml",
+                "options = { ssl = true }\n",
+                "tabelas inline",
+            ),
+            (
+                "list.yaml",
+                "compose_profile:\n  - redis\n",
+                "campos aninhados",
+            ),
+            ("item.yaml", "- redis\n", "itens de lista"),
+            ("nested.yaml", "options:\n  ssl: true\n", "campos aninhados"),
+        ] {
+            let message = parse(file, content).unwrap_err().to_string();
+            assert!(message.contains(what), "{}: {}", file, message);
+            assert!(message.contains("linhas planas"), "{}: {}", file, message);
+        }
+    }
+
+    #[test]
+    fn parse_laravel_version_accepts_a_trimmed_major() {
+        assert_eq!(parse_laravel_version(" 12 "), Ok(12));
+    }
+
+    #[test]
+    fn parse_laravel_version_explains_each_rejection() {
+        assert_eq!(
+            parse_laravel_version("08"),
+            Err("a versão não pode ter zeros à esquerda".to_string())
+        );
+        assert_eq!(
+            parse_laravel_version("12.0"),
+            Err("informe apenas a versão major, sem '.' (ex: 12 em vez de 12.0)".to_string())
+        );
+        assert_eq!(
+            parse_laravel_version("v12"),
+            Err("não use o prefixo 'v', informe apenas o número".to_string())
+        );
+        assert_eq!(
+            parse_laravel_version("  "),
+            Err("a versão está vazia".to_string())
+        );
+        assert_eq!(
+            parse_laravel_version("doze"),
+            Err("digite apenas o número inteiro da versão".to_string())
+        );
+        assert_eq!(
+            parse_laravel_version("999"),
+            Err("o número da versão está fora do intervalo".to_string())
+        );
+    }
+
+    #[test]
+    fn retry_with_backoff_retries_docker_errors_until_success() {
+        let mut calls = 0;
+        let result = retry_with_backoff(4, Duration::ZERO, |attempt| {
+            calls += 1;
+            if attempt < 3 {
+                Err(AppError::Docker("falha transitória".to_string()))
+            } else {
+                Ok(attempt)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn retry_with_backoff_stops_after_the_last_attempt() {
+        let mut calls = 0;
+        let result: Result<(), AppError> = retry_with_backoff(2, Duration::ZERO, |_| {
+            calls += 1;
+            Err(AppError::Docker("sempre falha".to_string()))
+        });
+        assert!(matches!(result, Err(AppError::Docker(_))));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn retry_with_backoff_does_not_retry_other_errors() {
+        let mut calls = 0;
+        let result: Result<(), AppError> = retry_with_backoff(5, Duration::ZERO, |_| {
+            calls += 1;
+            Err(AppError::Validation("entrada inválida".to_string()))
+        });
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn db_config_is_applied_to_the_stock_env_of_each_major() {
+        let config = test_config();
+        for (major, driver) in [("10", "mysql"), ("11", "mariadb"), ("12", "mariadb")] {
+            let input = test_input(major);
+            let configured = rewrite_env_content(
+                &fixture(&format!("laravel-{}.env", major)),
+                &env_replacements(&input, &config),
+                &[],
+            );
+            let expected = [
+                env_line("DB_CONNECTION", driver),
+                env_line("DB_HOST", db_host(&config)),
+                env_line("DB_PORT", &config.db_container_port.to_string()),
+                env_line("DB_DATABASE", &database_name(&input, &config)),
+                env_line("DB_USERNAME", &db_username(&input, &config)),
+                env_line("APP_URL", &app_url(&input, &config)),
+            ];
+            for line in expected {
+                assert!(
+                    configured.lines().any(|existing| existing == line),
+                    "Laravel {}: '{}' ausente no .env configurado",
+                    major,
+                    line
+                );
+            }
+            assert!(
+                !configured.lines().any(|line| line.starts_with("# DB_")),
+                "Laravel {}: ficou DB_* comentado",
+                major
+            );
+        }
+    }
+
+    #[test]
+    fn format_project_name_applies_each_naming_style() {
+        assert_eq!(
+            format_project_name("Minha App_Nova", NamingStyle::Kebab),
+            "minha-app-nova"
+        );
+        assert_eq!(
+            format_project_name("Minha App_Nova", NamingStyle::Snake),
+            "minha_app_nova"
+        );
+        assert_eq!(
+            format_project_name("Minha App_Nova", NamingStyle::Preserve),
+            "Minha-App_Nova"
+        );
+    }
+
+    #[test]
+    fn project_host_stays_dns_safe_for_every_style() {
+        for style in [
+            NamingStyle::Kebab,
+            NamingStyle::Snake,
+            NamingStyle::Preserve,
+        ] {
+            let host = project_host_for(&format_project_name("Minha App_Nova", style));
+            assert_eq!(host, "minha-app-nova.test");
+            assert_eq!(validate_dns_host(&host), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_dns_host_accepts_lowercase_labels() {
+        for host in ["demo-app.test", "a.b.c", "app2.local", &"a".repeat(63)] {
+            assert_eq!(validate_dns_host(host), Ok(()), "{}", host);
+        }
+    }
+
+    #[test]
+    fn validate_dns_host_explains_each_rejection() {
+        let long_host = vec!["a".repeat(63); 4].join(".");
+        for (host, reason) in [
+            (long_host.as_str(), "máximo 253"),
+            ("demo..test", "entre 1 e 63"),
+            (&"a".repeat(64), "entre 1 e 63"),
+            ("Demo.test", "letras minúsculas"),
+            ("demo_app.test", "letras minúsculas"),
+            ("1demo.test", "começar com uma letra"),
+            ("demo-.test", "terminar com '-'"),
+        ] {
+            let error = validate_dns_host(host).unwrap_err();
+            assert!(error.contains(reason), "{}: {}", host, error);
+        }
+    }
+
+    #[test]
+    fn app_key_must_be_base64_of_32_bytes() {
+        let valid = format!("base64:{}=", "A".repeat(43));
+        assert!(is_valid_app_key(&valid));
+        assert!(is_valid_app_key(
+            "base64:q2Fz+J8v/0OcG5YcQ1x0m7l9b3LrHk4WmZt2pQy6sVw="
+        ));
+
+        for key in [
+            String::new(),
+            "base64:".to_string(),
+            "A".repeat(44),
+            format!("base64:{}", "A".repeat(44)),
+            format!("base64:{}=", "A".repeat(42)),
+            format!("base64:{}=", "A".repeat(44)),
+            format!("base64:{}*=", "A".repeat(42)),
+            format!("SomeRandomString{}", "A".repeat(32)),
+        ] {
+            assert!(!is_valid_app_key(&key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn vhost_block_is_extracted_only_for_the_exact_project() {
+        let content = "# BEGIN demo-app-admin\nadmin\n# END demo-app-admin\n# BEGIN demo-app\n  <VirtualHost *:80>\n  </VirtualHost>\n# END demo-app\n";
+        assert_eq!(
+            extract_vhost_block(content, "demo-app").as_deref(),
+            Some("  <VirtualHost *:80>\n  </VirtualHost>")
+        );
+        assert_eq!(
+            extract_vhost_block(content, "demo-app-admin").as_deref(),
+            Some("admin")
+        );
+        assert_eq!(extract_vhost_block(content, "demo"), None);
+    }
+
+    #[test]
+    fn vhost_block_is_replaced_in_place_appended_or_removed() {
+        let content = "# BEGIN outro-app\noutro\n# END outro-app\n# BEGIN demo-app\nvelho\n# END demo-app\n# BEGIN terceiro\nterceiro\n# END terceiro\n";
+
+        assert_eq!(
+            replace_vhost_block(content, "demo-app", Some("novo\n")),
+            "# BEGIN outro-app\noutro\n# END outro-app\n# BEGIN demo-app\nnovo\n# END demo-app\n# BEGIN terceiro\nterceiro\n# END terceiro\n"
+        );
+        assert_eq!(
+            replace_vhost_block(content, "demo-app", None),
+            "# BEGIN outro-app\noutro\n# END outro-app\n# BEGIN terceiro\nterceiro\n# END terceiro\n"
+        );
+        assert_eq!(
+            replace_vhost_block(
+                "# BEGIN outro-app\noutro\n# END outro-app\n",
+                "demo-app",
+                Some("novo")
+            ),
+            "# BEGIN outro-app\noutro\n# END outro-app\n\n# BEGIN demo-app\nnovo\n# END demo-app\n"
+        );
+        assert_eq!(replace_vhost_block("", "demo-app", None), "");
+
+        let updated = replace_vhost_block(content, "demo-app", Some("novo"));
+        assert_eq!(
+            extract_vhost_block(&updated, "demo-app").as_deref(),
+            Some("novo")
+        );
+        assert_eq!(
+            replace_vhost_block(&updated, "demo-app", Some("novo")),
+            updated
+        );
     }
 }
@@ -0,0 +1,959 @@
+// Testes de ponta a ponta do lara com um 'docker' falso no início do PATH.
+// O script em tests/fixtures/fake-docker registra cada chamada e devolve saídas fixas,
+// então os testes conferem a sequência de comandos sem precisar de Docker.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+struct FakeDockerRun {
+    root: PathBuf,
+}
+
+impl FakeDockerRun {
+    // Monta um dev-container mínimo (docker/, src/, .env, docker-compose.yml) em um diretório temporário
+    fn new(name: &str) -> FakeDockerRun {
+        let root =
+            env::temp_dir().join(format!("lara-fake-docker-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("docker/apache/vhosts")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("state")).unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(root.join(".env"), "CONTAINER_NAME=fake\n").unwrap();
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../docker-compose.yml"),
+            root.join("docker-compose.yml"),
+        )
+        .unwrap();
+        FakeDockerRun { root }
+    }
+
+    fn run(&self, args: &[&str], fail_on: Option<&str>) -> Output {
+        self.run_with_env(args, fail_on, &[])
+    }
+
+    fn run_with_env(&self, args: &[&str], fail_on: Option<&str>, vars: &[(&str, &str)]) -> Output {
+        self.command(args, fail_on, vars).output().unwrap()
+    }
+
+    fn run_in(&self, dir: &Path, args: &[&str]) -> Output {
+        self.command(args, None, &[])
+            .current_dir(dir)
+            .output()
+            .unwrap()
+    }
+
+    fn command(&self, args: &[&str], fail_on: Option<&str>, vars: &[(&str, &str)]) -> Command {
+        let path = format!(
+            "{}/fake-docker:{}",
+            FIXTURES_DIR,
+            env::var("PATH").unwrap_or_default()
+        );
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_lara"));
+        command
+            .args(args)
+            .current_dir(&self.root)
+            .env_clear()
+            .env("PATH", path)
+            .env("HOME", &self.root)
+            .env("XDG_CONFIG_HOME", self.root.join("config"))
+            .env("FAKE_DOCKER_LOG", self.root.join("docker.log"))
+            .env("FAKE_DOCKER_STATE", self.root.join("state"))
+            .env("APACHE_SETTLE_SECS", "0")
+            .env(
+                "FAKE_DOCKER_ENV",
+                Path::new(FIXTURES_DIR).join("project.env"),
+            )
+            .stdin(std::process::Stdio::null());
+        if let Some(pattern) = fail_on {
+            command.env("FAKE_DOCKER_FAIL", pattern);
+        }
+        command.envs(vars.iter().copied());
+        command
+    }
+
+    fn calls(&self) -> Vec<String> {
+        fs::read_to_string(self.root.join("docker.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    // Cada trecho esperado precisa aparecer em alguma chamada, na ordem informada
+    fn assert_calls_in_order(&self, expected: &[&str]) {
+        let calls = self.calls();
+        let mut position = 0;
+        for fragment in expected {
+            match calls[position..]
+                .iter()
+                .position(|call| call.contains(fragment))
+            {
+                Some(offset) => position += offset + 1,
+                None => panic!(
+                    "chamada contendo '{}' não encontrada após a posição {}.\nChamadas:\n{}",
+                    fragment,
+                    position,
+                    calls.join("\n")
+                ),
+            }
+        }
+    }
+}
+
+impl Drop for FakeDockerRun {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn spec_path() -> String {
+    format!("{}/demo-app.toml", FIXTURES_DIR)
+}
+
+#[test]
+fn creates_project_with_expected_docker_calls() {
+    let fake = FakeDockerRun::new("success");
+    let spec = spec_path();
+    let output = fake.run(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+    );
+
+    assert!(
+        output.status.success(),
+        "lara falhou:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    fake.assert_calls_in_order(&[
+        "ps -q -f name=fake_php",
+        "compose up -d",
+        "exec fake_php composer --version",
+        "exec fake_php composer create-project laravel/laravel demo-app 12",
+        "sed -i 's/APP_URL=http:\\/\\/localhost/APP_URL=http:\\/\\/demo-app\\.test/' .env",
+        "DB_HOST=mariadb",
+        "php artisan config:clear",
+        "php artisan migrate --force",
+        "composer update",
+        "exec fake_node sh -c cd /var/www/html/demo-app && npm install",
+        "compose restart apache",
+        "exec fake_php cat /var/www/html/demo-app/.env",
+    ]);
+
+    // O docker falso não imprime nada no ensaio 'sed -n', então todas as expressões são no-op
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("não casaram com nenhuma linha do .env")
+    );
+    assert!(fake.calls().iter().any(|call| call.contains("sed -n 's/")));
+
+    let vhost = fs::read_to_string(fake.root.join("docker/apache/vhosts/demo-app.test.conf"))
+        .expect("vhost não foi criado");
+    assert!(vhost.contains("ServerName demo-app.test"));
+}
+
+#[test]
+fn skips_compose_up_when_stack_is_running() {
+    let fake = FakeDockerRun::new("running");
+    fs::write(fake.root.join("state/running"), "").unwrap();
+    let spec = spec_path();
+    let output = fake.run(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+    );
+
+    assert!(output.status.success());
+    assert!(!fake.calls().iter().any(|call| call.contains(" up -d")));
+}
+
+#[test]
+fn stops_before_configuration_when_create_project_fails() {
+    let fake = FakeDockerRun::new("composer-failure");
+    let spec = spec_path();
+    let output = fake.run(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        Some("create-project"),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Composer falhou ao criar o projeto"),
+        "stderr inesperado:\n{}",
+        stderr
+    );
+
+    fake.assert_calls_in_order(&["compose up -d", "composer create-project"]);
+    assert!(
+        !fake
+            .calls()
+            .iter()
+            .any(|call| call.contains("artisan migrate"))
+    );
+    assert!(
+        !fake
+            .root
+            .join("docker/apache/vhosts/demo-app.test.conf")
+            .exists()
+    );
+}
+
+#[test]
+fn reports_failure_when_apache_restart_fails() {
+    let fake = FakeDockerRun::new("apache-failure");
+    let spec = spec_path();
+    let output = fake.run(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        Some("restart apache"),
+    );
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Falha ao reiniciar o contêiner Apache")
+    );
+    fake.assert_calls_in_order(&[
+        "php artisan migrate --force",
+        "compose restart apache",
+        "compose logs --no-color --since",
+    ]);
+
+    // Os logs anexados ao erro são limitados ao início da etapa que falhou
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("docker compose logs --tail 50 --since"));
+    assert!(stderr.contains("AH00526: Syntax error (fake docker)"));
+}
+
+#[test]
+fn runs_custom_artisan_steps_in_order() {
+    let fake = FakeDockerRun::new("artisan-steps");
+    let spec = spec_path();
+    let output = fake.run_with_env(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+        &[(
+            "ARTISAN_STEPS",
+            "storage:link, migrate --force,optimize:clear",
+        )],
+    );
+
+    assert!(output.status.success());
+    fake.assert_calls_in_order(&[
+        "php artisan storage:link",
+        "php artisan migrate --force",
+        "php artisan optimize:clear",
+    ]);
+    assert!(
+        !fake
+            .calls()
+            .iter()
+            .any(|call| call.contains("artisan config:clear"))
+    );
+}
+
+#[test]
+fn rejects_invalid_artisan_steps() {
+    let fake = FakeDockerRun::new("artisan-steps-invalid");
+    let spec = spec_path();
+    let output = fake.run_with_env(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+        &[("ARTISAN_STEPS", "migrate; rm -rf /")],
+    );
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("ARTISAN_STEPS contém um comando inválido")
+    );
+    assert!(!fake.calls().iter().any(|call| call.contains("artisan")));
+}
+
+#[test]
+fn refuses_migrate_against_remote_db_host() {
+    let fake = FakeDockerRun::new("remote-db");
+    let spec = spec_path();
+    let remote_env = format!("{}/remote-db.env", FIXTURES_DIR);
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+
+    let output = fake.run_with_env(&args, None, &[("FAKE_DOCKER_ENV", &remote_env)]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("db.example.com"));
+    assert!(
+        !fake
+            .calls()
+            .iter()
+            .any(|call| call.contains("artisan migrate"))
+    );
+
+    let allowed = [&args[..], &["--allow-remote-db"]].concat();
+    let output = fake.run_with_env(&allowed, None, &[("FAKE_DOCKER_ENV", &remote_env)]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fake.assert_calls_in_order(&["php artisan migrate --force"]);
+}
+
+#[test]
+fn prints_vhost_without_touching_docker_or_files() {
+    let fake = FakeDockerRun::new("print-vhost");
+    let spec = spec_path();
+    let output = fake.run(&["--from-spec", &spec, "--print-vhost", "--no-color"], None);
+
+    assert!(output.status.success());
+    // O stdout contém só o vhost; as mensagens de progresso vão para o stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.trim_start().starts_with("<VirtualHost"),
+        "stdout inesperado:\n{}",
+        stdout
+    );
+    assert!(stdout.contains("ServerName demo-app.test"));
+    assert!(!stdout.contains("Dev Container Laravel Maker"));
+
+    assert!(!fake.calls().iter().any(|call| call.contains("compose")));
+    assert!(
+        !fake
+            .root
+            .join("docker/apache/vhosts/demo-app.test.conf")
+            .exists()
+    );
+}
+
+#[test]
+fn refuses_host_already_registered_for_another_path() {
+    let fake = FakeDockerRun::new("registry-duplicate");
+    fs::write(
+        fake.root.join(".lara-registry"),
+        "demo-app\tdemo-app.test\t/srv/outro/demo-app\t\t0\n",
+    )
+    .unwrap();
+    let spec = spec_path();
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+
+    let output = fake.run(&args, None);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/srv/outro/demo-app"));
+    assert!(
+        !fake
+            .calls()
+            .iter()
+            .any(|call| call.contains("create-project"))
+    );
+
+    // Com --force a entrada antiga é substituída em vez de duplicada
+    let forced = [&args[..], &["--force"]].concat();
+    let output = fake.run(&forced, None);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let registry = fs::read_to_string(fake.root.join(".lara-registry")).unwrap();
+    assert_eq!(registry.lines().count(), 1, "registro:\n{}", registry);
+    assert!(!registry.contains("/srv/outro"));
+}
+
+#[test]
+fn rewrite_strategy_preserves_quoted_env_values() {
+    let fake = FakeDockerRun::new("quoted-env");
+    let spec = spec_path();
+    let quoted_env = format!("{}/quoted.env", FIXTURES_DIR);
+    let output = fake.run_with_env(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+        &[
+            ("FAKE_DOCKER_ENV", &quoted_env),
+            ("ENV_STRATEGY", "rewrite"),
+            ("DB_ROOT_PASSWORD", "p@ss # wo\"rd"),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let env = fs::read_to_string(fake.root.join("state/.env")).expect(".env não foi gravado");
+    assert!(env.contains("APP_NAME=\"My # App\"\n"), ".env:\n{}", env);
+    assert!(env.contains("MAIL_FROM_NAME=\"Equipe = Suporte\" # comentário depois das aspas\n"));
+    // A linha dentro do valor multilinha não é uma atribuição e continua intacta
+    assert!(env.contains("CERT_PEM=\"-----BEGIN-----\nDB_HOST=127.0.0.1\n-----END-----\"\n"));
+    assert!(env.contains("\nDB_HOST=mariadb\n"));
+    assert!(env.contains("DB_PASSWORD=\"p@ss # wo\\\"rd\"\n"));
+    assert!(env.contains("APP_URL=http://demo-app.test\n"));
+}
+
+#[test]
+fn compose_build_runs_before_up_even_when_stack_is_running() {
+    let fake = FakeDockerRun::new("compose-build");
+    fs::write(fake.root.join("state/running"), "").unwrap();
+    let spec = spec_path();
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+
+    let output = fake.run(&[&args[..], &["--build-no-cache"]].concat(), None);
+    assert!(output.status.success());
+    fake.assert_calls_in_order(&["compose build --no-cache", "compose up -d"]);
+
+    let output = fake.run(
+        &[&args[..], &["--compose-build"]].concat(),
+        Some("compose build"),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'docker compose build' falhou"));
+}
+
+#[test]
+fn derives_project_name_from_git_branch() {
+    let fake = FakeDockerRun::new("name-from-git");
+    let git = Command::new("git")
+        .args(["init", "-q", "-b", "feature/Login_Page"])
+        .current_dir(&fake.root)
+        .status();
+    if !git.is_ok_and(|status| status.success()) {
+        eprintln!("git indisponível; teste ignorado");
+        return;
+    }
+
+    let output = fake.run(
+        &["--name-from-git", "--yes", "--no-hosts", "--no-color"],
+        None,
+    );
+    // A verificação final compara com o .env fixo do docker falso (demo-app); aqui importa só o nome
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("derivado do git: 'feature-login-page'")
+    );
+    fake.assert_calls_in_order(&["composer create-project laravel/laravel feature-login-page"]);
+}
+
+#[test]
+fn delete_with_data_drops_only_the_project_database_and_volumes() {
+    let fake = FakeDockerRun::new("delete-with-data");
+    fs::write(fake.root.join("state/running"), "").unwrap();
+    let project_dir = fake.root.join("src/demo-app");
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::copy(
+        Path::new(FIXTURES_DIR).join("project.env"),
+        project_dir.join(".env"),
+    )
+    .unwrap();
+    fs::write(
+        fake.root.join(".lara-registry"),
+        format!("demo-app\tdemo-app.test\t{}\t\t0\n", project_dir.display()),
+    )
+    .unwrap();
+
+    let output = fake.run(
+        &[
+            "delete",
+            "demo-app",
+            "--with-volumes",
+            "--yes",
+            "--no-color",
+        ],
+        None,
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    fake.assert_calls_in_order(&[
+        "SHOW DATABASES",
+        "DROP DATABASE IF EXISTS `demo-app`; DROP DATABASE IF EXISTS `demo-app_testing`;",
+        "volume rm demo-app_cache",
+    ]);
+    let calls = fake.calls();
+    assert!(!calls.iter().any(|call| call.contains("outro-app")));
+    assert!(
+        !calls
+            .iter()
+            .any(|call| call.contains("rm dev_container_mysql_data"))
+    );
+    assert!(
+        calls
+            .iter()
+            .any(|call| call.contains("label=com.docker.compose.project=demo-app"))
+    );
+    assert!(!calls.iter().any(|call| call.contains("rm demo-app_manual")));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Dados removidos:"));
+    assert!(stdout.contains("banco 'demo-app_testing' (demo-app)"));
+    assert!(!project_dir.exists());
+}
+
+#[test]
+fn advertised_url_omits_the_default_http_port() {
+    let spec = spec_path();
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+    for (port, expected) in [
+        ("80", "Domínio de acesso: http://demo-app.test\n"),
+        ("8080", "Domínio de acesso: http://demo-app.test:8080\n"),
+    ] {
+        let fake = FakeDockerRun::new(&format!("url-port-{}", port));
+        let output = fake.run_with_env(&args, None, &[("SERVER_PORT", port)]);
+        assert!(output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains(expected),
+            "esperado '{}' no stdout",
+            expected.trim()
+        );
+    }
+}
+
+#[test]
+fn batch_runs_specs_concurrently_and_restarts_apache_once() {
+    let fake = FakeDockerRun::new("batch");
+    let broken_spec = fake.root.join("broken-app.toml");
+    fs::write(
+        &broken_spec,
+        "name = \"broken-app\"\nlaravel_version = \"12\"\n",
+    )
+    .unwrap();
+    let spec = spec_path();
+    let output = fake.run(
+        &[
+            "batch",
+            &spec,
+            broken_spec.to_str().unwrap(),
+            "--concurrency",
+            "2",
+            "--no-hosts",
+            "--no-color",
+        ],
+        Some("create-project laravel/laravel broken-app"),
+    );
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("PROJETO"), "stdout:\n{}", stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("demo-app") && line.contains(" ok "))
+    );
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("broken-app") && line.contains("falhou"))
+    );
+    assert!(fake.root.join(".lara-batch/broken-app.log").exists());
+
+    // A stack sobe uma vez no processo pai e o Apache só reinicia no fim do batch
+    let calls = fake.calls();
+    let count = |fragment: &str| calls.iter().filter(|call| call.contains(fragment)).count();
+    assert_eq!(count("compose up -d"), 1);
+    assert_eq!(count("compose restart apache"), 1);
+    assert_eq!(count("composer create-project"), 2);
+    // O arquivo de lock fica, mas nenhum processo segura o flock depois do batch
+    let lock = fs::File::open(fake.root.join(".lara.lock")).unwrap();
+    assert!(lock.try_lock().is_ok());
+}
+
+#[test]
+fn check_update_uses_the_cached_release_tag() {
+    let fake = FakeDockerRun::new("check-update");
+    let cache_dir = fake.root.join(".cache/laravel-maker");
+    fs::create_dir_all(&cache_dir).unwrap();
+    fs::write(cache_dir.join("latest-release"), "v99.0.0\n").unwrap();
+
+    let output = fake.run(&["--check-update", "--offline", "--no-color"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Atualização disponível"));
+    assert!(stdout.contains("/releases/tag/v99.0.0"));
+    assert!(fake.calls().is_empty());
+}
+
+#[test]
+fn php_ini_overrides_are_written_to_the_project_user_ini() {
+    let fake = FakeDockerRun::new("php-ini");
+    let spec = spec_path();
+    let output = fake.run(
+        &[
+            "--from-spec",
+            &spec,
+            "--yes",
+            "--no-hosts",
+            "--no-color",
+            "--php-ini",
+            "memory_limit=512M",
+            "--php-ini",
+            "error_log=/tmp/php errors.log",
+        ],
+        None,
+    );
+    assert!(output.status.success());
+    let user_ini = fs::read_to_string(fake.root.join("state/.user.ini")).unwrap();
+    assert!(user_ini.contains("memory_limit = 512M\n"));
+    assert!(user_ini.contains("error_log = \"/tmp/php errors.log\"\n"));
+
+    let invalid = FakeDockerRun::new("php-ini-invalid");
+    let output = invalid.run(
+        &["--from-spec", &spec, "--yes", "--php-ini", "memory_limit"],
+        None,
+    );
+    assert!(!output.status.success());
+    assert!(invalid.calls().is_empty());
+}
+
+#[test]
+fn list_services_shows_running_containers_and_marks_the_stopped_ones() {
+    let fake = FakeDockerRun::new("list-services");
+    let output = fake.run(&["--list-services", "--no-color"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("php ") && line.contains("(não está rodando)"))
+    );
+
+    fs::write(fake.root.join("state/running"), "").unwrap();
+    let output = fake.run(&["--list-services", "--no-color"], None);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let php = stdout
+        .lines()
+        .find(|line| line.starts_with("php "))
+        .expect("linha do serviço php");
+    assert!(php.contains("fake_php"));
+    assert!(php.contains("--php-service, --php-container"));
+    assert!(
+        stdout.contains("(não está rodando)"),
+        "node e mariadb seguem parados"
+    );
+}
+
+#[test]
+fn skips_npm_and_vite_when_the_node_container_is_absent() {
+    let spec = spec_path();
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+
+    let fake = FakeDockerRun::new("no-node");
+    let output = fake.run_with_env(&args, None, &[("FAKE_DOCKER_ABSENT", "fake_node")]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("contêiner do Node (fake_node) não encontrado")
+    );
+    let calls = fake.calls();
+    assert!(!calls.iter().any(|call| call.contains("npm install")));
+    assert!(!calls.iter().any(|call| call.contains("vite.config.js")));
+
+    let strict = FakeDockerRun::new("no-node-required");
+    let mut strict_args = args.to_vec();
+    strict_args.push("--require-node");
+    let output = strict.run_with_env(&strict_args, None, &[("FAKE_DOCKER_ABSENT", "fake_node")]);
+    assert!(!output.status.success());
+    assert!(
+        !strict
+            .calls()
+            .iter()
+            .any(|call| call.contains("npm install"))
+    );
+}
+
+#[test]
+fn ipv6_adds_the_loopback_v6_line_to_the_hosts_hint() {
+    let fake = FakeDockerRun::new("ipv6-hosts");
+    let spec = spec_path();
+    let output = fake.run(
+        &[
+            "--from-spec",
+            &spec,
+            "--yes",
+            "--no-hosts",
+            "--ipv6",
+            "--no-color",
+        ],
+        None,
+    );
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("Adicione manualmente: 127.0.0.1 demo-app.test e ::1 demo-app.test")
+    );
+}
+
+#[test]
+fn dry_run_and_plan_only_list_actions_without_applying_them() {
+    let spec = spec_path();
+    let fake = FakeDockerRun::new("dry-run");
+    let output = fake.run(&["--from-spec", &spec, "--dry-run", "--no-color"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[compose_up] host: docker compose up -d"));
+    assert!(stdout.contains("[create_project]"));
+    assert!(stdout.contains("[hosts] adiciona ao /etc/hosts: 127.0.0.1 demo-app.test"));
+    assert!(
+        fake.calls()
+            .iter()
+            .all(|call| call.starts_with("ps ") || call.starts_with("compose config")),
+        "o plano só consulta o estado: {:?}",
+        fake.calls()
+    );
+    assert!(
+        !fake
+            .root
+            .join("docker/apache/vhosts/demo-app.test.conf")
+            .exists()
+    );
+
+    let output = fake.run(&["--from-spec", &spec, "--plan-only", "--ipv6"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("{\"project\":\"demo-app\",\"actions\":["));
+    assert!(stdout.contains("{\"step\":\"vhost\",\"type\":\"write_file\""));
+    assert!(stdout.contains("\"line\":\"::1 demo-app.test\""));
+}
+
+// (alvo, comando) de cada ação "run" do JSON do --plan-only
+fn planned_runs(plan_json: &str) -> Vec<(String, String)> {
+    let field = |action: &str, name: &str, next: &str| {
+        let start = action.find(&format!("\"{}\":\"", name))? + name.len() + 4;
+        let end = start + action[start..].find(&format!("\",\"{}\":", next))?;
+        Some(
+            action[start..end]
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+        )
+    };
+    plan_json
+        .split("{\"step\":")
+        .filter(|action| action.contains("\"type\":\"run\""))
+        .map(|action| {
+            (
+                field(action, "target", "command").unwrap(),
+                field(action, "command", "note").unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn plan_only_lists_the_commands_a_real_run_executes() {
+    let spec = spec_path();
+    let args = [
+        "--from-spec",
+        spec.as_str(),
+        "--yes",
+        "--no-hosts",
+        "--no-color",
+        "--no-scripts",
+        "--no-config-clear",
+        "--with-test-db",
+        "--optimize",
+    ];
+
+    let planned = FakeDockerRun::new("plan-matches-plan");
+    let output = planned.run(&[&args[..], &["--plan-only"]].concat(), None);
+    assert!(output.status.success());
+    let runs = planned_runs(&String::from_utf8_lossy(&output.stdout));
+    assert!(runs.len() >= 8, "{:?}", runs);
+    assert!(
+        !runs
+            .iter()
+            .any(|(_, command)| command.contains("config:clear"))
+    );
+
+    let applied = FakeDockerRun::new("plan-matches-apply");
+    let output = applied.run(&args, None);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Cada comando do plano aparece, na mesma ordem, entre as chamadas reais ao docker
+    let calls = applied.calls();
+    let mut remaining = calls.iter();
+    for (target, command) in &runs {
+        let found = remaining.any(|call| match command.strip_prefix("docker ") {
+            Some(host_command) if target == "host" => call == host_command,
+            _ => {
+                call.starts_with("exec ")
+                    && call.contains(&format!(" {} ", target))
+                    && call.ends_with(&format!(" {}", command))
+            }
+        });
+        assert!(
+            found,
+            "'{}: {}' não foi executado na ordem do plano:\n{}",
+            target,
+            command,
+            calls.join("\n")
+        );
+    }
+}
+
+#[test]
+fn finds_the_env_file_from_a_nested_project_directory() {
+    let fake = FakeDockerRun::new("nested-dir");
+    let nested = fake.root.join("src/demo-app/app/Http");
+    fs::create_dir_all(&nested).unwrap();
+    // O .env do Laravel no projeto não pode ser confundido com o do dev-container
+    fs::write(
+        fake.root.join("src/demo-app/.env"),
+        "CONTAINER_NAME=errado\n",
+    )
+    .unwrap();
+
+    let output = fake.run_in(&nested, &["--list-services", "--no-color"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Contêiner PHP: fake_php"));
+}
+
+#[test]
+fn offline_parallel_runs_neither_composer_update_nor_npm() {
+    let fake = FakeDockerRun::new("offline-parallel");
+    let spec = spec_path();
+    let output = fake.run(
+        &[
+            "--from-spec",
+            &spec,
+            "--yes",
+            "--no-hosts",
+            "--no-color",
+            "--offline",
+            "--parallel",
+        ],
+        None,
+    );
+    assert!(output.status.success());
+    let calls = fake.calls();
+    assert!(!calls.iter().any(|call| call.contains("composer update")));
+    assert!(!calls.iter().any(|call| call.contains("npm install")));
+}
+
+#[test]
+fn follows_a_symlinked_src_dir_and_warns_when_it_is_broken() {
+    let spec = spec_path();
+    let fake = FakeDockerRun::new("src-symlink");
+    let real_src = fake.root.join("real-src");
+    fs::create_dir_all(&real_src).unwrap();
+    fs::remove_dir_all(fake.root.join("src")).unwrap();
+    std::os::unix::fs::symlink(&real_src, fake.root.join("src")).unwrap();
+
+    let output = fake.run(&["--from-spec", &spec, "--dry-run", "--no-color"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!(
+        "é um link simbólico para {}. Usando o caminho real.",
+        fs::canonicalize(&real_src).unwrap().display()
+    )));
+
+    fs::remove_dir_all(&real_src).unwrap();
+    let output = fake.run(&["--from-spec", &spec, "--dry-run", "--no-color"], None);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("é um link simbólico quebrado."));
+}
+
+#[test]
+fn project_env_gets_the_internal_db_port_not_the_published_one() {
+    let fake = FakeDockerRun::new("db-port");
+    let spec = spec_path();
+    let stock_env = format!("{}/laravel-12.env", FIXTURES_DIR);
+    let output = fake.run_with_env(
+        &["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"],
+        None,
+        &[
+            ("FAKE_DOCKER_ENV", &stock_env),
+            ("ENV_STRATEGY", "rewrite"),
+            ("DB_PORT", "33060"),
+        ],
+    );
+    // O APP_KEY do .env de fábrica fica vazio no docker falso, então só o .env gravado importa
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("Arquivo .env configurado."),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let env = fs::read_to_string(fake.root.join("state/.env")).expect(".env não foi gravado");
+    assert!(env.contains("\nDB_PORT=3306\n"), ".env:\n{}", env);
+    assert!(!env.contains("33060"));
+}
+
+#[test]
+fn resume_skips_the_steps_recorded_by_the_failed_run() {
+    let fake = FakeDockerRun::new("resume-journal");
+    let spec = spec_path();
+    let args = ["--from-spec", &spec, "--yes", "--no-hosts", "--no-color"];
+    let journal = fake.root.join(".lara-applied/demo-app.test");
+
+    let output = fake.run(&args, Some("artisan migrate"));
+    assert!(!output.status.success());
+    let applied = fs::read_to_string(&journal).expect("diário não foi gravado");
+    assert!(
+        applied.lines().any(|step| step == "env_config"),
+        "{}",
+        applied
+    );
+    assert!(
+        !applied.lines().any(|step| step == "migrate"),
+        "{}",
+        applied
+    );
+
+    let mut resume_args = args.to_vec();
+    resume_args.push("--resume");
+    let output = fake.run(&resume_args, None);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "stdout:\n{}\nstderr:\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains(">> Etapa 'env_config' já aplicada, pulando (--resume)."));
+    assert!(!stdout.contains(">> Etapa 'migrate' já aplicada"));
+    fake.assert_calls_in_order(&["artisan migrate", "artisan migrate"]);
+    assert!(
+        !journal.exists(),
+        "o diário some depois de uma execução completa"
+    );
+}
+
+#[test]
+fn json_output_stays_alone_on_stdout() {
+    let fake = FakeDockerRun::new("json-stdout");
+    let spec = spec_path();
+
+    let output = fake.run(&["--env-report", "--json", "--no-color"], None);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.trim().starts_with('{') && stdout.trim().ends_with('}'),
+        "{}",
+        stdout
+    );
+
+    let output = fake.run(
+        &[
+            "--from-spec",
+            &spec,
+            "--yes",
+            "--no-hosts",
+            "--no-color",
+            "--json",
+        ],
+        None,
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(stdout.starts_with("{\"project\":"), "{}", stdout);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Rotina concluída com sucesso."));
+}